@@ -133,7 +133,7 @@ fn check_openapi_metadata(openapi: &Result<Value, String>, result: &mut Diagnost
             continue;
         }
 
-        for method in ["get", "post", "put", "delete"] {
+        for method in ["get", "post", "put", "delete", "patch", "head", "options"] {
             if let Some(operation) = item.get(method) {
                 let has_summary = operation.get("summary").is_some();
                 let has_description = operation.get("description").is_some();
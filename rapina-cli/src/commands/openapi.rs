@@ -183,7 +183,12 @@ fn detect_breaking_changes(base: &Value, current: &Value) -> ChangeReport {
                     // Check for removed methods
                     check_removed_methods(path, base_item, current_item, &mut report);
                     // Check for response schema changes
-                    check_response_changes(path, base_item, current_item, &mut report);
+                    check_response_changes(
+                        path,
+                        (base, base_item),
+                        (current, current_item),
+                        &mut report,
+                    );
                 }
             }
         }
@@ -208,7 +213,7 @@ fn check_removed_methods(
     current_item: &Value,
     report: &mut ChangeReport,
 ) {
-    let methods = ["get", "post", "put", "delete", "patch"];
+    let methods = ["get", "post", "put", "delete", "patch", "head", "options"];
 
     for method in methods {
         let base_has = base_item.get(method).is_some();
@@ -229,13 +234,16 @@ fn check_removed_methods(
 }
 
 /// Check for breaking changes in response schemas.
+///
+/// Each path item comes with the spec it belongs to, so schemas
+/// referenced from `components/schemas` can be resolved.
 fn check_response_changes(
     path: &str,
-    base_item: &Value,
-    current_item: &Value,
+    (base, base_item): (&Value, &Value),
+    (current, current_item): (&Value, &Value),
     report: &mut ChangeReport,
 ) {
-    let methods = ["get", "post", "put", "delete", "patch"];
+    let methods = ["get", "post", "put", "delete", "patch", "head", "options"];
 
     for method in methods {
         if let (Some(base_op), Some(current_op)) = (base_item.get(method), current_item.get(method))
@@ -257,8 +265,8 @@ fn check_response_changes(
             ) {
                 check_schema_changes(
                     &format!("{} {}", method.to_uppercase(), path),
-                    base_resp,
-                    current_resp,
+                    resolve_schema(base, base_resp),
+                    resolve_schema(current, current_resp),
                     report,
                 );
             }
@@ -266,6 +274,16 @@ fn check_response_changes(
     }
 }
 
+/// Follow a `$ref` into the spec's `components/schemas`.
+fn resolve_schema<'a>(spec: &'a Value, schema: &'a Value) -> &'a Value {
+    schema
+        .get("$ref")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.strip_prefix("#/components/schemas/"))
+        .and_then(|name| spec.get("components")?.get("schemas")?.get(name))
+        .unwrap_or(schema)
+}
+
 /// Check for breaking changes in schemas.
 fn check_schema_changes(
     context: &str,
@@ -378,4 +396,28 @@ mod tests {
         assert!(report.breaking.is_empty());
         assert!(report.non_breaking.is_empty());
     }
+
+    #[test]
+    fn test_detect_removed_field_behind_ref() {
+        let spec = |properties: Value| {
+            json!({
+                "paths": {
+                    "/users": { "get": { "responses": { "200": { "content": {
+                        "application/json": { "schema": { "$ref": "#/components/schemas/User" } }
+                    } } } } }
+                },
+                "components": { "schemas": { "User": { "type": "object", "properties": properties } } }
+            })
+        };
+        let base = spec(json!({ "id": {}, "email": {} }));
+        let current = spec(json!({ "id": {} }));
+
+        let report = detect_breaking_changes(&base, &current);
+        assert!(
+            report
+                .breaking
+                .iter()
+                .any(|c| c.contains("removed field 'email'"))
+        );
+    }
 }
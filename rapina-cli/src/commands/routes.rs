@@ -40,6 +40,7 @@ pub fn execute() -> Result<(), String> {
             "POST" => route.method.blue(),
             "PUT" => route.method.yellow(),
             "DELETE" => route.method.red(),
+            "PATCH" => route.method.magenta(),
             _ => route.method.normal(),
         };
         println!(
@@ -2,8 +2,10 @@
 
 use colored::Colorize;
 use notify_debouncer_mini::{DebounceEventResult, new_debouncer, notify::RecursiveMode};
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, mpsc};
@@ -14,9 +16,46 @@ use std::time::Duration;
 pub struct TestConfig {
     pub coverage: bool,
     pub watch: bool,
+    pub contract: bool,
+    pub update_snapshots: bool,
     pub filter: Option<String>,
 }
 
+/// Environment variable that turns on contract checks in `TestClient`.
+const CONTRACT_ENV: &str = "RAPINA_CONTRACT";
+/// Environment variable naming the file test clients report coverage to.
+const CONTRACT_REPORT_ENV: &str = "RAPINA_CONTRACT_REPORT";
+/// Environment variable that makes snapshot assertions write changed
+/// snapshots to `.snap.new` files instead of failing.
+const UPDATE_SNAPSHOTS_ENV: &str = "RAPINA_UPDATE_SNAPSHOTS";
+
+/// Coverage of a route's documented status codes, as reported by
+/// `TestClient` in contract mode.
+#[derive(Debug, Deserialize, PartialEq)]
+struct RouteCoverage {
+    method: String,
+    path: String,
+    handler_name: String,
+    documented: BTreeSet<u16>,
+    observed: BTreeSet<u16>,
+}
+
+impl RouteCoverage {
+    /// Documented status codes no test response had. Any `2xx` response
+    /// covers the documented `200`.
+    fn missing(&self) -> Vec<u16> {
+        self.documented
+            .iter()
+            .copied()
+            .filter(|&documented| {
+                !self.observed.iter().any(|&observed| {
+                    observed == documented || (documented == 200 && (200..300).contains(&observed))
+                })
+            })
+            .collect()
+    }
+}
+
 /// Test results summary.
 #[derive(Default)]
 struct TestSummary {
@@ -91,9 +130,24 @@ fn run_tests(config: &TestConfig) -> Result<(), String> {
     );
     println!();
 
+    let report = if config.contract {
+        Some(prepare_contract_report()?)
+    } else {
+        None
+    };
+
     let (cmd, args) = build_test_command(config);
 
-    let mut child = Command::new(&cmd)
+    let mut command = Command::new(&cmd);
+    if let Some(report) = &report {
+        command
+            .env(CONTRACT_ENV, "1")
+            .env(CONTRACT_REPORT_ENV, report);
+    }
+    if config.update_snapshots {
+        command.env(UPDATE_SNAPSHOTS_ENV, "1");
+    }
+    let mut child = command
         .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -124,6 +178,14 @@ fn run_tests(config: &TestConfig) -> Result<(), String> {
     println!();
     print_summary(&summary, status.success());
 
+    if let Some(report) = &report {
+        print_contract_coverage(report)?;
+    }
+
+    if config.update_snapshots {
+        review_snapshots()?;
+    }
+
     if status.success() {
         Ok(())
     } else {
@@ -236,6 +298,319 @@ fn build_test_command(config: &TestConfig) -> (String, Vec<String>) {
     ("cargo".to_string(), args)
 }
 
+/// Creates the directory of the contract report and removes the previous one.
+fn prepare_contract_report() -> Result<PathBuf, String> {
+    let dir = std::env::current_dir()
+        .map_err(|e| format!("Failed to get current directory: {}", e))?
+        .join("target")
+        .join("rapina");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let report = dir.join("contract.jsonl");
+    if report.exists() {
+        std::fs::remove_file(&report)
+            .map_err(|e| format!("Failed to remove old contract report: {}", e))?;
+    }
+    Ok(report)
+}
+
+/// Merges the coverage reported by every test client, one route per entry.
+fn merge_coverage(report: &str) -> Vec<RouteCoverage> {
+    let mut routes: BTreeMap<(String, String), RouteCoverage> = BTreeMap::new();
+    for line in report.lines() {
+        let Ok(coverage) = serde_json::from_str::<RouteCoverage>(line) else {
+            continue;
+        };
+        let key = (coverage.path.clone(), coverage.method.clone());
+        match routes.get_mut(&key) {
+            Some(route) => {
+                route.documented.extend(coverage.documented);
+                route.observed.extend(coverage.observed);
+            }
+            None => {
+                routes.insert(key, coverage);
+            }
+        }
+    }
+    routes.into_values().collect()
+}
+
+/// Print which documented status codes the tests exercised, per route.
+fn print_contract_coverage(report: &Path) -> Result<(), String> {
+    println!(
+        "{} Contract coverage",
+        "INFO".custom_color(colors::blue()).bold()
+    );
+    println!();
+
+    let routes = match std::fs::read_to_string(report) {
+        Ok(content) => merge_coverage(&content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("Failed to read contract report: {}", e)),
+    };
+    if routes.is_empty() {
+        println!(
+            "  {} No test client checked responses against the contract",
+            "⚠".custom_color(colors::yellow())
+        );
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "  {:<7} {:<30} {:<20} {:<8} {}",
+        "METHOD".bold(),
+        "PATH".bold(),
+        "HANDLER".bold(),
+        "COVERED".bold(),
+        "MISSING".bold()
+    );
+
+    let mut documented = 0;
+    let mut covered = 0;
+    for route in &routes {
+        let missing = route.missing();
+        let route_covered = route.documented.len() - missing.len();
+        documented += route.documented.len();
+        covered += route_covered;
+
+        let ratio = format!("{}/{}", route_covered, route.documented.len());
+        let (ratio, missing) = if missing.is_empty() {
+            (
+                ratio.custom_color(colors::green()),
+                "-".custom_color(colors::subtext()),
+            )
+        } else {
+            let missing = missing
+                .iter()
+                .map(u16::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            (
+                ratio.custom_color(colors::yellow()),
+                missing.custom_color(colors::yellow()),
+            )
+        };
+        println!(
+            "  {:<7} {:<30} {:<20} {:<8} {}",
+            route.method,
+            route.path,
+            route.handler_name.custom_color(colors::subtext()),
+            ratio,
+            missing
+        );
+    }
+
+    println!();
+    println!(
+        "  {} of {} documented responses covered across {} route(s)",
+        covered.to_string().bold(),
+        documented,
+        routes.len()
+    );
+    println!();
+    Ok(())
+}
+
+/// What to do with a pending snapshot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Review {
+    Accept,
+    AcceptAll,
+    Reject,
+    Skip,
+    Quit,
+}
+
+/// Parse an answer to the review prompt.
+fn parse_review(answer: &str) -> Option<Review> {
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => Some(Review::Accept),
+        "a" | "all" => Some(Review::AcceptAll),
+        "n" | "no" => Some(Review::Reject),
+        "s" | "skip" => Some(Review::Skip),
+        "q" | "quit" => Some(Review::Quit),
+        _ => None,
+    }
+}
+
+/// Ask what to do with a pending snapshot. The end of input quits.
+fn prompt_review() -> Result<Review, String> {
+    let stdin = std::io::stdin();
+    loop {
+        print!("  Accept? [y]es, [n]o, [s]kip, [a]ll, [q]uit: ");
+        std::io::stdout()
+            .flush()
+            .map_err(|e| format!("Failed to write prompt: {}", e))?;
+
+        let mut answer = String::new();
+        let read = stdin
+            .lock()
+            .read_line(&mut answer)
+            .map_err(|e| format!("Failed to read answer: {}", e))?;
+        if read == 0 {
+            println!();
+            return Ok(Review::Quit);
+        }
+        if let Some(review) = parse_review(&answer) {
+            return Ok(review);
+        }
+    }
+}
+
+/// Collect the pending `.snap.new` files under `dir`, skipping build
+/// output and hidden directories.
+fn find_pending_snapshots(dir: &Path, pending: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if path.is_dir() {
+            if name != "target" && !name.starts_with('.') {
+                find_pending_snapshots(&path, pending);
+            }
+        } else if name.ends_with(".snap.new") {
+            pending.push(path);
+        }
+    }
+}
+
+/// Show each new or changed snapshot the tests wrote and ask whether to
+/// accept it.
+fn review_snapshots() -> Result<(), String> {
+    println!(
+        "{} Snapshot review",
+        "INFO".custom_color(colors::blue()).bold()
+    );
+    println!();
+
+    let mut pending = Vec::new();
+    find_pending_snapshots(Path::new("."), &mut pending);
+    pending.sort();
+    if pending.is_empty() {
+        println!(
+            "  {} All snapshots are up to date",
+            "✓".custom_color(colors::green())
+        );
+        println!();
+        return Ok(());
+    }
+
+    let (mut accepted, mut rejected) = (0, 0);
+    let mut accept_all = false;
+    for new_path in &pending {
+        let path = new_path.with_extension("");
+        let new = std::fs::read_to_string(new_path)
+            .map_err(|e| format!("Failed to read {}: {}", new_path.display(), e))?;
+        let old = std::fs::read_to_string(&path).ok();
+
+        let label = if old.is_some() { "Changed" } else { "New" };
+        println!(
+            "  {} {}",
+            label.custom_color(colors::yellow()).bold(),
+            path.strip_prefix(".").unwrap_or(&path).display()
+        );
+        println!();
+        print_snapshot_diff(old.as_deref().unwrap_or(""), &new);
+        println!();
+
+        let review = if accept_all {
+            Review::Accept
+        } else {
+            prompt_review()?
+        };
+        match review {
+            Review::Accept | Review::AcceptAll => {
+                std::fs::rename(new_path, &path)
+                    .map_err(|e| format!("Failed to accept {}: {}", path.display(), e))?;
+                accepted += 1;
+                accept_all |= review == Review::AcceptAll;
+            }
+            Review::Reject => {
+                std::fs::remove_file(new_path)
+                    .map_err(|e| format!("Failed to reject {}: {}", path.display(), e))?;
+                rejected += 1;
+            }
+            Review::Skip => {}
+            Review::Quit => break,
+        }
+        println!();
+    }
+
+    println!(
+        "  {} accepted, {} rejected, {} left for review",
+        accepted.to_string().custom_color(colors::green()).bold(),
+        rejected.to_string().custom_color(colors::red()),
+        pending.len() - accepted - rejected
+    );
+    println!();
+    Ok(())
+}
+
+/// A line of a snapshot diff.
+#[derive(Debug, PartialEq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Match up the lines of two snapshots by their longest common subsequence.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// Print a snapshot diff, removed lines in red and added ones in green.
+fn print_snapshot_diff(old: &str, new: &str) {
+    for line in diff_lines(old, new) {
+        match line {
+            DiffLine::Same(line) => {
+                println!("    {}", line.custom_color(colors::subtext()))
+            }
+            DiffLine::Removed(line) => {
+                println!("  {} {}", "-".red(), line.custom_color(colors::red()))
+            }
+            DiffLine::Added(line) => {
+                println!("  {} {}", "+".green(), line.custom_color(colors::green()))
+            }
+        }
+    }
+}
+
 /// Process a line of test output.
 fn process_test_line(line: &str, summary: &mut TestSummary) {
     // Parse test result lines
@@ -347,3 +722,52 @@ mod colors {
         CustomColor::new(137, 180, 250)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_coverage() {
+        let report = r#"{"method":"GET","path":"/users/:id","handler_name":"get_user","documented":[200,404],"observed":[200]}
+{"method":"GET","path":"/users/:id","handler_name":"get_user","documented":[200,404,401],"observed":[401]}
+not json
+{"method":"POST","path":"/users","handler_name":"create_user","documented":[200],"observed":[201]}
+"#;
+        let routes = merge_coverage(report);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].handler_name, "create_user");
+        assert!(routes[0].missing().is_empty());
+        assert_eq!(routes[1].documented, BTreeSet::from([200, 401, 404]));
+        assert_eq!(routes[1].observed, BTreeSet::from([200, 401]));
+        assert_eq!(routes[1].missing(), vec![404]);
+    }
+
+    #[test]
+    fn test_parse_review() {
+        assert_eq!(parse_review("y\n"), Some(Review::Accept));
+        assert_eq!(parse_review(" All "), Some(Review::AcceptAll));
+        assert_eq!(parse_review("n"), Some(Review::Reject));
+        assert_eq!(parse_review("s"), Some(Review::Skip));
+        assert_eq!(parse_review("q"), Some(Review::Quit));
+        assert_eq!(parse_review("maybe"), None);
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "200 OK\n\n{\n  \"name\": \"Ann\"\n}\n";
+        let new = "200 OK\n\n{\n  \"name\": \"Bob\"\n}\n";
+        assert_eq!(
+            diff_lines(old, new),
+            vec![
+                DiffLine::Same("200 OK"),
+                DiffLine::Same(""),
+                DiffLine::Same("{"),
+                DiffLine::Removed("  \"name\": \"Ann\""),
+                DiffLine::Added("  \"name\": \"Bob\""),
+                DiffLine::Same("}"),
+            ]
+        );
+        assert_eq!(diff_lines("", "a\n"), vec![DiffLine::Added("a")]);
+    }
+}
@@ -56,6 +56,14 @@ enum Commands {
         /// Watch for changes and re-run tests
         #[arg(short, long)]
         watch: bool,
+        /// Check responses against the documented API contract and report
+        /// per-route coverage of documented status codes
+        #[arg(long)]
+        contract: bool,
+        /// Review new and changed response snapshots and accept or reject
+        /// each change
+        #[arg(long)]
+        update_snapshots: bool,
         /// Filter tests by name
         filter: Option<String>,
     },
@@ -158,11 +166,15 @@ fn main() {
         Some(Commands::Test {
             coverage,
             watch,
+            contract,
+            update_snapshots,
             filter,
         }) => {
             let config = commands::test::TestConfig {
                 coverage,
                 watch,
+                contract,
+                update_snapshots,
                 filter,
             };
             if let Err(e) = commands::test::execute(config) {
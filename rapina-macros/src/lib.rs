@@ -24,11 +24,82 @@ pub fn delete(attr: TokenStream, item: TokenStream) -> TokenStream {
     route_macro(attr, item)
 }
 
+#[proc_macro_attribute]
+pub fn patch(attr: TokenStream, item: TokenStream) -> TokenStream {
+    route_macro(attr, item)
+}
+
+#[proc_macro_attribute]
+pub fn head(attr: TokenStream, item: TokenStream) -> TokenStream {
+    route_macro(attr, item)
+}
+
+#[proc_macro_attribute]
+pub fn options(attr: TokenStream, item: TokenStream) -> TokenStream {
+    route_macro(attr, item)
+}
+
+/// Defines a WebSocket route.
+///
+/// The function receives the upgraded `WebSocket` once the handshake
+/// completes. Any other arguments must be parts-only extractors (such as
+/// `CurrentUser` or `State<T>`); they run against the upgrade request, so
+/// a failing extractor rejects the handshake. Register the handler as a
+/// `GET` route.
+///
+/// # Example
+///
+/// ```ignore
+/// use rapina::prelude::*;
+/// use rapina::websocket::{Message, WebSocket};
+///
+/// #[ws("/chat")]
+/// async fn chat(mut socket: WebSocket, user: CurrentUser) {
+///     let _ = socket.send(Message::Text(format!("hello {}", user.id))).await;
+/// }
+///
+/// let router = Router::new().get("/chat", chat);
+/// ```
+#[proc_macro_attribute]
+pub fn ws(attr: TokenStream, item: TokenStream) -> TokenStream {
+    ws_macro_core(attr.into(), item.into()).into()
+}
+
+/// Tags a route in the OpenAPI spec.
+///
+/// Takes one or more tag names. Use it together with a route macro, in
+/// either order. The handler's doc comment becomes the operation's
+/// summary (first line) and description (the rest). `#[deprecated]`
+/// marks the operation as deprecated, with its note appended to the
+/// description, and `#[operation_id = "..."]`,
+/// placed below the route macro, overrides the operationId.
+///
+/// # Example
+///
+/// ```ignore
+/// use rapina::prelude::*;
+///
+/// /// List users
+/// ///
+/// /// Returns users ordered by creation date.
+/// #[tag("Users")]
+/// #[get("/users")]
+/// #[operation_id = "users.list"]
+/// async fn list_users() -> Json<Vec<User>> {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn tag(attr: TokenStream, item: TokenStream) -> TokenStream {
+    tag_macro_core(attr.into(), item.into()).into()
+}
+
 /// Marks a route as public (no authentication required).
 ///
 /// When authentication is enabled via `Rapina::with_auth()`, all routes
 /// require a valid JWT token by default. Use `#[public]` to allow
-/// unauthenticated access to specific routes.
+/// unauthenticated access to specific routes. Public routes are also
+/// documented without a security requirement in the OpenAPI spec.
 ///
 /// # Example
 ///
@@ -50,27 +121,64 @@ pub fn delete(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Note: Routes starting with `/__rapina` are automatically public.
 #[proc_macro_attribute]
-pub fn public(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    // The #[public] attribute is a marker that doesn't modify the item.
-    // It's used by the application to register public routes.
-    // We just pass through the item unchanged.
-    item
+pub fn public(attr: TokenStream, item: TokenStream) -> TokenStream {
+    public_macro_core(attr.into(), item.into()).into()
+}
+
+/// Attaches middleware to a single route handler.
+///
+/// Takes one or more expressions evaluating to
+/// [`Middleware`](rapina::middleware::Middleware) values. Use it together
+/// with a route macro, in either order. The middleware runs after global
+/// middleware and router layers, in the order it's listed.
+///
+/// # Example
+///
+/// ```ignore
+/// use std::time::Duration;
+/// use rapina::middleware::TimeoutMiddleware;
+/// use rapina::prelude::*;
+///
+/// #[post("/admin/reindex")]
+/// #[middleware(TimeoutMiddleware::new(Duration::from_secs(600)))]
+/// async fn reindex() -> StatusCode {
+///     StatusCode::ACCEPTED
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn middleware(attr: TokenStream, item: TokenStream) -> TokenStream {
+    middleware_macro_core(attr.into(), item.into()).into()
 }
 
 fn route_macro_core(
     attr: proc_macro2::TokenStream,
     item: proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
-    let _path: LitStr = syn::parse2(attr).expect("expected path as string literal");
+    let path: LitStr = syn::parse2(attr).expect("expected path as string literal");
     let mut func: ItemFn = syn::parse2(item).expect("expected function");
 
     let func_name = &func.sig.ident;
     let func_name_str = func_name.to_string();
     let func_vis = &func.vis;
 
+    let path_impl = path_helper_impl(&path, func_name, func_vis);
+
     // Extract #[errors(ErrorType)] attribute if present
     let error_type = extract_errors_attr(&mut func.attrs);
 
+    let public_impl = public_impl(&mut func.attrs);
+    let middleware_impl = middleware_impl(&mut func.attrs);
+
+    // Doc comments, #[tag], #[deprecated] and #[operation_id] for OpenAPI.
+    // Doc comments and #[deprecated] also go on the generated handler
+    let docs = extract_route_docs(&mut func.attrs);
+    let docs_impl = docs.to_impl();
+    let doc_attrs: Vec<_> = func
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("deprecated"))
+        .collect();
+
     let error_responses_impl = if let Some(err_type) = &error_type {
         quote! {
             fn error_responses() -> Vec<rapina::error::ErrorVariant> {
@@ -89,6 +197,16 @@ fn route_macro_core(
                     Some(serde_json::to_value(rapina::schemars::schema_for!(#inner_type)).unwrap())
                 }
             }
+        } else if is_sse_return_type(return_type) {
+            quote! {
+                fn response_schema() -> Option<serde_json::Value> {
+                    Some(serde_json::json!({ "type": "string" }))
+                }
+
+                fn response_content_type() -> Option<&'static str> {
+                    Some("text/event-stream")
+                }
+            }
         } else {
             quote! {}
         }
@@ -98,6 +216,8 @@ fn route_macro_core(
 
     let args: Vec<_> = func.sig.inputs.iter().collect();
 
+    let request_schema_impl = request_schema_impl(&args);
+
     // Extract return type for type annotation (helps with type inference in async blocks)
     let return_type_annotation = match &func.sig.output {
         syn::ReturnType::Type(_, ty) => quote! { : #ty },
@@ -114,25 +234,26 @@ fn route_macro_core(
         }
     } else {
         let mut parts_extractions = Vec::new();
-        let mut body_extractors: Vec<(syn::Ident, Box<syn::Type>)> = Vec::new();
+        let mut body_extractors: Vec<(syn::PatIdent, Box<syn::Type>)> = Vec::new();
 
         for arg in &args {
             if let FnArg::Typed(pat_type) = arg
                 && let Pat::Ident(pat_ident) = &*pat_type.pat
             {
                 let arg_name = &pat_ident.ident;
+                let arg_mut = &pat_ident.mutability;
                 let arg_type = &pat_type.ty;
 
                 let type_str = quote!(#arg_type).to_string();
                 if is_parts_only_extractor(&type_str) {
                     parts_extractions.push(quote! {
-                        let #arg_name = match <#arg_type as rapina::extract::FromRequestParts>::from_request_parts(&__rapina_parts, &__rapina_params, &__rapina_state).await {
+                        let #arg_mut #arg_name = match <#arg_type as rapina::extract::FromRequestParts>::from_request_parts(&__rapina_parts, &__rapina_params, &__rapina_state).await {
                             Ok(v) => v,
                             Err(e) => return rapina::response::IntoResponse::into_response(e),
                         };
                     });
                 } else {
-                    body_extractors.push((arg_name.clone(), arg_type.clone()));
+                    body_extractors.push((pat_ident.clone(), arg_type.clone()));
                 }
             }
         }
@@ -149,7 +270,10 @@ fn route_macro_core(
                 };
             }
         } else {
-            let names: Vec<_> = body_extractors.iter().map(|(n, _)| n.to_string()).collect();
+            let names: Vec<_> = body_extractors
+                .iter()
+                .map(|(n, _)| n.ident.to_string())
+                .collect();
             panic!(
                 "Multiple body-consuming extractors are not supported: {}. Only one extractor can consume the request body.",
                 names.join(", ")
@@ -169,19 +293,25 @@ fn route_macro_core(
 
     // Generate the struct and Handler impl
     quote! {
+        #(#doc_attrs)*
         #[derive(Clone, Copy)]
         #[allow(non_camel_case_types)]
         #func_vis struct #func_name;
 
+        #[allow(deprecated)]
         impl rapina::handler::Handler for #func_name {
             const NAME: &'static str = #func_name_str;
 
             #response_schema_impl
+            #request_schema_impl
             #error_responses_impl
+            #docs_impl
+            #public_impl
+            #middleware_impl
 
             fn call(
                 &self,
-                __rapina_req: rapina::hyper::Request<rapina::hyper::body::Incoming>,
+                __rapina_req: rapina::hyper::Request<rapina::extract::RequestBody>,
                 __rapina_params: rapina::extract::PathParams,
                 __rapina_state: std::sync::Arc<rapina::state::AppState>,
             ) -> std::pin::Pin<Box<dyn std::future::Future<Output = rapina::hyper::Response<rapina::response::BoxBody>> + Send>> {
@@ -190,6 +320,183 @@ fn route_macro_core(
                 })
             }
         }
+
+        #path_impl
+    }
+}
+
+/// `Handler::is_public` for a handler marked `#[public]`, which skips
+/// authentication.
+fn public_impl(attrs: &mut Vec<syn::Attribute>) -> proc_macro2::TokenStream {
+    if extract_public_attr(attrs) {
+        quote! {
+            fn is_public() -> bool {
+                true
+            }
+        }
+    } else {
+        quote! {}
+    }
+}
+
+/// `Handler::middlewares` for the handler's `#[middleware(...)]`
+/// attributes, which run around this handler only.
+fn middleware_impl(attrs: &mut Vec<syn::Attribute>) -> proc_macro2::TokenStream {
+    let middlewares = extract_middleware_attrs(attrs);
+    if middlewares.is_empty() {
+        return quote! {};
+    }
+    quote! {
+        fn middlewares() -> Vec<std::sync::Arc<dyn rapina::middleware::Middleware>> {
+            vec![#(std::sync::Arc::new(#middlewares) as std::sync::Arc<dyn rapina::middleware::Middleware>),*]
+        }
+    }
+}
+
+/// Generates `handler::path(..)`, taking the route parameters in order.
+///
+/// Required parameters take any `Display` value, optional ones an `Option`.
+/// The path is the one given to the macro, without any group prefix.
+fn path_helper_impl(
+    path: &LitStr,
+    func_name: &syn::Ident,
+    vis: &syn::Visibility,
+) -> proc_macro2::TokenStream {
+    let pattern = path.value();
+    let mut params = Vec::new();
+    let mut values = Vec::new();
+
+    for segment in pattern.split('/') {
+        let (name, optional) = if let Some(name) = segment.strip_prefix('*') {
+            (name, false)
+        } else if let Some(param) = segment.strip_prefix(':') {
+            let (param, optional) = match param.strip_suffix('?') {
+                Some(param) => (param, true),
+                None => (param, false),
+            };
+            (param.split('<').next().unwrap_or(param), optional)
+        } else {
+            continue;
+        };
+
+        let ident = syn::parse_str::<syn::Ident>(name)
+            .unwrap_or_else(|_| syn::Ident::new_raw(name, path.span()));
+        if optional {
+            params.push(quote! { #ident: Option<&dyn std::fmt::Display> });
+            values.push(quote! { #ident.map(|v| v.to_string()) });
+        } else {
+            params.push(quote! { #ident: impl std::fmt::Display });
+            values.push(quote! { Some(#ident.to_string()) });
+        }
+    }
+
+    quote! {
+        #[allow(deprecated)]
+        impl #func_name {
+            /// Returns the path to this route with the given parameters,
+            /// percent-encoded.
+            ///
+            /// Panics if a value is `.` or `..`, which clients would resolve
+            /// to another route.
+            #[allow(dead_code)]
+            #vis fn path(#(#params),*) -> String {
+                rapina::router::typed_path(#pattern, &[#(#values),*])
+            }
+        }
+    }
+}
+
+fn ws_macro_core(
+    attr: proc_macro2::TokenStream,
+    item: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let path: LitStr = syn::parse2(attr).expect("expected path as string literal");
+    let mut func: ItemFn = syn::parse2(item).expect("expected function");
+
+    let func_name = &func.sig.ident;
+    let func_name_str = func_name.to_string();
+    let func_vis = &func.vis;
+
+    let path_impl = path_helper_impl(&path, func_name, func_vis);
+
+    let public_impl = public_impl(&mut func.attrs);
+    let middleware_impl = middleware_impl(&mut func.attrs);
+    let docs_impl = extract_route_docs(&mut func.attrs).to_impl();
+    let doc_attrs: Vec<_> = func
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("deprecated"))
+        .collect();
+
+    let mut socket = None;
+    let mut parts_extractions = Vec::new();
+
+    for arg in &func.sig.inputs {
+        if let FnArg::Typed(pat_type) = arg
+            && let Pat::Ident(pat_ident) = &*pat_type.pat
+        {
+            let arg_name = &pat_ident.ident;
+            let arg_mut = &pat_ident.mutability;
+            let arg_type = &pat_type.ty;
+
+            let type_str = quote!(#arg_type).to_string();
+            if type_str.ends_with("WebSocket") {
+                if socket.is_some() {
+                    panic!("#[ws] handlers take a single WebSocket argument");
+                }
+                socket = Some((pat_type.pat.clone(), arg_type.clone()));
+            } else if is_parts_only_extractor(&type_str) {
+                parts_extractions.push(quote! {
+                    let #arg_mut #arg_name = match <#arg_type as rapina::extract::FromRequestParts>::from_request_parts(&__rapina_parts, &__rapina_params, &__rapina_state).await {
+                        Ok(v) => v,
+                        Err(e) => return rapina::response::IntoResponse::into_response(e),
+                    };
+                });
+            } else {
+                panic!(
+                    "#[ws] handlers cannot consume the request body: {}",
+                    arg_name
+                );
+            }
+        }
+    }
+
+    let (socket_pat, socket_type) = socket.expect("#[ws] handlers must take a WebSocket argument");
+    let inner_block = &func.block;
+
+    quote! {
+        #(#doc_attrs)*
+        #[derive(Clone, Copy)]
+        #[allow(non_camel_case_types)]
+        #func_vis struct #func_name;
+
+        #[allow(deprecated)]
+        impl rapina::handler::Handler for #func_name {
+            const NAME: &'static str = #func_name_str;
+
+            #docs_impl
+            #public_impl
+            #middleware_impl
+
+            fn call(
+                &self,
+                __rapina_req: rapina::hyper::Request<rapina::extract::RequestBody>,
+                __rapina_params: rapina::extract::PathParams,
+                __rapina_state: std::sync::Arc<rapina::state::AppState>,
+            ) -> std::pin::Pin<Box<dyn std::future::Future<Output = rapina::hyper::Response<rapina::response::BoxBody>> + Send>> {
+                Box::pin(async move {
+                    let (__rapina_parts, _) = __rapina_req.into_parts();
+                    let __rapina_upgrade = match <rapina::websocket::WebSocketUpgrade as rapina::extract::FromRequestParts>::from_request_parts(&__rapina_parts, &__rapina_params, &__rapina_state).await {
+                        Ok(v) => v,
+                        Err(e) => return rapina::response::IntoResponse::into_response(e),
+                    };
+                    #(#parts_extractions)*
+                    __rapina_upgrade.on_upgrade(move |#socket_pat: #socket_type| async move #inner_block)
+                })
+            }
+        }
+
+        #path_impl
     }
 }
 
@@ -202,6 +509,10 @@ fn is_parts_only_extractor(type_str: &str) -> bool {
         || type_str.contains("CurrentUser")
         || type_str.contains("Db")
         || type_str.contains("Cookie")
+        || type_str.contains("LastEventId")
+        || type_str.contains("WebSocketUpgrade")
+        || type_str.contains("PeerCertificate")
+        || type_str.contains("UrlFor")
 }
 
 /// Extracts the inner type from Json<T> wrapper for schema generation
@@ -228,6 +539,122 @@ fn extract_json_inner_type(return_type: &syn::Type) -> Option<proc_macro2::Token
     None
 }
 
+/// Returns `T` when `ty` is `Name<T>`
+fn generic_inner_type<'a>(ty: &'a syn::Type, name: &str) -> Option<&'a syn::Type> {
+    if let syn::Type::Path(type_path) = ty
+        && let Some(last_segment) = type_path.path.segments.last()
+        && last_segment.ident == name
+        && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
+        && let Some(syn::GenericArgument::Type(inner_type)) = args.args.first()
+    {
+        return Some(inner_type);
+    }
+    None
+}
+
+/// Looks up the JSON Schema of `ty`, or `None` if it doesn't implement `JsonSchema`
+fn schema_probe(ty: &syn::Type) -> proc_macro2::TokenStream {
+    quote! {
+        {
+            use rapina::openapi::probe::{WithSchema as _, WithoutSchema as _};
+            (&rapina::openapi::probe::SchemaProbe::<#ty>::new()).schema()
+        }
+    }
+}
+
+/// Generates the request documentation methods for the handler's extractors
+fn request_schema_impl(args: &[&FnArg]) -> proc_macro2::TokenStream {
+    let mut body = None;
+    let mut query = None;
+    let mut path = None;
+
+    for arg in args {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let ty = generic_inner_type(&pat_type.ty, "Validated").unwrap_or(&pat_type.ty);
+
+        if let Some(inner_type) = generic_inner_type(ty, "Json") {
+            let schema = schema_probe(inner_type);
+            body = Some((schema, quote! { None }));
+        } else if let Some(inner_type) = generic_inner_type(ty, "Form") {
+            let schema = schema_probe(inner_type);
+            body = Some((schema, quote! { Some("application/x-www-form-urlencoded") }));
+        } else if let Some(inner_type) = generic_inner_type(ty, "MultipartForm") {
+            body = Some((
+                quote! { Some(<#inner_type as rapina::multipart::FromMultipart>::schema()) },
+                quote! { Some(rapina::multipart::MULTIPART_CONTENT_TYPE) },
+            ));
+        } else if let syn::Type::Path(type_path) = ty
+            && type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Multipart")
+        {
+            body = Some((
+                quote! { Some(serde_json::json!({ "type": "object" })) },
+                quote! { Some(rapina::multipart::MULTIPART_CONTENT_TYPE) },
+            ));
+        } else if let Some(inner_type) = generic_inner_type(ty, "Query") {
+            query.get_or_insert_with(|| schema_probe(inner_type));
+        } else if let Some(inner_type) = generic_inner_type(ty, "Path") {
+            path.get_or_insert_with(|| schema_probe(inner_type));
+        }
+    }
+
+    let body_impl = body.map(|(schema, content_type)| {
+        quote! {
+            fn request_schema() -> Option<serde_json::Value> {
+                #schema
+            }
+
+            fn request_content_type() -> Option<&'static str> {
+                #content_type
+            }
+        }
+    });
+    let query_impl = query.map(|schema| {
+        quote! {
+            fn query_schema() -> Option<serde_json::Value> {
+                #schema
+            }
+        }
+    });
+    let path_impl = path.map(|schema| {
+        quote! {
+            fn path_schema() -> Option<serde_json::Value> {
+                #schema
+            }
+        }
+    });
+
+    quote! {
+        #body_impl
+        #query_impl
+        #path_impl
+    }
+}
+
+/// Checks whether the return type is `Sse<S>` or `Result<Sse<S>>`
+fn is_sse_return_type(return_type: &syn::Type) -> bool {
+    if let syn::Type::Path(type_path) = return_type
+        && let Some(last_segment) = type_path.path.segments.last()
+    {
+        if last_segment.ident == "Sse" {
+            return true;
+        }
+
+        if last_segment.ident == "Result"
+            && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
+            && let Some(syn::GenericArgument::Type(ok_type)) = args.args.first()
+        {
+            return is_sse_return_type(ok_type);
+        }
+    }
+    false
+}
+
 /// Extract #[errors(ErrorType)] attribute from function attributes, removing it if found.
 fn extract_errors_attr(attrs: &mut Vec<syn::Attribute>) -> Option<syn::Type> {
     let idx = attrs
@@ -238,6 +665,237 @@ fn extract_errors_attr(attrs: &mut Vec<syn::Attribute>) -> Option<syn::Type> {
     Some(err_type)
 }
 
+fn extract_public_attr(attrs: &mut Vec<syn::Attribute>) -> bool {
+    let len = attrs.len();
+    attrs.retain(|attr| !attr.path().is_ident("public"));
+    attrs.len() != len
+}
+
+/// Removes all `#[middleware(...)]` attributes, returning their expressions in order.
+fn extract_middleware_attrs(attrs: &mut Vec<syn::Attribute>) -> Vec<syn::Expr> {
+    let mut middlewares = Vec::new();
+    attrs.retain(|attr| {
+        if !attr.path().is_ident("middleware") {
+            return true;
+        }
+        let exprs = attr
+            .parse_args_with(
+                syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+            )
+            .expect("expected #[middleware(expr, ...)]");
+        middlewares.extend(exprs);
+        false
+    });
+    middlewares
+}
+
+/// OpenAPI metadata collected from a handler's attributes
+#[derive(Default)]
+struct RouteDocs {
+    summary: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+    operation_id: Option<String>,
+}
+
+impl RouteDocs {
+    fn to_impl(&self) -> proc_macro2::TokenStream {
+        let mut items = Vec::new();
+        if let Some(summary) = &self.summary {
+            items.push(quote! {
+                fn summary() -> Option<&'static str> {
+                    Some(#summary)
+                }
+            });
+        }
+        if let Some(description) = &self.description {
+            items.push(quote! {
+                fn description() -> Option<&'static str> {
+                    Some(#description)
+                }
+            });
+        }
+        if !self.tags.is_empty() {
+            let tags = &self.tags;
+            items.push(quote! {
+                fn tags() -> &'static [&'static str] {
+                    &[#(#tags),*]
+                }
+            });
+        }
+        if self.deprecated {
+            items.push(quote! {
+                fn deprecated() -> bool {
+                    true
+                }
+            });
+        }
+        if let Some(operation_id) = &self.operation_id {
+            items.push(quote! {
+                fn operation_id() -> Option<&'static str> {
+                    Some(#operation_id)
+                }
+            });
+        }
+        quote! { #(#items)* }
+    }
+}
+
+/// Reads the doc comment and removes `#[tag]` and `#[operation_id]`
+/// attributes, which only describe the route.
+///
+/// The first line of the doc comment becomes the summary and the
+/// remaining lines the description, followed by the note of a
+/// `#[deprecated]` attribute. That attribute is kept, to be put on the
+/// generated handler.
+fn extract_route_docs(attrs: &mut Vec<syn::Attribute>) -> RouteDocs {
+    let mut docs = RouteDocs::default();
+    let mut lines = Vec::new();
+    let mut deprecation_note = None;
+
+    attrs.retain(|attr| {
+        if attr.path().is_ident("doc") {
+            if let syn::Meta::NameValue(nv) = &attr.meta
+                && let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(line),
+                    ..
+                }) = &nv.value
+            {
+                let line = line.value();
+                lines.push(
+                    line.strip_prefix(' ')
+                        .unwrap_or(&line)
+                        .trim_end()
+                        .to_string(),
+                );
+            }
+            true
+        } else if attr.path().is_ident("tag") {
+            let tags = attr
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<LitStr, syn::Token![,]>::parse_terminated,
+                )
+                .expect("expected #[tag(\"Name\")]");
+            docs.tags.extend(tags.iter().map(LitStr::value));
+            false
+        } else if attr.path().is_ident("operation_id") {
+            let value = match &attr.meta {
+                syn::Meta::NameValue(nv) => match &nv.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(id),
+                        ..
+                    }) => id.value(),
+                    _ => panic!("expected #[operation_id = \"...\"]"),
+                },
+                _ => panic!("expected #[operation_id = \"...\"]"),
+            };
+            docs.operation_id = Some(value);
+            false
+        } else if attr.path().is_ident("deprecated") {
+            docs.deprecated = true;
+            deprecation_note = deprecation_note_of(attr);
+            true
+        } else {
+            true
+        }
+    });
+
+    let mut lines = lines.into_iter().skip_while(|line| line.is_empty());
+    docs.summary = lines.next();
+    let mut description = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+    if let Some(note) = deprecation_note {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&format!("Deprecated: {}", note));
+    }
+    if !description.is_empty() {
+        docs.description = Some(description);
+    }
+
+    docs
+}
+
+/// The note of `#[deprecated = "..."]` or `#[deprecated(note = "...")]`.
+fn deprecation_note_of(attr: &syn::Attribute) -> Option<String> {
+    let string = |expr: &syn::Expr| match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(note),
+            ..
+        }) => Some(note.value()),
+        _ => None,
+    };
+    match &attr.meta {
+        syn::Meta::NameValue(nv) => string(&nv.value),
+        syn::Meta::List(_) => attr
+            .parse_args_with(
+                syn::punctuated::Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated,
+            )
+            .ok()?
+            .iter()
+            .find(|nv| nv.path.is_ident("note"))
+            .and_then(|nv| string(&nv.value)),
+        syn::Meta::Path(_) => None,
+    }
+}
+
+/// Returns whether the attribute is one of the route macros.
+fn is_route_attr(attr: &syn::Attribute) -> bool {
+    attr.path().segments.last().is_some_and(|segment| {
+        matches!(
+            segment.ident.to_string().as_str(),
+            "get" | "post" | "put" | "delete" | "patch" | "head" | "options" | "ws"
+        )
+    })
+}
+
+/// Moves `#[tag]` below the route attribute, so the route macro reads it
+/// regardless of the order they're written in.
+fn tag_macro_core(
+    attr: proc_macro2::TokenStream,
+    item: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut func: ItemFn = syn::parse2(item).expect("expected function");
+    if !func.attrs.iter().any(is_route_attr) {
+        panic!("#[tag] must be used on a route handler, together with #[get], #[post], etc.");
+    }
+    func.attrs.push(syn::parse_quote!(#[tag(#attr)]));
+    quote!(#func)
+}
+
+/// Moves `#[public]` below the route attribute, like [`tag_macro_core`].
+///
+/// Items without a route attribute are passed through unchanged.
+fn public_macro_core(
+    _attr: proc_macro2::TokenStream,
+    item: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let Ok(mut func) = syn::parse2::<ItemFn>(item.clone()) else {
+        return item;
+    };
+    if !func.attrs.iter().any(is_route_attr) {
+        return item;
+    }
+    func.attrs.push(syn::parse_quote!(#[public]));
+    quote!(#func)
+}
+
+/// Moves `#[middleware]` below the route attribute, like [`tag_macro_core`].
+fn middleware_macro_core(
+    attr: proc_macro2::TokenStream,
+    item: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut func: ItemFn = syn::parse2(item).expect("expected function");
+    if !func.attrs.iter().any(is_route_attr) {
+        panic!(
+            "#[middleware] must be used on a route handler, together with #[get], #[post], etc."
+        );
+    }
+    func.attrs.push(syn::parse_quote!(#[middleware(#attr)]));
+    quote!(#func)
+}
+
 fn route_macro(attr: TokenStream, item: TokenStream) -> TokenStream {
     route_macro_core(attr.into(), item.into()).into()
 }
@@ -250,6 +908,31 @@ pub fn derive_config(input: TokenStream) -> TokenStream {
     derive_config_impl(input.into()).into()
 }
 
+/// Derive macro for typed `multipart/form-data` bodies
+///
+/// Implements `rapina::multipart::FromMultipart`, so the struct can be
+/// extracted with `MultipartForm<T>`. Every field type must implement
+/// `FromMultipartField`: `UploadedFile`, `String`, numbers, `bool`, and
+/// `Option`/`Vec` of those. Use `#[multipart(rename = "name")]` when the
+/// form field name differs from the struct field.
+///
+/// # Example
+///
+/// ```ignore
+/// use rapina::multipart::{MultipartForm, UploadedFile};
+///
+/// #[derive(MultipartForm)]
+/// struct Upload {
+///     title: String,
+///     #[multipart(rename = "file")]
+///     attachments: Vec<UploadedFile>,
+/// }
+/// ```
+#[proc_macro_derive(MultipartForm, attributes(multipart))]
+pub fn derive_multipart_form(input: TokenStream) -> TokenStream {
+    derive_multipart_form_impl(input.into()).into()
+}
+
 /// Define database entities with Prisma-like syntax.
 ///
 /// This macro generates SeaORM entity definitions from a declarative syntax
@@ -399,9 +1082,93 @@ fn derive_config_impl(input: proc_macro2::TokenStream) -> proc_macro2::TokenStre
     }
 }
 
+fn derive_multipart_form_impl(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let input: syn::DeriveInput = syn::parse2(input).expect("expected struct");
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        syn::Data::Struct(data) => match &data.fields {
+            syn::Fields::Named(fields) => &fields.named,
+            _ => panic!("MultipartForm derive only supports structs with named fields"),
+        },
+        _ => panic!("MultipartForm derive only supports structs"),
+    };
+
+    let mut field_inits = Vec::new();
+    let mut schema_fields = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+
+        // Find #[multipart(rename = "name")] attribute
+        let mut form_name = field_name.to_string();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("multipart"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    form_name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"`"))
+                }
+            })
+            .expect("invalid #[multipart] attribute");
+        }
+
+        let form_name_lit = LitStr::new(&form_name, proc_macro2::Span::call_site());
+
+        field_inits.push(quote! {
+            #field_name: <#field_type as rapina::multipart::FromMultipartField>::from_field(
+                #form_name_lit,
+                __rapina_parts.remove(#form_name_lit).unwrap_or_default(),
+            )?
+        });
+        schema_fields.push(quote! {
+            __rapina_properties.insert(
+                #form_name_lit.to_string(),
+                <#field_type as rapina::multipart::FromMultipartField>::schema(),
+            );
+            if <#field_type as rapina::multipart::FromMultipartField>::required() {
+                __rapina_required.push(serde_json::Value::from(#form_name_lit));
+            }
+        });
+    }
+
+    quote! {
+        impl rapina::multipart::FromMultipart for #name {
+            async fn from_multipart(
+                multipart: rapina::multipart::Multipart,
+            ) -> std::result::Result<Self, rapina::error::Error> {
+                let mut __rapina_parts = multipart.into_parts().await?;
+                Ok(Self {
+                    #(#field_inits),*
+                })
+            }
+
+            fn schema() -> serde_json::Value {
+                let mut __rapina_properties = serde_json::Map::new();
+                let mut __rapina_required: Vec<serde_json::Value> = Vec::new();
+                #(#schema_fields)*
+                serde_json::json!({
+                    "type": "object",
+                    "properties": __rapina_properties,
+                    "required": __rapina_required,
+                })
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::route_macro_core;
+    use super::{
+        derive_multipart_form_impl, extract_route_docs, middleware_macro_core, public_macro_core,
+        route_macro_core, tag_macro_core, ws_macro_core,
+    };
     use quote::quote;
 
     #[test]
@@ -543,6 +1310,101 @@ mod tests {
         assert!(output_str.contains("UserError"));
     }
 
+    #[test]
+    fn test_sse_return_type_generates_event_stream_content_type() {
+        let path = quote!("/events");
+        let input = quote! {
+            async fn events() -> Sse<BoxStream<'static, Result<Event, Infallible>>> {
+                Sse::new(stream::empty().boxed())
+            }
+        };
+
+        let output = route_macro_core(path, input);
+        let output_str = output.to_string();
+
+        assert!(output_str.contains("fn response_content_type"));
+        assert!(output_str.contains("text/event-stream"));
+    }
+
+    #[test]
+    fn test_ws_macro_upgrades_connection() {
+        let path = quote!("/chat");
+        let input = quote! {
+            async fn chat(mut socket: WebSocket, user: CurrentUser) {
+                let _ = socket.recv().await;
+            }
+        };
+
+        let output = ws_macro_core(path, input);
+        let output_str = output.to_string();
+
+        assert!(output_str.contains("struct chat"));
+        assert!(output_str.contains("WebSocketUpgrade"));
+        assert!(output_str.contains("on_upgrade"));
+        assert!(output_str.contains("FromRequestParts > :: from_request_parts"));
+    }
+
+    #[test]
+    fn test_ws_macro_keeps_mutability_and_path_helper() {
+        let path = quote!("/rooms/:room");
+        let input = quote! {
+            async fn chat(socket: WebSocket, mut room: Path<String>) {}
+        };
+
+        let output_str = ws_macro_core(path, input).to_string();
+
+        assert!(output_str.contains("let mut room ="));
+        assert!(output_str.contains("fn path (room : impl std :: fmt :: Display) -> String"));
+        assert!(output_str.contains("rapina :: router :: typed_path (\"/rooms/:room\""));
+    }
+
+    #[test]
+    fn test_ws_macro_reads_route_attrs() {
+        // How `#[public]`, `#[tag]` and `#[middleware]` above `#[ws]` arrive
+        let input = quote! {
+            #[ws("/chat")]
+            async fn chat(socket: WebSocket) {}
+        };
+        let input = public_macro_core(quote!(), input);
+        let input = tag_macro_core(quote!("Chat"), input);
+        let input = middleware_macro_core(quote!(TimeoutMiddleware::new(DURATION)), input);
+
+        let mut func: syn::ItemFn = syn::parse2(input).unwrap();
+        let ws_attr = func.attrs.remove(0);
+        assert!(ws_attr.path().is_ident("ws"));
+        let func = quote!(#func);
+        let output_str = ws_macro_core(quote!("/chat"), func).to_string();
+
+        assert!(output_str.contains("fn is_public"));
+        assert!(output_str.contains("fn tags"));
+        assert!(output_str.contains("\"Chat\""));
+        assert!(output_str.contains("fn middlewares"));
+        assert!(output_str.contains("TimeoutMiddleware"));
+        assert!(!output_str.contains("# [public]"));
+    }
+
+    #[test]
+    #[should_panic(expected = "must take a WebSocket argument")]
+    fn test_ws_macro_requires_socket() {
+        let path = quote!("/chat");
+        let input = quote! {
+            async fn chat(user: CurrentUser) {}
+        };
+
+        ws_macro_core(path, input);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot consume the request body")]
+    fn test_ws_macro_rejects_body_extractors() {
+        let path = quote!("/chat");
+        let input = quote! {
+            async fn chat(socket: WebSocket, body: Json<String>) {}
+        };
+
+        ws_macro_core(path, input);
+    }
+
     #[test]
     fn test_non_json_return_type_no_response_schema() {
         let path = quote!("/health");
@@ -600,4 +1462,300 @@ mod tests {
         assert!(output_str.contains("__rapina_result"));
         assert!(output_str.contains("Result < String , Error >"));
     }
+    #[test]
+    fn test_multipart_form_extractor_generates_request_schema() {
+        let path = quote!("/upload");
+        let input = quote! {
+            async fn upload(form: MultipartForm<Upload>) -> &'static str {
+                "ok"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+
+        assert!(output_str.contains("fn request_schema"));
+        assert!(
+            output_str.contains("< Upload as rapina :: multipart :: FromMultipart > :: schema ()")
+        );
+        assert!(output_str.contains("MULTIPART_CONTENT_TYPE"));
+    }
+
+    #[test]
+    fn test_derive_multipart_form_reads_renamed_fields() {
+        let input = quote! {
+            struct Upload {
+                title: String,
+                #[multipart(rename = "file")]
+                attachments: Vec<UploadedFile>,
+            }
+        };
+
+        let output_str = derive_multipart_form_impl(input).to_string();
+
+        assert!(output_str.contains("impl rapina :: multipart :: FromMultipart for Upload"));
+        assert!(output_str.contains("\"title\""));
+        assert!(output_str.contains("\"file\""));
+        assert!(!output_str.contains("\"attachments\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "MultipartForm derive only supports structs")]
+    fn test_derive_multipart_form_rejects_enums() {
+        derive_multipart_form_impl(quote! {
+            enum Upload { A, B }
+        });
+    }
+    #[test]
+    fn test_mutable_extractor_binding_preserved() {
+        let path = quote!("/upload");
+        let input = quote! {
+            async fn upload(mut multipart: Multipart) -> &'static str {
+                "ok"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+
+        assert!(output_str.contains("let mut multipart ="));
+    }
+    #[test]
+    fn test_json_extractor_generates_request_schema() {
+        let path = quote!("/users");
+        let input = quote! {
+            async fn create_user(body: Validated<Json<CreateUser>>) -> &'static str {
+                "ok"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+
+        assert!(output_str.contains("fn request_schema"));
+        assert!(output_str.contains("SchemaProbe :: < CreateUser >"));
+        assert!(!output_str.contains("fn query_schema"));
+    }
+
+    #[test]
+    fn test_form_extractor_sets_urlencoded_content_type() {
+        let path = quote!("/login");
+        let input = quote! {
+            async fn login(form: Form<Login>) -> &'static str {
+                "ok"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+
+        assert!(output_str.contains("application/x-www-form-urlencoded"));
+    }
+
+    #[test]
+    fn test_query_and_path_extractors_generate_parameter_schemas() {
+        let path = quote!("/users/:id/posts");
+        let input = quote! {
+            async fn list_posts(id: Path<u64>, query: Query<Pagination>) -> &'static str {
+                "ok"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+
+        assert!(output_str.contains("fn query_schema"));
+        assert!(output_str.contains("SchemaProbe :: < Pagination >"));
+        assert!(output_str.contains("fn path_schema"));
+        assert!(output_str.contains("SchemaProbe :: < u64 >"));
+        assert!(!output_str.contains("fn request_schema"));
+    }
+
+    #[test]
+    fn test_doc_comment_generates_summary_and_description() {
+        let path = quote!("/users");
+        let input = quote! {
+            /// List users
+            ///
+            /// Ordered by creation date.
+            /// Paginated.
+            async fn list_users() -> &'static str {
+                "users"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+        assert!(output_str.contains("fn summary"));
+        assert!(output_str.contains("\"List users\""));
+        assert!(output_str.contains("fn description"));
+        assert!(output_str.contains("\"Ordered by creation date.\\nPaginated.\""));
+    }
+
+    #[test]
+    fn test_route_attrs_generate_tags_deprecated_and_operation_id() {
+        let path = quote!("/users");
+        let input = quote! {
+            #[tag("Users", "Admin")]
+            #[deprecated]
+            #[operation_id = "users.list"]
+            async fn list_users() -> &'static str {
+                "users"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+        assert!(output_str.contains("fn tags"));
+        assert!(output_str.contains("\"Users\" , \"Admin\""));
+        assert!(output_str.contains("fn deprecated"));
+        assert!(output_str.contains("\"users.list\""));
+        assert!(!output_str.contains("fn summary"));
+        // Callers still get the deprecation warning
+        assert!(output_str.contains("# [deprecated] # [derive (Clone , Copy)]"));
+    }
+
+    #[test]
+    fn test_deprecation_note_is_documented() {
+        let path = quote!("/users");
+        let input = quote! {
+            /// List users
+            ///
+            /// Paginated.
+            #[deprecated(since = "0.5.0", note = "use `search_users`")]
+            async fn list_users() -> &'static str {
+                "users"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+        assert!(output_str.contains("\"Paginated.\\n\\nDeprecated: use `search_users`\""));
+        assert!(
+            output_str
+                .contains("# [deprecated (since = \"0.5.0\" , note = \"use `search_users`\")]")
+        );
+
+        let mut attrs: Vec<syn::Attribute> = vec![syn::parse_quote!(#[deprecated = "gone"])];
+        let docs = extract_route_docs(&mut attrs);
+        assert_eq!(docs.description.as_deref(), Some("Deprecated: gone"));
+        assert_eq!(attrs.len(), 1);
+    }
+
+    #[test]
+    fn test_tag_macro_moves_below_route_attr() {
+        let input = quote! {
+            #[get("/users")]
+            async fn list_users() -> &'static str {
+                "users"
+            }
+        };
+
+        let output_str = tag_macro_core(quote!("Users"), input).to_string();
+        let route = output_str.find("get").unwrap();
+        let tag = output_str.find("tag").unwrap();
+        assert!(route < tag);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be used on a route handler")]
+    fn test_tag_macro_requires_route_attr() {
+        let input = quote! {
+            async fn list_users() {}
+        };
+        tag_macro_core(quote!("Users"), input);
+    }
+
+    #[test]
+    fn test_public_attr_marks_handler_public() {
+        let path = quote!("/health");
+        let input = quote! {
+            #[public]
+            async fn health() -> &'static str {
+                "ok"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+        assert!(output_str.contains("fn is_public"));
+        assert!(!output_str.contains("# [public]"));
+    }
+
+    #[test]
+    fn test_public_macro_moves_below_route_attr() {
+        let input = quote! {
+            #[get("/health")]
+            async fn health() -> &'static str {
+                "ok"
+            }
+        };
+
+        let output_str = public_macro_core(quote!(), input).to_string();
+        let route = output_str.find("get").unwrap();
+        let public = output_str.find("public").unwrap();
+        assert!(route < public);
+
+        let plain = quote! {
+            async fn health() {}
+        };
+        let output = public_macro_core(quote!(), plain.clone());
+        assert_eq!(output.to_string(), plain.to_string());
+    }
+
+    #[test]
+    fn test_middleware_attr_generates_middlewares() {
+        let path = quote!("/admin");
+        let input = quote! {
+            #[middleware(Audit, TimeoutMiddleware::new(Duration::from_secs(5)))]
+            #[middleware(Trace)]
+            async fn admin() -> &'static str {
+                "admin"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+        assert!(output_str.contains("fn middlewares"));
+        assert!(!output_str.contains("# [middleware"));
+        let audit = output_str.find("Audit").unwrap();
+        let timeout = output_str.find("TimeoutMiddleware").unwrap();
+        let trace = output_str.find("(Trace)").unwrap();
+        assert!(audit < timeout && timeout < trace);
+    }
+
+    #[test]
+    fn test_middleware_macro_moves_below_route_attr() {
+        let input = quote! {
+            #[get("/admin")]
+            async fn admin() -> &'static str {
+                "admin"
+            }
+        };
+
+        let output_str = middleware_macro_core(quote!(Audit), input).to_string();
+        let route = output_str.find("get").unwrap();
+        let middleware = output_str.find("middleware").unwrap();
+        assert!(route < middleware);
+    }
+
+    #[test]
+    fn test_route_generates_typed_path_helper() {
+        let path = quote!("/orgs/:org/files/*path");
+        let input = quote! {
+            async fn get_file(params: Path<(String, String)>) -> String {
+                params.1.clone()
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+        assert!(output_str.contains("impl get_file"));
+        assert!(output_str.contains(
+            "fn path (org : impl std :: fmt :: Display , path : impl std :: fmt :: Display) -> String"
+        ));
+        assert!(output_str.contains("rapina :: router :: typed_path (\"/orgs/:org/files/*path\""));
+    }
+
+    #[test]
+    fn test_typed_path_helper_optional_params() {
+        let path = quote!("/posts/:page<int>?");
+        let input = quote! {
+            async fn list_posts() -> &'static str {
+                "posts"
+            }
+        };
+
+        let output_str = route_macro_core(path, input).to_string();
+        assert!(output_str.contains("fn path (page : Option < & dyn std :: fmt :: Display >)"));
+    }
 }
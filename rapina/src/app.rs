@@ -1,17 +1,26 @@
 //! The main application builder for Rapina.
 
+use std::future::Future;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use crate::auth::{AuthConfig, AuthMiddleware, PublicRoutes};
+use crate::extract::TrustProxyHeaders;
 use crate::introspection::{RouteRegistry, list_routes};
 use crate::middleware::{
     CompressionConfig, CompressionMiddleware, CorsConfig, CorsMiddleware, Middleware,
     MiddlewareStack, RateLimitConfig, RateLimitMiddleware,
 };
 use crate::observability::TracingConfig;
-use crate::openapi::{OpenApiRegistry, build_openapi_spec, openapi_spec};
+#[cfg(feature = "docs")]
+use crate::openapi::docs::{DocsPage, DocsUi, docs_asset, docs_page};
+use crate::openapi::{
+    OpenApiError, OpenApiOptions, OpenApiRegistry, OpenApiVersion, openapi_spec,
+    try_build_openapi_spec,
+};
 use crate::router::Router;
-use crate::server::serve;
+use crate::server::{ServerConfig, ShutdownConfig, serve_with_shutdown, shutdown_signal};
+use crate::service::RapinaService;
 use crate::state::AppState;
 
 /// The main application type for building Rapina servers.
@@ -53,10 +62,18 @@ pub struct Rapina {
     pub(crate) openapi: bool,
     pub(crate) openapi_title: String,
     pub(crate) openapi_version: String,
+    pub(crate) openapi_spec_version: OpenApiVersion,
+    /// UI served at `/__rapina/docs`, if enabled
+    #[cfg(feature = "docs")]
+    pub(crate) docs_ui: Option<DocsUi>,
     /// Authentication configuration (if enabled)
     pub(crate) auth_config: Option<AuthConfig>,
     /// Public routes registry
     pub(crate) public_routes: PublicRoutes,
+    /// Grace period and hooks used when the server shuts down
+    pub(crate) shutdown: ShutdownConfig,
+    /// Protocol and connection settings for the HTTP server
+    pub(crate) server_config: ServerConfig,
 }
 
 impl Rapina {
@@ -72,8 +89,13 @@ impl Rapina {
             openapi: false,
             openapi_title: "API".to_string(),
             openapi_version: "1.0.0".to_string(),
+            openapi_spec_version: OpenApiVersion::default(),
+            #[cfg(feature = "docs")]
+            docs_ui: None,
             auth_config: None,
             public_routes: PublicRoutes::new(),
+            shutdown: ShutdownConfig::default(),
+            server_config: ServerConfig::default(),
         }
     }
 
@@ -89,6 +111,15 @@ impl Rapina {
         self
     }
 
+    /// Trusts the `Forwarded` and `X-Forwarded-*` headers when building
+    /// absolute URLs with [`UrlFor`](crate::extract::UrlFor).
+    ///
+    /// Only enable this behind a proxy that sets these headers, since any
+    /// client can send them.
+    pub fn trust_proxy_headers(self) -> Self {
+        self.state(TrustProxyHeaders)
+    }
+
     /// Adds a middleware to the application.
     pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
         self.middlewares.add(middleware);
@@ -142,6 +173,8 @@ impl Rapina {
     ///
     /// When enabled, all routes require a valid `Authorization: Bearer <token>` header
     /// unless marked with `#[public]` or registered via [`public_route`](Self::public_route).
+    /// The OpenAPI spec then documents a `bearerAuth` security scheme, which
+    /// public routes opt out of.
     ///
     /// # Example
     ///
@@ -162,7 +195,9 @@ impl Rapina {
     /// Registers a route as public (no authentication required).
     ///
     /// Use this for routes that should be accessible without a JWT token.
-    /// Routes starting with `/__rapina` are automatically public.
+    /// `path` is the route's pattern, like `/users/:id`; it makes that route
+    /// public, not other routes whose paths it would match. Routes starting
+    /// with `/__rapina` are automatically public.
     ///
     /// # Example
     ///
@@ -180,6 +215,64 @@ impl Rapina {
         self
     }
 
+    /// Sets how long in-flight requests may take to finish after a shutdown
+    /// signal before their connections are dropped.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn shutdown_timeout(mut self, grace_period: Duration) -> Self {
+        self.shutdown.grace_period = grace_period;
+        self
+    }
+
+    /// Registers a hook to run when the server shuts down.
+    ///
+    /// Hooks run in registration order, after in-flight requests have
+    /// finished (or the grace period has elapsed) and before
+    /// [`listen`](Self::listen) returns.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .on_shutdown(|| async {
+    ///         tracing::info!("flushing metrics");
+    ///     })
+    ///     .router(router)
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown.hooks.push(Box::new(move || Box::pin(hook())));
+        self
+    }
+
+    /// Configures the HTTP server's protocols and connection limits.
+    ///
+    /// By default the server accepts both HTTP/1.1 and HTTP/2, including
+    /// HTTP/2 over plain TCP (h2c) from clients using prior knowledge.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Rapina::new()
+    ///     .server_config(
+    ///         ServerConfig::new()
+    ///             .http2_max_concurrent_streams(100)
+    ///             .http2_keep_alive(Duration::from_secs(30), Duration::from_secs(10)),
+    ///     )
+    ///     .router(router)
+    ///     .listen("127.0.0.1:3000")
+    ///     .await
+    /// ```
+    pub fn server_config(mut self, config: ServerConfig) -> Self {
+        self.server_config = config;
+        self
+    }
+
     /// Configures tracing/logging for the application.
     pub fn with_tracing(self, config: TracingConfig) -> Self {
         config.init();
@@ -209,11 +302,43 @@ impl Rapina {
         self
     }
 
+    /// Sets the OpenAPI version of the generated spec.
+    ///
+    /// Defaults to 3.0.3. Use [`OpenApiVersion::V3_1`] to get schemas in
+    /// JSON Schema 2020-12 form.
+    pub fn openapi_spec_version(mut self, version: OpenApiVersion) -> Self {
+        self.openapi_spec_version = version;
+        self
+    }
+
+    /// Serves an interactive API docs page at `/__rapina/docs`.
+    ///
+    /// The page renders the spec from `/__rapina/openapi.json` with the
+    /// chosen UI, using the title and version given to [`openapi`](Self::openapi).
+    /// The UI's assets are embedded in the binary, so it works offline. When
+    /// [`with_auth`](Self::with_auth) is configured, "Try it out" requests can
+    /// send a bearer token. Enables OpenAPI if it isn't already.
+    ///
+    /// ```rust,ignore
+    /// use rapina::openapi::DocsUi;
+    ///
+    /// Rapina::new()
+    ///     .openapi("My API", "1.0.0")
+    ///     .docs(DocsUi::Scalar)
+    /// ```
+    #[cfg(feature = "docs")]
+    pub fn docs(mut self, ui: DocsUi) -> Self {
+        self.openapi = true;
+        self.docs_ui = Some(ui);
+        self
+    }
+
     /// Configures database connection with the given configuration.
     ///
     /// This method connects to the database and registers the connection
     /// in the application state. Use the [`Db`](crate::database::Db) extractor
-    /// in your handlers to access the connection.
+    /// in your handlers to access the connection. The connection pool is
+    /// closed when the server shuts down.
     ///
     /// # Example
     ///
@@ -247,8 +372,12 @@ impl Rapina {
             .connect()
             .await
             .map_err(|e| std::io::Error::other(format!("Database connection failed: {}", e)))?;
-        self.state = self.state.with(conn);
-        Ok(self)
+        self.state = self.state.with(conn.clone());
+        Ok(self.on_shutdown(move || async move {
+            if let Err(e) = conn.close().await {
+                tracing::error!(error = %e, "failed to close database connection");
+            }
+        }))
     }
 
     /// Runs all pending database migrations at startup.
@@ -290,41 +419,206 @@ impl Rapina {
 
     /// Starts the HTTP server on the given address.
     ///
+    /// The router is compiled before the server binds, so conflicting
+    /// routes are reported as an error here rather than at request time.
+    ///
+    /// The server shuts down gracefully on SIGINT (Ctrl+C) or SIGTERM.
+    /// See [`listen_with_shutdown`](Self::listen_with_shutdown).
+    ///
     /// # Panics
     ///
     /// Panics if the address cannot be parsed.
-    pub async fn listen(mut self, addr: &str) -> std::io::Result<()> {
+    pub async fn listen(self, addr: &str) -> std::io::Result<()> {
+        self.listen_with_shutdown(addr, shutdown_signal()).await
+    }
+
+    /// Starts an HTTPS server on the given address.
+    ///
+    /// TLS is terminated in-process with rustls, so no proxy is needed in
+    /// front of the service. HTTP/1.1 and HTTP/2 are negotiated through ALPN,
+    /// and the certificate is reloaded when its files change on disk. See
+    /// [`TlsConfig`](crate::tls::TlsConfig) for client certificates (mTLS).
+    ///
+    /// Requires the `tls` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rapina::prelude::*;
+    /// use rapina::tls::TlsConfig;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     Rapina::new()
+    ///         .router(Router::new())
+    ///         .listen_tls("0.0.0.0:443", TlsConfig::new("cert.pem", "key.pem"))
+    ///         .await
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address cannot be parsed.
+    #[cfg(feature = "tls")]
+    pub async fn listen_tls(
+        mut self,
+        addr: &str,
+        tls: crate::tls::TlsConfig,
+    ) -> std::io::Result<()> {
+        self.server_config.tls = Some(tls);
+        self.listen(addr).await
+    }
+
+    /// Starts the HTTP server and shuts it down gracefully when `signal` completes.
+    ///
+    /// On shutdown the server stops accepting connections, closes idle
+    /// keep-alive connections and lets in-flight requests finish within
+    /// the [`shutdown_timeout`](Self::shutdown_timeout). It then runs the
+    /// [`on_shutdown`](Self::on_shutdown) hooks and returns.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rapina::prelude::*;
+    /// use std::time::Duration;
+    /// use tokio::sync::oneshot;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    ///     # drop(stop_tx);
+    ///
+    ///     Rapina::new()
+    ///         .shutdown_timeout(Duration::from_secs(10))
+    ///         .router(Router::new())
+    ///         .listen_with_shutdown("127.0.0.1:3000", async {
+    ///             let _ = stop_rx.await;
+    ///         })
+    ///         .await
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address cannot be parsed.
+    pub async fn listen_with_shutdown(
+        mut self,
+        addr: &str,
+        signal: impl Future<Output = ()> + Send,
+    ) -> std::io::Result<()> {
         let addr: SocketAddr = addr.parse().expect("invalid address");
+        self.prepare()?;
 
-        // Add auth middleware if configured
-        if let Some(auth_config) = self.auth_config.take() {
-            let auth_middleware =
-                AuthMiddleware::with_public_routes(auth_config, self.public_routes.clone());
-            self.middlewares.add(auth_middleware);
-        }
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        println!(
+            "Rapina listening on {}://{}",
+            self.server_config.scheme(),
+            listener.local_addr()?
+        );
+        serve_with_shutdown(
+            self.router,
+            self.state,
+            self.middlewares,
+            listener,
+            async {
+                signal.await;
+                println!("Rapina shutting down...");
+            },
+            self.server_config,
+            self.shutdown,
+        )
+        .await
+    }
 
+    /// Turns the application into a tower `Service`, to serve it from
+    /// another server or wrap it in tower layers.
+    ///
+    /// The built-in endpoints and authentication are set up as with
+    /// [`listen`](Self::listen). Use [`RapinaService::any_body`] for
+    /// requests whose body isn't hyper's `Incoming`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the router or the OpenAPI spec is invalid.
+    pub fn into_service(mut self) -> RapinaService {
+        self.prepare().unwrap_or_else(|e| panic!("{}", e));
+        RapinaService::new(self.router, self.state, self.middlewares)
+    }
+
+    /// Registers the built-in endpoints and auth, then compiles the router.
+    pub(crate) fn prepare(&mut self) -> std::io::Result<()> {
         if self.introspection {
             // Store route metadata in state for the introspection endpoint
             let routes = self.router.routes();
-            self.state = self.state.with(RouteRegistry::with_routes(routes));
+            self.state = std::mem::take(&mut self.state).with(RouteRegistry::with_routes(routes));
 
             // Register the introspection endpoint
-            self.router = self
-                .router
-                .get_named("/__rapina/routes", "list_routes", list_routes);
+            let router = std::mem::take(&mut self.router);
+            self.router = router.get_named("/__rapina/routes", "list_routes", list_routes);
         }
 
-        if self.openapi {
-            let routes = self.router.routes();
-            let spec = build_openapi_spec(&self.openapi_title, &self.openapi_version, &routes);
-            self.state = self.state.with(OpenApiRegistry::new(spec));
+        self.mount_openapi().map_err(std::io::Error::other)?;
+        self.mount_auth();
+
+        self.router.compile().map_err(std::io::Error::other)
+    }
+
+    /// Adds the auth middleware if configured, letting `#[public]` routes through.
+    pub(crate) fn mount_auth(&mut self) {
+        let Some(auth_config) = self.auth_config.take() else {
+            return;
+        };
+
+        for (method, route) in &mut self.router.routes {
+            route.public = route.public
+                || self
+                    .public_routes
+                    .covers_route(method.as_str(), &route.pattern);
+        }
+
+        let auth_middleware =
+            AuthMiddleware::with_public_routes(auth_config, self.public_routes.clone());
+        self.middlewares.add(auth_middleware);
+    }
+
+    /// Registers the OpenAPI spec endpoint and, if enabled, the docs page.
+    pub(crate) fn mount_openapi(&mut self) -> Result<(), OpenApiError> {
+        if !self.openapi {
+            return Ok(());
+        }
+
+        let mut routes = self.router.routes();
+        for route in &mut routes {
+            route.public =
+                route.public || self.public_routes.covers_route(&route.method, &route.path);
+        }
+        let options = OpenApiOptions::new()
+            .version(self.openapi_spec_version)
+            .bearer_auth(self.auth_config.is_some());
+        let spec =
+            try_build_openapi_spec(&self.openapi_title, &self.openapi_version, &routes, options)?;
+        self.state = std::mem::take(&mut self.state).with(OpenApiRegistry::new(spec));
+
+        let router = std::mem::take(&mut self.router);
+        self.router = router.get_named("/__rapina/openapi.json", "openapi_spec", openapi_spec);
+
+        #[cfg(feature = "docs")]
+        if let Some(ui) = self.docs_ui {
+            let page = DocsPage::new(
+                ui,
+                &self.openapi_title,
+                &self.openapi_version,
+                self.auth_config.is_some(),
+            );
+            self.state = std::mem::take(&mut self.state).with(page);
 
-            self.router =
-                self.router
-                    .get_named("/__rapina/openapi.json", "openapi_spec", openapi_spec);
+            let router = std::mem::take(&mut self.router);
+            self.router = router
+                .get_named("/__rapina/docs", "docs_page", docs_page)
+                .get_named("/__rapina/docs/:asset", "docs_asset", docs_asset);
         }
 
-        serve(self.router, self.state, self.middlewares, addr).await
+        Ok(())
     }
 }
 
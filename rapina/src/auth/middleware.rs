@@ -1,13 +1,14 @@
 //! Authentication middleware for Rapina.
 
-use hyper::body::Incoming;
 use hyper::{Request, Response};
 
 use crate::auth::{AuthConfig, CurrentUser, PublicRoutes};
 use crate::context::RequestContext;
 use crate::error::Error;
+use crate::extract::RequestBody;
 use crate::middleware::{BoxFuture, Middleware, Next};
 use crate::response::{BoxBody, IntoResponse};
+use crate::router::MatchedRoute;
 
 /// Middleware that enforces JWT authentication on all routes.
 ///
@@ -51,7 +52,7 @@ impl AuthMiddleware {
     }
 
     /// Extracts the bearer token from the Authorization header.
-    fn extract_bearer_token(req: &Request<Incoming>) -> Option<&str> {
+    fn extract_bearer_token(req: &Request<RequestBody>) -> Option<&str> {
         req.headers()
             .get(http::header::AUTHORIZATION)
             .and_then(|v| v.to_str().ok())
@@ -62,7 +63,7 @@ impl AuthMiddleware {
 impl Middleware for AuthMiddleware {
     fn handle<'a>(
         &'a self,
-        mut req: Request<Incoming>,
+        mut req: Request<RequestBody>,
         _ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
@@ -70,8 +71,14 @@ impl Middleware for AuthMiddleware {
             let method = req.method().as_str();
             let path = req.uri().path();
 
-            // Check if this route is public
-            if self.public_routes.is_public(method, path) {
+            // Check if this route is public. The route the router matched
+            // decides, so a public `/users/:id` doesn't open `/users/me`.
+            // Paths that match no route, like fallbacks, go by the registry.
+            let public = match req.extensions().get::<MatchedRoute>() {
+                Some(route) => route.public,
+                None => self.public_routes.is_public(method, path),
+            };
+            if public {
                 return next.run(req).await;
             }
 
@@ -38,6 +38,7 @@ pub use middleware::AuthMiddleware;
 
 use crate::error::Error;
 use crate::extract::{FromRequestParts, PathParams};
+use crate::router::pattern::Pattern;
 use crate::state::AppState;
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
@@ -233,7 +234,7 @@ impl AuthConfig {
 /// should be accessible without a valid JWT token.
 #[derive(Clone, Default)]
 pub struct PublicRoutes {
-    routes: Vec<(String, String)>, // (method, path)
+    routes: Vec<(String, String, Option<Pattern>)>, // (method, path, parsed path)
 }
 
 impl PublicRoutes {
@@ -244,7 +245,22 @@ impl PublicRoutes {
 
     /// Adds a public route.
     pub fn add(&mut self, method: &str, path: &str) {
-        self.routes.push((method.to_string(), path.to_string()));
+        let pattern = Pattern::parse(path).ok();
+        self.routes
+            .push((method.to_string(), path.to_string(), pattern));
+    }
+
+    /// Whether the route registered with `method` and `pattern` is public.
+    ///
+    /// Unlike [`is_public`](Self::is_public), this compares patterns rather
+    /// than matching a path, so a public `/users/:id` doesn't cover a
+    /// `/users/me` route.
+    pub(crate) fn covers_route(&self, method: &str, pattern: &str) -> bool {
+        pattern.starts_with("/__rapina")
+            || self
+                .routes
+                .iter()
+                .any(|(m, p, _)| m == method && p == pattern)
     }
 
     /// Checks if a route is public.
@@ -254,26 +270,13 @@ impl PublicRoutes {
             return true;
         }
 
-        self.routes
-            .iter()
-            .any(|(m, p)| m == method && Self::matches_pattern(p, path))
-    }
-
-    /// Matches a route pattern against a path.
-    fn matches_pattern(pattern: &str, path: &str) -> bool {
-        let pattern_parts: Vec<&str> = pattern.split('/').collect();
-        let path_parts: Vec<&str> = path.split('/').collect();
-
-        if pattern_parts.len() != path_parts.len() {
-            return false;
-        }
-
-        pattern_parts
-            .iter()
-            .zip(path_parts.iter())
-            .all(|(pattern_part, path_part)| {
-                pattern_part.starts_with(':') || pattern_part == path_part
-            })
+        self.routes.iter().any(|(m, p, pattern)| {
+            m == method
+                && (p == path
+                    || pattern
+                        .as_ref()
+                        .is_some_and(|pattern| pattern.matches(path).is_some()))
+        })
     }
 }
 
@@ -375,6 +378,28 @@ mod tests {
         assert!(!routes.is_public("GET", "/users/123/private"));
     }
 
+    #[test]
+    fn test_public_routes_with_catch_all_and_constraints() {
+        let mut routes = PublicRoutes::new();
+        routes.add("GET", "/assets/*path");
+        routes.add("GET", "/posts/:id<int>");
+
+        assert!(routes.is_public("GET", "/assets/css/app.css"));
+        assert!(routes.is_public("GET", "/posts/7"));
+        assert!(!routes.is_public("GET", "/posts/draft"));
+    }
+
+    #[test]
+    fn test_public_routes_cover_routes_by_pattern() {
+        let mut routes = PublicRoutes::new();
+        routes.add("GET", "/users/:id");
+
+        assert!(routes.covers_route("GET", "/users/:id"));
+        assert!(!routes.covers_route("GET", "/users/me"));
+        assert!(!routes.covers_route("DELETE", "/users/:id"));
+        assert!(routes.covers_route("GET", "/__rapina/openapi.json"));
+    }
+
     #[test]
     fn test_public_routes_introspection_always_public() {
         let routes = PublicRoutes::new();
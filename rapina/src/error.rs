@@ -25,15 +25,13 @@
 //! }
 //! ```
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-use crate::response::{BoxBody, IntoResponse};
-use bytes::Bytes;
-use http_body_util::Full;
+use crate::response::{BoxBody, IntoResponse, full};
 
 /// The JSON structure returned for error responses.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     /// The error details.
     pub error: ErrorDetail,
@@ -42,7 +40,7 @@ pub struct ErrorResponse {
 }
 
 /// Detailed error information in the response body.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorDetail {
     /// Machine-readable error code (e.g., "NOT_FOUND", "BAD_REQUEST").
     pub code: String,
@@ -128,11 +126,21 @@ impl Error {
         Self::new(404, "NOT_FOUND", message)
     }
 
+    /// Creates a 405 Method Not Allowed error.
+    pub fn method_not_allowed(message: impl Into<String>) -> Self {
+        Self::new(405, "METHOD_NOT_ALLOWED", message)
+    }
+
     /// Creates a 409 Conflict error.
     pub fn conflict(message: impl Into<String>) -> Self {
         Self::new(409, "CONFLICT", message)
     }
 
+    /// Creates a 413 Payload Too Large error.
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self::new(413, "PAYLOAD_TOO_LARGE", message)
+    }
+
     /// Creates a 422 Validation Error.
     pub fn validation(message: impl Into<String>) -> Self {
         Self::new(422, "VALIDATION_ERROR", message)
@@ -287,7 +295,7 @@ impl IntoResponse for Error {
         http::Response::builder()
             .status(self.status)
             .header("content-type", "application/json")
-            .body(Full::new(Bytes::from(body)))
+            .body(full(body))
             .unwrap()
     }
 }
@@ -416,6 +424,13 @@ mod tests {
         assert_eq!(err.code, "NOT_FOUND");
     }
 
+    #[test]
+    fn test_error_method_not_allowed() {
+        let err = Error::method_not_allowed("method not allowed");
+        assert_eq!(err.status, 405);
+        assert_eq!(err.code, "METHOD_NOT_ALLOWED");
+    }
+
     #[test]
     fn test_error_conflict() {
         let err = Error::conflict("already exists");
@@ -423,6 +438,13 @@ mod tests {
         assert_eq!(err.code, "CONFLICT");
     }
 
+    #[test]
+    fn test_error_payload_too_large() {
+        let err = Error::payload_too_large("body too large");
+        assert_eq!(err.status, 413);
+        assert_eq!(err.code, "PAYLOAD_TOO_LARGE");
+    }
+
     #[test]
     fn test_error_validation() {
         let err = Error::validation("invalid data");
@@ -3,21 +3,91 @@
 //! Extractors are types that implement [`FromRequest`] or [`FromRequestParts`]
 //! and can be used as handler parameters to automatically parse request data.
 
-use bytes::Bytes;
 use http::Request;
 use http_body_util::BodyExt;
-use hyper::body::Incoming;
 use serde::de::DeserializeOwned;
 use std::collections::HashMap;
-use std::str::FromStr;
 use std::sync::Arc;
 use validator::Validate;
 
 use crate::context::RequestContext;
 use crate::error::Error;
-use crate::response::{BoxBody, IntoResponse};
+use crate::response::{BoxBody, BoxError, IntoResponse};
 use crate::state::AppState;
 
+mod path_de;
+
+/// The body of requests seen by handlers, extractors and middleware.
+///
+/// Wraps any [`Body`](hyper::body::Body) behind a box. Requests from the
+/// server carry hyper's body; requests built elsewhere, such as by
+/// [`AnyBodyService`](crate::service::AnyBodyService), the in-process test
+/// client or a tower layer that wraps the body, are boxed the same way and
+/// read as they arrive.
+pub struct RequestBody(http_body_util::combinators::BoxBody<bytes::Bytes, BoxError>);
+
+impl RequestBody {
+    /// Boxes a body, keeping a `RequestBody` as-is.
+    pub fn new<B>(body: B) -> Self
+    where
+        B: hyper::body::Body + Send + Sync + 'static,
+        B::Error: Into<BoxError>,
+    {
+        let mut body = Some(body);
+        if let Some(boxed) = (&mut body as &mut dyn std::any::Any).downcast_mut::<Option<Self>>() {
+            return boxed.take().unwrap();
+        }
+        Self(
+            body.unwrap()
+                .map_frame(|frame| {
+                    frame.map_data(|mut data| {
+                        let len = bytes::Buf::remaining(&data);
+                        bytes::Buf::copy_to_bytes(&mut data, len)
+                    })
+                })
+                .map_err(Into::into)
+                .boxed(),
+        )
+    }
+
+    /// Creates an empty body.
+    pub fn empty() -> Self {
+        Self::new(http_body_util::Empty::<bytes::Bytes>::new())
+    }
+}
+
+impl Default for RequestBody {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl std::fmt::Debug for RequestBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestBody").finish_non_exhaustive()
+    }
+}
+
+impl hyper::body::Body for RequestBody {
+    type Data = bytes::Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+        std::pin::Pin::new(&mut self.get_mut().0).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.0.is_end_stream()
+    }
+
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.0.size_hint()
+    }
+}
+
 const JSON_CONTENT_TYPE: &str = "application/json";
 const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
 
@@ -46,10 +116,12 @@ const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
 #[derive(Debug)]
 pub struct Json<T>(pub T);
 
-/// Extracts a single path parameter from the URL.
+/// Extracts path parameters from the URL.
 ///
-/// Parses a path segment into the specified type `T`.
-/// Returns 400 Bad Request if parsing fails.
+/// A single value such as `Path<u64>` reads the route's only parameter.
+/// Tuples read the parameters in the order they appear in the pattern,
+/// and any `Deserialize` struct reads them by name.
+/// Returns 400 Bad Request naming the offending segment if parsing fails.
 ///
 /// # Examples
 ///
@@ -60,6 +132,23 @@ pub struct Json<T>(pub T);
 /// async fn get_user(id: Path<u64>) -> String {
 ///     format!("User ID: {}", id.into_inner())
 /// }
+///
+/// #[get("/orgs/:org/users/:user_id")]
+/// async fn get_member(path: Path<(String, u64)>) -> String {
+///     let (org, user_id) = path.into_inner();
+///     format!("{} in {}", user_id, org)
+/// }
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct MemberPath {
+///     org: String,
+///     user_id: u64,
+/// }
+///
+/// #[get("/orgs/:org/members/:user_id")]
+/// async fn get_member_by_name(path: Path<MemberPath>) -> String {
+///     format!("{} in {}", path.0.user_id, path.0.org)
+/// }
 /// ```
 #[derive(Debug)]
 pub struct Path<T>(pub T);
@@ -198,6 +287,72 @@ pub struct State<T>(pub T);
 #[derive(Debug)]
 pub struct Context(pub RequestContext);
 
+/// Builds paths and absolute URLs to named routes.
+///
+/// Routes are named after their handler, and URLs are built like
+/// [`Router::url_for`](crate::router::Router::url_for). The scheme and
+/// host come from the `Host` header. Behind a proxy, enable
+/// [`Rapina::trust_proxy_headers`](crate::app::Rapina::trust_proxy_headers)
+/// to read them from the `Forwarded` header, then `X-Forwarded-Proto` and
+/// `X-Forwarded-Host`, instead. Hosts that aren't a valid authority are
+/// ignored.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::http::Response;
+/// use rapina::prelude::*;
+/// use rapina::response::{BoxBody, full};
+///
+/// #[post("/users")]
+/// async fn create_user(urls: UrlFor) -> Result<Response<BoxBody>> {
+///     let location = urls.url("get_user", &[("id", &42)])?;
+///     Ok(Response::builder()
+///         .status(StatusCode::CREATED)
+///         .header("location", location)
+///         .body(full(""))
+///         .unwrap())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct UrlFor {
+    routes: crate::router::RouteUrls,
+    base: String,
+}
+
+impl UrlFor {
+    /// Returns the path to the named route, e.g. `/users/42`.
+    pub fn path(
+        &self,
+        name: &str,
+        params: &[(&str, &dyn std::fmt::Display)],
+    ) -> Result<String, crate::router::UrlForError> {
+        self.routes.path_for(name, params)
+    }
+
+    /// Returns the absolute URL of the named route, e.g.
+    /// `https://api.example.com/users/42`.
+    pub fn url(
+        &self,
+        name: &str,
+        params: &[(&str, &dyn std::fmt::Display)],
+    ) -> Result<String, crate::router::UrlForError> {
+        Ok(format!("{}{}", self.base, self.path(name, params)?))
+    }
+
+    /// Returns the scheme and host the request was made to, e.g.
+    /// `https://api.example.com`.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+}
+
+impl std::fmt::Debug for UrlFor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UrlFor").field("base", &self.base).finish()
+    }
+}
+
 /// Wraps an extractor and validates the extracted value.
 ///
 /// Uses the `validator` crate to run validation rules on the inner value.
@@ -226,8 +381,75 @@ pub struct Context(pub RequestContext);
 #[derive(Debug)]
 pub struct Validated<T>(pub T);
 
-/// Type alias for path parameters extracted from the URL.
-pub type PathParams = HashMap<String, String>;
+/// Path parameters extracted from the URL.
+///
+/// Parameters are kept in the order they appear in the route pattern, so
+/// `/orgs/:org_id/users/:user_id` yields `org_id` before `user_id`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathParams {
+    params: Vec<(String, String)>,
+}
+
+impl PathParams {
+    /// Creates an empty set of parameters.
+    pub fn new() -> Self {
+        Self { params: Vec::new() }
+    }
+
+    /// Adds a parameter, replacing the value of an existing one with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        let value = value.into();
+        match self.params.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.params.push((name, value)),
+        }
+    }
+
+    /// Returns the value of the named parameter.
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.params.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Returns whether the named parameter is present.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Iterates over `(name, value)` pairs in pattern order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.params.iter().map(|(n, v)| (n, v))
+    }
+
+    /// Returns the number of parameters.
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns whether there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+}
+
+impl FromIterator<(String, String)> for PathParams {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        let mut params = PathParams::new();
+        for (name, value) in iter {
+            params.insert(name, value);
+        }
+        params
+    }
+}
+
+impl IntoIterator for PathParams {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.params.into_iter()
+    }
+}
 
 /// Trait for extractors that consume the request body.
 ///
@@ -236,7 +458,7 @@ pub type PathParams = HashMap<String, String>;
 pub trait FromRequest: Sized {
     /// Extract the value from the request.
     fn from_request(
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         params: &PathParams,
         state: &Arc<AppState>,
     ) -> impl std::future::Future<Output = Result<Self, Error>> + Send;
@@ -336,7 +558,7 @@ impl<T> Validated<T> {
 
 impl<T: DeserializeOwned + Send> FromRequest for Json<T> {
     async fn from_request(
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         _params: &PathParams,
         _state: &Arc<AppState>,
     ) -> Result<Self, Error> {
@@ -344,7 +566,7 @@ impl<T: DeserializeOwned + Send> FromRequest for Json<T> {
         let bytes = body
             .collect()
             .await
-            .map_err(|_| Error::bad_request("Failed to read request body"))?
+            .map_err(|e| body_error(e, "Failed to read request body"))?
             .to_bytes();
 
         let value: T = serde_json::from_slice(&bytes)
@@ -354,13 +576,23 @@ impl<T: DeserializeOwned + Send> FromRequest for Json<T> {
     }
 }
 
+/// The error for a body that couldn't be read: `413` when a body limit
+/// (such as tower-http's `RequestBodyLimitLayer`) cut it off, `400` otherwise.
+pub(crate) fn body_error(err: BoxError, message: &str) -> Error {
+    if err.is::<http_body_util::LengthLimitError>() {
+        Error::payload_too_large("body too large")
+    } else {
+        Error::bad_request(message)
+    }
+}
+
 impl<T: serde::Serialize> IntoResponse for (http::StatusCode, Json<T>) {
     fn into_response(self) -> http::Response<BoxBody> {
         let body = serde_json::to_vec(&(self.1).0).unwrap_or_default();
         http::Response::builder()
             .status(self.0)
             .header("content-type", JSON_CONTENT_TYPE)
-            .body(http_body_util::Full::new(Bytes::from(body)))
+            .body(crate::response::full(body))
             .unwrap()
     }
 }
@@ -373,7 +605,7 @@ impl<T: serde::Serialize> IntoResponse for Json<T> {
 
 impl<T: DeserializeOwned + Send> FromRequest for Form<T> {
     async fn from_request(
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         _params: &PathParams,
         _state: &Arc<AppState>,
     ) -> Result<Self, Error> {
@@ -397,7 +629,7 @@ impl<T: DeserializeOwned + Send> FromRequest for Form<T> {
         let bytes = body
             .collect()
             .await
-            .map_err(|_| Error::bad_request("Failed to read form data from request body"))?
+            .map_err(|e| body_error(e, "Failed to read form data from request body"))?
             .to_bytes();
 
         let value: T = serde_urlencoded::from_bytes(&bytes)
@@ -409,7 +641,7 @@ impl<T: DeserializeOwned + Send> FromRequest for Form<T> {
 
 impl<T: DeserializeOwned + Validate + Send> FromRequest for Validated<Json<T>> {
     async fn from_request(
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         params: &PathParams,
         state: &Arc<AppState>,
     ) -> Result<Self, Error> {
@@ -424,7 +656,7 @@ impl<T: DeserializeOwned + Validate + Send> FromRequest for Validated<Json<T>> {
 
 impl<T: DeserializeOwned + Validate + Send> FromRequest for Validated<Form<T>> {
     async fn from_request(
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         params: &PathParams,
         state: &Arc<AppState>,
     ) -> Result<Self, Error> {
@@ -473,6 +705,98 @@ impl FromRequestParts for Context {
     }
 }
 
+impl FromRequestParts for UrlFor {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let routes = state
+            .get::<crate::router::RouteUrls>()
+            .cloned()
+            .ok_or_else(|| {
+                Error::internal("Route table missing. UrlFor needs the app to be served by Rapina.")
+            })?;
+        let trust_proxy = state.get::<TrustProxyHeaders>().is_some();
+        Ok(UrlFor {
+            routes,
+            base: request_base(parts, trust_proxy),
+        })
+    }
+}
+
+/// Stored in the app state when the forwarded headers set by a proxy can
+/// be trusted.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TrustProxyHeaders;
+
+/// Returns the `scheme://host` a request was made to.
+///
+/// The forwarded headers are only read when `trust_proxy` is set, since
+/// any client can send them.
+fn request_base(parts: &http::request::Parts, trust_proxy: bool) -> String {
+    let header = |name: &str| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+
+    // Only the first (client-facing) element of `Forwarded` matters
+    let forwarded = header("forwarded")
+        .filter(|_| trust_proxy)
+        .and_then(|value| value.split(',').next())
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| {
+                    (
+                        key.trim().to_ascii_lowercase(),
+                        value.trim().trim_matches('"').to_string(),
+                    )
+                })
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    let forwarded_header = |name| header(name).filter(|_| trust_proxy);
+
+    let scheme = forwarded
+        .get("proto")
+        .map(String::as_str)
+        .or_else(|| forwarded_header("x-forwarded-proto"))
+        .map(str::to_ascii_lowercase)
+        .filter(|scheme| scheme == "http" || scheme == "https")
+        .or_else(|| parts.uri.scheme_str().map(str::to_string))
+        .or_else(|| {
+            parts
+                .extensions
+                .get::<http::uri::Scheme>()
+                .map(|scheme| scheme.to_string())
+        })
+        .unwrap_or_else(|| "http".to_string());
+    let host = [
+        forwarded.get("host").map(String::as_str),
+        forwarded_header("x-forwarded-host"),
+        header("host"),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(parts.uri.authority().map(|a| a.as_str()))
+    .find(|host| is_valid_host(host))
+    .map_or_else(|| "localhost".to_string(), str::to_string);
+
+    format!("{}://{}", scheme, host)
+}
+
+/// Whether `host` is a `host[:port]` authority, without user info.
+fn is_valid_host(host: &str) -> bool {
+    !host.contains('@') && host.parse::<http::uri::Authority>().is_ok()
+}
+
 impl<T: DeserializeOwned + Send> FromRequestParts for Query<T> {
     async fn from_request_parts(
         parts: &http::request::Parts,
@@ -534,38 +858,21 @@ impl<T: DeserializeOwned + Send> FromRequestParts for Cookie<T> {
     }
 }
 
-impl<T: FromStr + Send> FromRequestParts for Path<T>
-where
-    T::Err: std::fmt::Display,
-{
+impl<T: DeserializeOwned + Send> FromRequestParts for Path<T> {
     async fn from_request_parts(
         _parts: &http::request::Parts,
         params: &PathParams,
         _state: &Arc<AppState>,
     ) -> Result<Self, Error> {
-        let (param_name, value) = params.iter().next().ok_or_else(|| {
-            Error::bad_request(
-                "Missing path parameter. Ensure your route pattern includes a parameter like /:id",
-            )
-        })?;
-
-        let parsed = value.parse::<T>().map_err(|e| {
-            Error::bad_request(format!(
-                "Path parameter '{}' must be a valid {}, got '{}': {}",
-                param_name,
-                std::any::type_name::<T>(),
-                value,
-                e
-            ))
-        })?;
-
-        Ok(Path(parsed))
+        T::deserialize(path_de::PathDeserializer::new(params))
+            .map(Path)
+            .map_err(|e| Error::bad_request(e.to_string()))
     }
 }
 
 impl<T: FromRequestParts> FromRequest for T {
     async fn from_request(
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         params: &PathParams,
         state: &Arc<AppState>,
     ) -> Result<Self, Error> {
@@ -574,25 +881,15 @@ impl<T: FromRequestParts> FromRequest for T {
     }
 }
 
+/// Matches `path` against a route pattern, returning the captured parameters.
+///
+/// Supports the full pattern syntax of [`Router`](crate::router::Router),
+/// including constrained, optional and catch-all segments. Invalid patterns
+/// never match.
 pub fn extract_path_params(pattern: &str, path: &str) -> Option<PathParams> {
-    let pattern_parts: Vec<&str> = pattern.split('/').collect();
-    let path_parts: Vec<&str> = path.split('/').collect();
-
-    if pattern_parts.len() != path_parts.len() {
-        return None;
-    }
-
-    let mut params = HashMap::new();
-
-    for (pattern_part, path_part) in pattern_parts.iter().zip(path_parts.iter()) {
-        if let Some(param_name) = pattern_part.strip_prefix(':') {
-            params.insert(param_name.to_string(), path_part.to_string());
-        } else if pattern_part != path_part {
-            return None;
-        }
-    }
-
-    Some(params)
+    crate::router::pattern::Pattern::parse(pattern)
+        .ok()?
+        .matches(path)
 }
 
 // Database extractor (requires "database" feature)
@@ -662,6 +959,18 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn test_extract_path_params_catch_all() {
+        let params = extract_path_params("/files/*path", "/files/a/b.txt").unwrap();
+        assert_eq!(params.get("path"), Some(&"a/b.txt".to_string()));
+    }
+
+    #[test]
+    fn test_extract_path_params_constraint() {
+        assert!(extract_path_params("/users/:id<int>", "/users/42").is_some());
+        assert!(extract_path_params("/users/:id<int>", "/users/me").is_none());
+    }
+
     // Query extractor tests
     #[tokio::test]
     async fn test_query_extractor_success() {
@@ -0,0 +1,417 @@
+//! Serde deserializer for path parameters.
+//!
+//! A single value reads the only parameter, tuples and sequences read the
+//! parameters in the order they appear in the route pattern, and structs
+//! and maps read them by name. Each value is parsed from its segment on
+//! demand, so `Path<(String, u64)>` and `Path<MyParams>` both work.
+
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::PathParams;
+
+/// Error produced while deserializing path parameters.
+#[derive(Debug)]
+pub(crate) struct PathError(String);
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl de::Error for PathError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        PathError(msg.to_string())
+    }
+}
+
+/// Deserializes all path parameters of a request.
+pub(crate) struct PathDeserializer<'de> {
+    params: &'de PathParams,
+}
+
+impl<'de> PathDeserializer<'de> {
+    pub(crate) fn new(params: &'de PathParams) -> Self {
+        Self { params }
+    }
+
+    /// Returns the only parameter, for targets that hold a single value.
+    fn single(&self) -> Result<ValueDeserializer<'de>, PathError> {
+        let mut iter = self.params.iter();
+        match (iter.next(), iter.next()) {
+            (Some((name, value)), None) => Ok(ValueDeserializer { name, value }),
+            (None, _) => Err(PathError(
+                "Missing path parameter. Ensure your route pattern includes a parameter like /:id"
+                    .to_string(),
+            )),
+            (Some(_), Some(_)) => Err(PathError(format!(
+                "Expected 1 path parameter, found {}; extract a tuple or struct instead",
+                self.params.len()
+            ))),
+        }
+    }
+}
+
+macro_rules! single_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for PathDeserializer<'de> {
+    type Error = PathError;
+
+    single_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_unit
+        deserialize_identifier
+    }
+
+    /// An omitted optional segment (`/posts/:page?`) deserializes as `None`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.params.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.params.len() == 1 {
+            self.single()?.deserialize_any(visitor)
+        } else {
+            self.deserialize_map(visitor)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqAccess {
+            params: self.params.iter(),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if self.params.len() != len {
+            return Err(PathError(format!(
+                "Expected {} path parameters, found {}",
+                len,
+                self.params.len()
+            )));
+        }
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(MapAccess {
+            params: self.params.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct SeqAccess<'de, I: Iterator<Item = (&'de String, &'de String)>> {
+    params: I,
+}
+
+impl<'de, I: Iterator<Item = (&'de String, &'de String)>> de::SeqAccess<'de> for SeqAccess<'de, I> {
+    type Error = PathError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.params.next() {
+            Some((name, value)) => seed
+                .deserialize(ValueDeserializer { name, value })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct MapAccess<'de, I: Iterator<Item = (&'de String, &'de String)>> {
+    params: I,
+    value: Option<(&'de String, &'de String)>,
+}
+
+impl<'de, I: Iterator<Item = (&'de String, &'de String)>> de::MapAccess<'de> for MapAccess<'de, I> {
+    type Error = PathError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.params.next() {
+            Some((name, value)) => {
+                self.value = Some((name, value));
+                seed.deserialize(name.as_str().into_deserializer())
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (name, value) = self
+            .value
+            .take()
+            .ok_or_else(|| PathError("path parameter value requested before its name".into()))?;
+        seed.deserialize(ValueDeserializer { name, value })
+    }
+}
+
+/// Deserializes a single segment, parsing it into the requested type.
+struct ValueDeserializer<'de> {
+    name: &'de str,
+    value: &'de str,
+}
+
+impl ValueDeserializer<'_> {
+    fn invalid(&self, expected: &str) -> PathError {
+        PathError(format!(
+            "Path parameter '{}' must be a valid {}, got '{}'",
+            self.name, expected, self.value
+        ))
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident: $ty:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let parsed = self
+                    .value
+                    .parse::<$ty>()
+                    .map_err(|_| self.invalid(stringify!($ty)))?;
+                visitor.$visit(parsed)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = PathError;
+
+    parse_value! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char,
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if !variants.contains(&self.value) {
+            return Err(self.invalid(&format!("one of {}", variants.join(", "))));
+        }
+        visitor.visit_enum(self.value.into_deserializer())
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(PathError(format!(
+            "Path parameter '{}' is a single segment and can't hold a sequence",
+            self.name
+        )))
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    fn params(pairs: &[(&str, &str)]) -> PathParams {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn from_params<T: de::DeserializeOwned>(pairs: &[(&str, &str)]) -> Result<T, PathError> {
+        T::deserialize(PathDeserializer::new(&params(pairs)))
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Ids {
+        user_id: u64,
+        org: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Open,
+        Closed,
+    }
+
+    #[test]
+    fn test_single_value() {
+        assert_eq!(from_params::<u64>(&[("id", "42")]).unwrap(), 42);
+        assert_eq!(from_params::<String>(&[("slug", "a-b")]).unwrap(), "a-b");
+        assert_eq!(
+            from_params::<Status>(&[("status", "open")]).unwrap(),
+            Status::Open
+        );
+    }
+
+    #[test]
+    fn test_single_value_requires_one_param() {
+        let err = from_params::<u64>(&[]).unwrap_err();
+        assert!(err.to_string().contains("Missing path parameter"));
+
+        let err = from_params::<u64>(&[("a", "1"), ("b", "2")]).unwrap_err();
+        assert!(err.to_string().contains("found 2"));
+    }
+
+    #[test]
+    fn test_tuple_follows_segment_order() {
+        let pairs = [("org", "acme"), ("user_id", "7")];
+        let (org, user_id): (String, u64) = from_params(&pairs).unwrap();
+        assert_eq!(org, "acme");
+        assert_eq!(user_id, 7);
+
+        let err = from_params::<(String, u64, u64)>(&pairs).unwrap_err();
+        assert_eq!(err.to_string(), "Expected 3 path parameters, found 2");
+    }
+
+    #[test]
+    fn test_struct_by_name() {
+        let ids: Ids = from_params(&[("org", "acme"), ("user_id", "7")]).unwrap();
+        assert_eq!(
+            ids,
+            Ids {
+                user_id: 7,
+                org: "acme".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_error_names_segment() {
+        let err = from_params::<Ids>(&[("org", "acme"), ("user_id", "seven")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Path parameter 'user_id' must be a valid u64, got 'seven'"
+        );
+
+        let err =
+            from_params::<(String, Status)>(&[("org", "acme"), ("status", "gone")]).unwrap_err();
+        assert!(err.to_string().contains("'status'"));
+        assert!(err.to_string().contains("open, closed"));
+    }
+
+    #[test]
+    fn test_option_without_params_is_none() {
+        assert_eq!(from_params::<Option<u32>>(&[]).unwrap(), None);
+        assert_eq!(
+            from_params::<Option<u32>>(&[("page", "2")]).unwrap(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_missing_struct_field() {
+        let err = from_params::<Ids>(&[("org", "acme")]).unwrap_err();
+        assert!(err.to_string().contains("user_id"));
+    }
+}
@@ -5,10 +5,10 @@ use std::pin::Pin;
 use std::sync::Arc;
 
 use http::Request;
-use hyper::body::Incoming;
 
 use crate::error::ErrorVariant;
-use crate::extract::PathParams;
+use crate::extract::{PathParams, RequestBody};
+use crate::middleware::Middleware;
 use crate::response::BoxBody;
 use crate::state::AppState;
 
@@ -27,11 +27,76 @@ pub trait Handler: Clone + Send + Sync + 'static {
         None
     }
 
+    /// Media type of the success response, if it isn't `application/json`.
+    fn response_content_type() -> Option<&'static str> {
+        None
+    }
+
+    /// JSON Schema for the request body (if available).
+    fn request_schema() -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Media type of the request body, if it isn't `application/json`.
+    fn request_content_type() -> Option<&'static str> {
+        None
+    }
+
+    /// JSON Schema of the `Query<T>` type, expanded into query parameters.
+    fn query_schema() -> Option<serde_json::Value> {
+        None
+    }
+
+    /// JSON Schema of the `Path<T>` type, used for path parameter schemas.
+    fn path_schema() -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Operation summary, from the first line of the handler's doc comment.
+    fn summary() -> Option<&'static str> {
+        None
+    }
+
+    /// Operation description, from the rest of the handler's doc comment.
+    fn description() -> Option<&'static str> {
+        None
+    }
+
+    /// Tags grouping the operation, from `#[tag("...")]`.
+    fn tags() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Whether the handler is marked `#[deprecated]`.
+    fn deprecated() -> bool {
+        false
+    }
+
+    /// Explicit operationId from `#[operation_id = "..."]`, overriding [`NAME`](Self::NAME).
+    fn operation_id() -> Option<&'static str> {
+        None
+    }
+
+    /// Whether the handler is marked `#[public]` and skips authentication.
+    fn is_public() -> bool {
+        false
+    }
+
     /// Error variants for OpenAPI documentation.
     fn error_responses() -> Vec<ErrorVariant> {
         Vec::new()
     }
 
+    /// Middleware from `#[middleware(...)]`, run around this handler only.
+    fn middlewares() -> Vec<Arc<dyn Middleware>> {
+        Vec::new()
+    }
+
     /// Handle the request.
-    fn call(&self, req: Request<Incoming>, params: PathParams, state: Arc<AppState>) -> BoxFuture;
+    fn call(
+        &self,
+        req: Request<RequestBody>,
+        params: PathParams,
+        state: Arc<AppState>,
+    ) -> BoxFuture;
 }
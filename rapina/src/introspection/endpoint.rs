@@ -3,9 +3,8 @@
 use std::sync::Arc;
 
 use http::{Request, Response, StatusCode};
-use hyper::body::Incoming;
 
-use crate::extract::PathParams;
+use crate::extract::{PathParams, RequestBody};
 use crate::introspection::RouteInfo;
 use crate::response::{BoxBody, IntoResponse};
 use crate::state::AppState;
@@ -40,7 +39,7 @@ impl RouteRegistry {
 ///
 /// Returns all registered routes as JSON.
 pub async fn list_routes(
-    _req: Request<Incoming>,
+    _req: Request<RequestBody>,
     _params: PathParams,
     state: Arc<AppState>,
 ) -> Response<BoxBody> {
@@ -52,7 +51,7 @@ pub async fn list_routes(
             Response::builder()
                 .status(StatusCode::OK)
                 .header("content-type", "application/json")
-                .body(http_body_util::Full::new(bytes::Bytes::from(json)))
+                .body(crate::response::full(json))
                 .unwrap()
         }
         None => StatusCode::NOT_FOUND.into_response(),
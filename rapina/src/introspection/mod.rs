@@ -7,4 +7,4 @@ mod endpoint;
 mod route_info;
 
 pub use endpoint::{RouteRegistry, list_routes};
-pub use route_info::RouteInfo;
+pub use route_info::{PathParamInfo, RouteInfo};
@@ -3,6 +3,7 @@
 use serde::Serialize;
 
 use crate::error::ErrorVariant;
+use crate::router::pattern::{Pattern, Segment};
 
 /// Metadata about a registered route.
 ///
@@ -29,9 +30,106 @@ pub struct RouteInfo {
     /// JSON Schema for the success response.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_schema: Option<serde_json::Value>,
+    /// Media type of the success response, when it isn't `application/json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_content_type: Option<String>,
+    /// JSON Schema for the request body.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_schema: Option<serde_json::Value>,
+    /// Media type of the request body, when it isn't `application/json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_content_type: Option<String>,
+    /// JSON Schema of the query string, one property per parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_schema: Option<serde_json::Value>,
+    /// JSON Schema of the path parameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_schema: Option<serde_json::Value>,
     /// Error variants for OpenAPI documentation.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub error_responses: Vec<ErrorVariant>,
+    /// Short summary, from the first line of the handler's doc comment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Longer description, from the rest of the handler's doc comment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Tags grouping the route in the OpenAPI spec.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Whether the handler is deprecated.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// Explicit operationId, when it differs from the handler name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    /// Whether the route is accessible without authentication.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub public: bool,
+    /// Parameters captured by the path pattern, in pattern order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub path_params: Vec<PathParamInfo>,
+    /// Names of the router layers and `#[middleware]` attached to the
+    /// route, in the order they run. Global middleware isn't listed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub middleware: Vec<String>,
+}
+
+/// A parameter captured by a route's path pattern.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::introspection::RouteInfo;
+///
+/// let info = RouteInfo::new("GET", "/files/:id<int>/*rest", "get_file", None, Vec::new());
+/// assert_eq!(info.path_params[0].constraint.as_deref(), Some("int"));
+/// assert!(info.path_params[1].catch_all);
+/// ```
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PathParamInfo {
+    /// The parameter name.
+    pub name: String,
+    /// The constraint as written in the pattern (`int`, `uuid` or a regex).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<String>,
+    /// Whether the segment may be omitted (`:name?`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Whether the parameter captures the rest of the path (`*name`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub catch_all: bool,
+}
+
+impl PathParamInfo {
+    fn from_pattern(path: &str) -> Vec<Self> {
+        let Ok(pattern) = Pattern::parse(path) else {
+            return Vec::new();
+        };
+        pattern
+            .segments()
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Static(_) => None,
+                Segment::Param {
+                    name,
+                    constraint,
+                    optional,
+                } => Some(PathParamInfo {
+                    name: name.clone(),
+                    constraint: constraint.as_ref().map(|c| c.source().to_string()),
+                    optional: *optional,
+                    catch_all: false,
+                }),
+                Segment::CatchAll(name) => Some(PathParamInfo {
+                    name: name.clone(),
+                    constraint: None,
+                    optional: false,
+                    catch_all: true,
+                }),
+            })
+            .collect()
+    }
 }
 
 impl RouteInfo {
@@ -43,12 +141,26 @@ impl RouteInfo {
         response_schema: Option<serde_json::Value>,
         error_responses: Vec<ErrorVariant>,
     ) -> Self {
+        let path = path.into();
         Self {
             method: method.into(),
-            path: path.into(),
+            path_params: PathParamInfo::from_pattern(&path),
+            path,
             handler_name: handler_name.into(),
             response_schema,
+            response_content_type: None,
+            request_schema: None,
+            request_content_type: None,
+            query_schema: None,
+            path_schema: None,
             error_responses,
+            summary: None,
+            description: None,
+            tags: Vec::new(),
+            deprecated: false,
+            operation_id: None,
+            public: false,
+            middleware: Vec::new(),
         }
     }
 }
@@ -69,6 +181,27 @@ mod tests {
     fn test_route_info_with_params() {
         let info = RouteInfo::new("GET", "/users/:id", "get_user", None, Vec::new());
         assert_eq!(info.path, "/users/:id");
+        assert_eq!(info.path_params.len(), 1);
+        assert_eq!(info.path_params[0].name, "id");
+    }
+
+    #[test]
+    fn test_route_info_path_params_serialization() {
+        let info = RouteInfo::new(
+            "GET",
+            "/posts/:id<int>/:page?",
+            "get_post",
+            None,
+            Vec::new(),
+        );
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            json["path_params"],
+            serde_json::json!([
+                { "name": "id", "constraint": "int" },
+                { "name": "page", "optional": true }
+            ])
+        );
     }
 
     #[test]
@@ -85,6 +218,20 @@ mod tests {
         assert!(json.contains("\"method\":\"GET\""));
         assert!(json.contains("\"path\":\"/health\""));
         assert!(json.contains("\"handler_name\":\"health_check\""));
+        assert!(!json.contains("deprecated"));
+        assert!(!json.contains("tags"));
+    }
+
+    #[test]
+    fn test_route_info_serialize_docs() {
+        let mut info = RouteInfo::new("GET", "/health", "health_check", None, Vec::new());
+        info.summary = Some("Health check".to_string());
+        info.tags = vec!["Ops".to_string()];
+        info.deprecated = true;
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["summary"], "Health check");
+        assert_eq!(json["tags"], serde_json::json!(["Ops"]));
+        assert_eq!(json["deprecated"], true);
     }
 
     #[test]
@@ -13,6 +13,12 @@
 //! - **Structured errors** - Standardized error responses with `trace_id` for debugging
 //! - **Validation** - Built-in request validation using the `validator` crate
 //! - **Observability** - Integrated tracing for structured logging
+//! - **Streaming** - Stream large bodies and push Server-Sent Events
+//! - **File uploads** - Stream or collect `multipart/form-data` bodies, spilling large files to disk
+//! - **WebSockets** - Upgrade connections with `#[ws]`, behind the same middleware
+//! - **HTTP/2** - HTTP/1.1 and HTTP/2 (including h2c) negotiated on the same port
+//! - **TLS** - Native HTTPS with mTLS and certificate hot-reload (`tls` feature)
+//! - **API Docs** - Embedded Swagger UI, Redoc or Scalar page (`docs` feature)
 //!
 //! ## Quick Start
 //!
@@ -51,6 +57,8 @@
 //! - [`Path`](extract::Path) - Extract path parameters
 //! - [`Query`](extract::Query) - Parse query string parameters
 //! - [`Form`](extract::Form) - Parse URL-encoded form data
+//! - [`Multipart`](multipart::Multipart) - Stream `multipart/form-data` bodies
+//! - [`MultipartForm`](multipart::MultipartForm) - Collect `multipart/form-data` bodies into a struct
 //! - [`Headers`](extract::Headers) - Access request headers
 //! - [`Cookie`](extract::Cookie) - Extract and deserialize cookies
 //! - [`State`](extract::State) - Access application state
@@ -78,6 +86,7 @@
 //! Integration testing utilities:
 //!
 //! - [`TestClient`](testing::TestClient) - Test client for integration testing
+//! - `Fuzzer` - Property-based API fuzzing from the OpenAPI spec (requires the `fuzz` feature)
 
 pub mod app;
 pub mod auth;
@@ -92,14 +101,20 @@ pub mod introspection;
 pub mod middleware;
 #[cfg(feature = "database")]
 pub mod migration;
+pub mod multipart;
 pub mod observability;
 pub mod openapi;
 pub mod response;
 pub mod router;
 pub mod server;
+pub mod service;
+pub mod sse;
 pub mod state;
 pub mod test;
 pub mod testing;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod websocket;
 
 /// Convenient re-exports for common Rapina types.
 ///
@@ -117,12 +132,15 @@ pub mod prelude {
     };
     pub use crate::context::RequestContext;
     pub use crate::error::{DocumentedError, Error, ErrorVariant, IntoApiError, Result};
-    pub use crate::extract::{Context, Cookie, Form, Headers, Json, Path, Query, State, Validated};
+    pub use crate::extract::{
+        Context, Cookie, Form, Headers, Json, Path, Query, State, UrlFor, Validated,
+    };
     pub use crate::introspection::RouteInfo;
     pub use crate::middleware::{KeyExtractor, Middleware, Next, RateLimitConfig};
     pub use crate::observability::TracingConfig;
     pub use crate::response::IntoResponse;
     pub use crate::router::Router;
+    pub use crate::server::ServerConfig;
 
     pub use http::{Method, StatusCode};
     pub use schemars::JsonSchema;
@@ -130,10 +148,14 @@ pub mod prelude {
     pub use tracing;
     pub use validator::Validate;
 
-    pub use rapina_macros::{Config, delete, get, post, public, put, schema};
+    pub use rapina_macros::{
+        Config, delete, get, head, middleware, options, patch, post, public, put, schema, tag, ws,
+    };
 }
 
 // Re-export dependencies so users don't need to add them to their Cargo.toml
+pub use bytes;
+pub use futures_util;
 pub use http;
 pub use hyper;
 pub use schemars;
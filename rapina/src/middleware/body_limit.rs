@@ -1,14 +1,20 @@
-use hyper::body::Incoming;
 use hyper::{Request, Response};
 
 use crate::context::RequestContext;
 use crate::error::Error;
+use crate::extract::RequestBody;
 use crate::response::{BoxBody, IntoResponse};
 
 use super::{BoxFuture, Middleware, Next};
 
 const DEFAULT_MAX_SIZE: usize = 1024 * 1024; // 1MB
 
+/// The body size limit set by [`BodyLimitMiddleware`], stored in the
+/// request extensions so extractors that stream the body can enforce it
+/// when the request has no `Content-Length`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BodyLimit(pub(crate) usize);
+
 #[derive(Debug, Clone)]
 pub struct BodyLimitMiddleware {
     pub(crate) max_size: usize,
@@ -29,7 +35,7 @@ impl Default for BodyLimitMiddleware {
 impl Middleware for BodyLimitMiddleware {
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
+        mut req: Request<RequestBody>,
         _ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
@@ -41,8 +47,9 @@ impl Middleware for BodyLimitMiddleware {
                 .and_then(|v| v.parse::<usize>().ok());
 
             if content_length.is_some_and(|len| len > self.max_size) {
-                return Error::bad_request("body too large").into_response();
+                return Error::payload_too_large("body too large").into_response();
             }
+            req.extensions_mut().insert(BodyLimit(self.max_size));
 
             next.run(req).await
         })
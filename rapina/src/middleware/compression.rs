@@ -1,15 +1,18 @@
 use std::io::Write;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
 
 use bytes::Bytes;
 use flate2::Compression;
 use flate2::write::{DeflateEncoder, GzEncoder};
-use http::{HeaderValue, Response, header};
-use http_body_util::{BodyExt, Full};
+use http::{HeaderMap, HeaderValue, Response, header};
+use http_body_util::BodyExt;
 use hyper::Request;
-use hyper::body::Incoming;
+use hyper::body::{Body, Frame, SizeHint};
 
 use crate::context::RequestContext;
-use crate::response::BoxBody;
+use crate::extract::RequestBody;
+use crate::response::{BoxBody, BoxError, empty, full};
 
 use super::{BoxFuture, Middleware, Next};
 
@@ -53,6 +56,116 @@ impl Algorithm {
             }
         }
     }
+
+    fn encoder(&self, level: Compression) -> Encoder {
+        match self {
+            Algorithm::Gzip => Encoder::Gzip(GzEncoder::new(Vec::new(), level)),
+            Algorithm::Deflate => Encoder::Deflate(DeflateEncoder::new(Vec::new(), level)),
+        }
+    }
+}
+
+/// An incremental encoder used for bodies whose size is not known upfront.
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    Deflate(DeflateEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    /// Compresses a chunk and returns everything the encoder produced so far.
+    ///
+    /// The encoder is flushed after every chunk so that clients receive data
+    /// as soon as the handler produces it, rather than once the compressor's
+    /// internal buffer fills up.
+    fn encode(&mut self, data: &[u8]) -> std::io::Result<Bytes> {
+        let output = match self {
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+            Encoder::Deflate(encoder) => {
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+        };
+        Ok(Bytes::from(std::mem::take(output)))
+    }
+
+    fn finish(self) -> std::io::Result<Bytes> {
+        let output = match self {
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            Encoder::Deflate(encoder) => encoder.finish()?,
+        };
+        Ok(Bytes::from(output))
+    }
+}
+
+/// A body that compresses the frames of another body as they arrive.
+struct CompressedBody {
+    inner: BoxBody,
+    encoder: Option<Encoder>,
+    trailers: Option<HeaderMap>,
+}
+
+impl CompressedBody {
+    fn new(inner: BoxBody, encoder: Encoder) -> Self {
+        Self {
+            inner,
+            encoder: Some(encoder),
+            trailers: None,
+        }
+    }
+}
+
+impl Body for CompressedBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        let this = self.get_mut();
+
+        loop {
+            let Some(encoder) = this.encoder.as_mut() else {
+                return Poll::Ready(this.trailers.take().map(|t| Ok(Frame::trailers(t))));
+            };
+
+            match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => {
+                        let output = encoder.encode(&data)?;
+                        if !output.is_empty() {
+                            return Poll::Ready(Some(Ok(Frame::data(output))));
+                        }
+                    }
+                    // Trailers end the data stream, so the encoder is
+                    // finished first and the trailers sent on the next poll.
+                    Err(frame) => {
+                        this.trailers = frame.into_trailers().ok();
+                        let output = this.encoder.take().unwrap().finish()?;
+                        return Poll::Ready(Some(Ok(Frame::data(output))));
+                    }
+                },
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    let output = this.encoder.take().unwrap().finish()?;
+                    return Poll::Ready(Some(Ok(Frame::data(output))));
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.encoder.is_none() && self.trailers.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -109,6 +222,17 @@ impl CompressionMiddleware {
     }
 }
 
+fn set_encoding_headers(response: &mut Response<BoxBody>, algorithm: Algorithm) {
+    response.headers_mut().insert(
+        header::CONTENT_ENCODING,
+        HeaderValue::from_static(algorithm.content_encoding()),
+    );
+    response.headers_mut().remove(header::CONTENT_LENGTH);
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+}
+
 impl Default for CompressionMiddleware {
     fn default() -> Self {
         Self::new(CompressionConfig::default())
@@ -118,7 +242,7 @@ impl Default for CompressionMiddleware {
 impl Middleware for CompressionMiddleware {
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         _ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
@@ -145,36 +269,39 @@ impl Middleware for CompressionMiddleware {
                 _ => return response,
             };
 
+            let level = Compression::new(self.config.level);
+
+            // Bodies of unknown length (e.g. `Stream` responses) are compressed
+            // chunk by chunk instead of being buffered in memory.
+            if response.body().size_hint().exact().is_none() {
+                let mut response = response
+                    .map(|body| CompressedBody::new(body, algorithm.encoder(level)).boxed_unsync());
+                set_encoding_headers(&mut response, algorithm);
+                return response;
+            }
+
             let (parts, body) = response.into_parts();
             let body_bytes = match body.collect().await {
                 Ok(collected) => collected.to_bytes(),
-                Err(_) => return Response::from_parts(parts, Full::new(Bytes::new())),
+                Err(_) => return Response::from_parts(parts, empty()),
             };
 
             if body_bytes.len() < self.config.min_size {
-                return Response::from_parts(parts, Full::new(body_bytes));
+                return Response::from_parts(parts, full(body_bytes));
             }
 
-            let level = Compression::new(self.config.level);
             let compressed = match algorithm.compress(&body_bytes, level) {
                 Ok(data) => data,
-                Err(_) => return Response::from_parts(parts, Full::new(body_bytes)),
+                Err(_) => return Response::from_parts(parts, full(body_bytes)),
             };
 
             // not worth it
             if compressed.len() >= body_bytes.len() {
-                return Response::from_parts(parts, Full::new(body_bytes));
+                return Response::from_parts(parts, full(body_bytes));
             }
 
-            let mut response = Response::from_parts(parts, Full::new(Bytes::from(compressed)));
-            response.headers_mut().insert(
-                header::CONTENT_ENCODING,
-                HeaderValue::from_static(algorithm.content_encoding()),
-            );
-            response.headers_mut().remove(header::CONTENT_LENGTH);
-            response
-                .headers_mut()
-                .insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+            let mut response = Response::from_parts(parts, full(compressed));
+            set_encoding_headers(&mut response, algorithm);
 
             response
         })
@@ -184,6 +311,7 @@ impl Middleware for CompressionMiddleware {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::response::IntoResponse;
 
     #[test]
     fn test_config_default() {
@@ -229,6 +357,29 @@ mod tests {
         assert!(compressed.len() < data.len());
     }
 
+    #[tokio::test]
+    async fn test_compressed_body_streams_chunks() {
+        use flate2::read::DeflateDecoder;
+        use std::io::Read;
+
+        let chunks = futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(Bytes::from("hello ")),
+            Ok(Bytes::from("from ")),
+            Ok(Bytes::from("rapina")),
+        ]);
+        let inner = crate::response::Stream::new(chunks)
+            .into_response()
+            .into_body();
+        let body = CompressedBody::new(inner, Algorithm::Deflate.encoder(Compression::default()));
+
+        let compressed = body.collect().await.unwrap().to_bytes();
+        let mut decoded = String::new();
+        DeflateDecoder::new(&compressed[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "hello from rapina");
+    }
+
     #[test]
     fn test_is_compressible_content_type() {
         assert!(CompressionMiddleware::is_compressible_content_type(Some(
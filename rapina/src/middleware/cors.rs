@@ -4,9 +4,9 @@
 //! handling preflight OPTIONS requests and adding appropriate headers.
 
 use http::{HeaderValue, Method, Request, Response, StatusCode, header};
-use hyper::body::Incoming;
 
 use crate::context::RequestContext;
+use crate::extract::RequestBody;
 use crate::response::BoxBody;
 
 use super::{BoxFuture, Middleware, Next};
@@ -170,7 +170,7 @@ impl CorsMiddleware {
 impl Middleware for CorsMiddleware {
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         _ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
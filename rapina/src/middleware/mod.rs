@@ -9,6 +9,16 @@
 //! - [`BodyLimitMiddleware`] - Limit request body size
 //! - [`TraceIdMiddleware`] - Add trace IDs to requests/responses
 //! - [`RequestLogMiddleware`] - Structured request logging
+//! - [`TowerLayerMiddleware`] - Run a tower `Layer`, e.g. from `tower-http`
+//!
+//! # Ordering
+//!
+//! Global middleware, added with [`Rapina::middleware`](crate::app::Rapina::middleware),
+//! runs first for every request, in the order it was added, and then routes
+//! the request. For a matched route, the layers added with
+//! [`Router::layer`] run next, from the outermost group inwards, followed
+//! by the handler's own `#[middleware(...)]` attributes and the handler.
+//! Unmatched requests only go through global middleware.
 
 mod body_limit;
 mod compression;
@@ -16,26 +26,29 @@ mod cors;
 mod rate_limit;
 mod request_log;
 mod timeout;
+mod tower_layer;
 mod trace_id;
 
+pub(crate) use body_limit::BodyLimit;
 pub use body_limit::BodyLimitMiddleware;
 pub use compression::{CompressionConfig, CompressionMiddleware};
 pub use cors::{AllowedHeaders, AllowedMethods, AllowedOrigins, CorsConfig, CorsMiddleware};
 pub use rate_limit::{KeyExtractor, RateLimitConfig, RateLimitMiddleware};
 pub use request_log::RequestLogMiddleware;
 pub use timeout::TimeoutMiddleware;
+pub use tower_layer::{NextService, TowerLayerMiddleware};
 pub use trace_id::{TRACE_ID_HEADER, TraceIdMiddleware};
 
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
-use hyper::body::Incoming;
 use hyper::{Request, Response};
 
 use crate::context::RequestContext;
+use crate::extract::{PathParams, RequestBody};
 use crate::response::BoxBody;
-use crate::router::Router;
+use crate::router::{HandlerFn, Router};
 use crate::state::AppState;
 
 /// A boxed future type used by middleware.
@@ -56,7 +69,7 @@ pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 /// impl Middleware for LoggingMiddleware {
 ///     fn handle<'a>(
 ///         &'a self,
-///         req: Request<Incoming>,
+///         req: Request<RequestBody>,
 ///         ctx: &'a RequestContext,
 ///         next: Next<'a>,
 ///     ) -> BoxFuture<'a, Response<BoxBody>> {
@@ -71,20 +84,37 @@ pub trait Middleware: Send + Sync + 'static {
     /// Handles the request, optionally modifying it or the response.
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>>;
+
+    /// The name shown for this middleware in route introspection.
+    ///
+    /// Defaults to the type name without its module path.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
 }
 
 /// Represents the next middleware or handler in the chain.
 pub struct Next<'a> {
     middlewares: &'a [Arc<dyn Middleware>],
-    router: &'a Router,
+    endpoint: Endpoint<'a>,
     state: &'a Arc<AppState>,
     ctx: &'a RequestContext,
 }
 
+/// What runs once all middleware in a chain has been called.
+enum Endpoint<'a> {
+    /// Global middleware ends by routing the request.
+    Router(&'a Router),
+    /// Route and router middleware ends by calling the matched handler.
+    Handler(&'a HandlerFn, PathParams),
+}
+
 impl<'a> Next<'a> {
     pub(crate) fn new(
         middlewares: &'a [Arc<dyn Middleware>],
@@ -94,24 +124,45 @@ impl<'a> Next<'a> {
     ) -> Self {
         Self {
             middlewares,
-            router,
+            endpoint: Endpoint::Router(router),
+            state,
+            ctx,
+        }
+    }
+
+    /// Creates a chain that ends by calling a matched route's handler.
+    pub(crate) fn for_handler(
+        middlewares: &'a [Arc<dyn Middleware>],
+        handler: &'a HandlerFn,
+        params: PathParams,
+        state: &'a Arc<AppState>,
+        ctx: &'a RequestContext,
+    ) -> Self {
+        Self {
+            middlewares,
+            endpoint: Endpoint::Handler(handler, params),
             state,
             ctx,
         }
     }
 
     /// Runs the next middleware or handler in the chain.
-    pub async fn run(self, req: Request<Incoming>) -> Response<BoxBody> {
+    pub async fn run(self, req: Request<RequestBody>) -> Response<BoxBody> {
         if let Some((current, rest)) = self.middlewares.split_first() {
             let next = Next {
                 middlewares: rest,
-                router: self.router,
+                endpoint: self.endpoint,
                 state: self.state,
                 ctx: self.ctx,
             };
             current.handle(req, self.ctx, next).await
         } else {
-            self.router.handle(req, self.state).await
+            match self.endpoint {
+                Endpoint::Router(router) => router.handle(req, self.state).await,
+                Endpoint::Handler(handler, params) => {
+                    handler(req, params, self.state.clone()).await
+                }
+            }
         }
     }
 }
@@ -138,11 +189,14 @@ impl MiddlewareStack {
 
     pub async fn execute(
         &self,
-        req: Request<Incoming>,
+        mut req: Request<RequestBody>,
         router: &Router,
         state: &Arc<AppState>,
         ctx: &RequestContext,
     ) -> Response<BoxBody> {
+        if let Some(route) = router.matched_route(req.method(), req.uri().path()) {
+            req.extensions_mut().insert(route);
+        }
         let next = Next::new(&self.middlewares, router, state, ctx);
         next.run(req).await
     }
@@ -169,7 +223,7 @@ mod tests {
     impl Middleware for TestMiddleware {
         fn handle<'a>(
             &'a self,
-            req: Request<Incoming>,
+            req: Request<RequestBody>,
             _ctx: &'a RequestContext,
             next: Next<'a>,
         ) -> BoxFuture<'a, Response<BoxBody>> {
@@ -214,6 +268,12 @@ mod tests {
         assert_eq!(stack.middlewares.len(), 3);
     }
 
+    #[test]
+    fn test_middleware_name_drops_module_path() {
+        assert_eq!(TestMiddleware.name(), "TestMiddleware");
+        assert_eq!(TimeoutMiddleware::default().name(), "TimeoutMiddleware");
+    }
+
     #[test]
     fn test_timeout_middleware_new() {
         let mw = TimeoutMiddleware::new(Duration::from_secs(60));
@@ -3,17 +3,17 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
-use hyper::body::Incoming;
 use hyper::{Request, Response};
 
 use crate::context::RequestContext;
 use crate::error::Error;
+use crate::extract::RequestBody;
 use crate::response::{BoxBody, IntoResponse};
 
 use super::{BoxFuture, Middleware, Next};
 
 /// Type alias for custom key extractor functions
-type KeyExtractorFn = Arc<dyn Fn(&Request<Incoming>) -> String + Send + Sync>;
+type KeyExtractorFn = Arc<dyn Fn(&Request<RequestBody>) -> String + Send + Sync>;
 
 /// How often to run cleanup (every N requests)
 const CLEANUP_INTERVAL: u64 = 1000;
@@ -48,14 +48,14 @@ impl std::fmt::Debug for KeyExtractor {
 
 impl KeyExtractor {
     /// Extract the rate limit key from a request
-    fn extract(&self, req: &Request<Incoming>) -> String {
+    fn extract(&self, req: &Request<RequestBody>) -> String {
         match self {
             KeyExtractor::Ip => Self::extract_ip(req),
             KeyExtractor::Custom(f) => f(req),
         }
     }
 
-    fn extract_ip(req: &Request<Incoming>) -> String {
+    fn extract_ip(req: &Request<RequestBody>) -> String {
         // X-Forwarded-For can have multiple IPs: "client, proxy1, proxy2"
         // We want the leftmost (original client)
         if let Some(ip) = req
@@ -183,7 +183,7 @@ impl RateLimitMiddleware {
 impl Middleware for RateLimitMiddleware {
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
@@ -1,8 +1,8 @@
-use hyper::body::Incoming;
 use hyper::{Request, Response};
 use tracing::{Instrument, info, info_span};
 
 use crate::context::RequestContext;
+use crate::extract::RequestBody;
 use crate::response::BoxBody;
 
 use super::{BoxFuture, Middleware, Next};
@@ -25,7 +25,7 @@ impl Default for RequestLogMiddleware {
 impl Middleware for RequestLogMiddleware {
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
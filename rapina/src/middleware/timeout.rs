@@ -1,14 +1,19 @@
 use std::time::Duration;
 
-use hyper::body::Incoming;
 use hyper::{Request, Response};
 
 use crate::context::RequestContext;
 use crate::error::Error;
+use crate::extract::RequestBody;
 use crate::response::{BoxBody, IntoResponse};
 
 use super::{BoxFuture, Middleware, Next};
 
+/// Fails requests whose handler takes longer than the configured duration.
+///
+/// The timeout covers producing the response, not sending its body: once a
+/// handler returns a streamed body, chunks are sent for as long as the
+/// stream keeps producing them.
 #[derive(Debug, Clone)]
 pub struct TimeoutMiddleware {
     pub(crate) duration: Duration,
@@ -29,7 +34,7 @@ impl Default for TimeoutMiddleware {
 impl Middleware for TimeoutMiddleware {
     fn handle<'a>(
         &'a self,
-        req: Request<Incoming>,
+        req: Request<RequestBody>,
         _ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
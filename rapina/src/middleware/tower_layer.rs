@@ -0,0 +1,192 @@
+use std::any::type_name;
+use std::convert::Infallible;
+use std::future::Future;
+use std::task::{Context, Poll};
+
+use hyper::body::Body;
+use hyper::{Request, Response};
+use tokio::sync::{Mutex, mpsc, oneshot};
+use tower::{Layer, Service};
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::extract::RequestBody;
+use crate::response::{BoxBody, BoxError, IntoResponse};
+use crate::service::{box_request, box_response, service_error};
+
+use super::{BoxFuture, Middleware, Next};
+
+/// Runs a tower [`Layer`] as middleware, e.g. one from `tower-http`.
+///
+/// The layer wraps a [`NextService`] standing for the rest of the chain,
+/// and the resulting service is built once, in [`new`](Self::new), so
+/// layers that keep state, such as concurrency or rate limits, share it
+/// between requests. Requests wait for the service to be ready one at a
+/// time. Layers may change the request body type; bodies are boxed back
+/// into a [`RequestBody`] before reaching Rapina. Errors returned by the
+/// layered service become `500` responses.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::middleware::TowerLayerMiddleware;
+/// use rapina::prelude::*;
+/// use tower_http::set_header::SetResponseHeaderLayer;
+///
+/// let app = Rapina::new().middleware(TowerLayerMiddleware::new(
+///     SetResponseHeaderLayer::overriding(
+///         http::header::SERVER,
+///         http::HeaderValue::from_static("rapina"),
+///     ),
+/// ));
+/// ```
+pub struct TowerLayerMiddleware<L: Layer<NextService>> {
+    service: Mutex<L::Service>,
+}
+
+impl<L: Layer<NextService>> TowerLayerMiddleware<L> {
+    pub fn new(layer: L) -> Self {
+        Self {
+            service: Mutex::new(layer.layer(NextService { _private: () })),
+        }
+    }
+}
+
+impl<L: Layer<NextService>> std::fmt::Debug for TowerLayerMiddleware<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TowerLayerMiddleware")
+            .field("layer", &type_name::<L>())
+            .finish()
+    }
+}
+
+impl<L, S, ResBody> Middleware for TowerLayerMiddleware<L>
+where
+    L: Layer<NextService, Service = S> + Send + Sync + 'static,
+    S: Service<Request<RequestBody>, Response = Response<ResBody>> + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    ResBody: Body<Data = bytes::Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    fn handle<'a>(
+        &'a self,
+        mut req: Request<RequestBody>,
+        _ctx: &'a RequestContext,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Response<BoxBody>> {
+        // The layered service hands the request back to this call through
+        // the sender it carries, see `NextService`.
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        req.extensions_mut().insert(ChainSender(tx));
+        let mut outer = Box::pin(async move {
+            let future = {
+                let mut service = self.service.lock().await;
+                std::future::poll_fn(|cx| service.poll_ready(cx)).await?;
+                service.call(req)
+            };
+            future.await
+        });
+
+        // The layered service runs the rest of the chain through `rx`,
+        // so both are polled together here.
+        let mut next = Some(next);
+        let mut inner: Option<(BoxFuture<'a, Response<BoxBody>>, Reply)> = None;
+        Box::pin(std::future::poll_fn(move |cx| {
+            if let Some((future, _)) = &mut inner
+                && let Poll::Ready(response) = future.as_mut().poll(cx)
+                && let Some((_, reply)) = inner.take()
+            {
+                let _ = reply.send(response);
+            }
+            while let Poll::Ready(Some((req, reply))) = rx.poll_recv(cx) {
+                match next.take() {
+                    Some(next) => {
+                        inner = Some((Box::pin(next.run(req)), reply));
+                        cx.waker().wake_by_ref();
+                    }
+                    None => {
+                        let _ = reply.send(
+                            Error::internal("the rest of the chain can only run once")
+                                .into_response(),
+                        );
+                    }
+                }
+            }
+            outer.as_mut().poll(cx).map(|result| match result {
+                Ok(response) => box_response(response),
+                Err(e) => service_error(e.into()),
+            })
+        }))
+    }
+
+    fn name(&self) -> &'static str {
+        let name = type_name::<L>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
+}
+
+type Reply = oneshot::Sender<Response<BoxBody>>;
+
+/// Carries a request from the layered service back to the
+/// [`TowerLayerMiddleware`] call that sent it.
+#[derive(Clone)]
+struct ChainSender(mpsc::UnboundedSender<(Request<RequestBody>, Reply)>);
+
+/// The rest of the middleware chain, as the service wrapped by a
+/// [`TowerLayerMiddleware`]'s layer.
+///
+/// Requests find their way back through their extensions, so layers must
+/// keep the extensions of the requests they pass on.
+#[derive(Debug, Clone)]
+pub struct NextService {
+    _private: (),
+}
+
+impl<B> Service<Request<B>> for NextService
+where
+    B: Body + Send + Sync + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let mut req = box_request(req);
+        let Some(ChainSender(tx)) = req.extensions_mut().remove::<ChainSender>() else {
+            return Box::pin(async {
+                Ok(
+                    Error::internal("request lost its way back to the middleware chain")
+                        .into_response(),
+                )
+            });
+        };
+        Box::pin(async move {
+            let (reply, response) = oneshot::channel();
+            if tx.send((req, reply)).is_err() {
+                return Ok(Error::internal("middleware chain has ended").into_response());
+            }
+            Ok(response
+                .await
+                .unwrap_or_else(|_| Error::internal("middleware chain has ended").into_response()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::layer::util::Identity;
+
+    #[test]
+    fn test_name_is_the_layer_name() {
+        let middleware = TowerLayerMiddleware::new(Identity::new());
+        assert_eq!(middleware.name(), "Identity");
+    }
+}
@@ -1,8 +1,8 @@
-use hyper::body::Incoming;
 use hyper::header::HeaderValue;
 use hyper::{Request, Response};
 
 use crate::context::RequestContext;
+use crate::extract::RequestBody;
 use crate::response::BoxBody;
 
 use super::{BoxFuture, Middleware, Next};
@@ -27,7 +27,7 @@ impl Default for TraceIdMiddleware {
 impl Middleware for TraceIdMiddleware {
     fn handle<'a>(
         &'a self,
-        mut req: Request<Incoming>,
+        mut req: Request<RequestBody>,
         ctx: &'a RequestContext,
         next: Next<'a>,
     ) -> BoxFuture<'a, Response<BoxBody>> {
@@ -0,0 +1,653 @@
+//! Extractors for `multipart/form-data` request bodies.
+//!
+//! [`Multipart`] streams the parts of the body one at a time, which is what
+//! large uploads want. [`MultipartForm`] collects the whole body into a
+//! struct deriving [`MultipartForm`](macro@MultipartForm), mixing text
+//! fields and [`UploadedFile`]s.
+//!
+//! Limits come from a [`MultipartConfig`] registered as application state,
+//! and the total size never exceeds the limit set by
+//! [`BodyLimitMiddleware`](crate::middleware::BodyLimitMiddleware), even for
+//! chunked requests without a `Content-Length`.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use rapina::prelude::*;
+//! use rapina::multipart::{Multipart, MultipartForm, UploadedFile};
+//!
+//! #[post("/raw")]
+//! async fn raw(mut multipart: Multipart) -> Result<String> {
+//!     let mut total = 0;
+//!     while let Some(mut field) = multipart.next_field().await? {
+//!         while let Some(chunk) = field.chunk().await? {
+//!             total += chunk.len();
+//!         }
+//!     }
+//!     Ok(format!("received {} bytes", total))
+//! }
+//!
+//! #[derive(MultipartForm)]
+//! struct Avatar {
+//!     description: Option<String>,
+//!     file: UploadedFile,
+//! }
+//!
+//! #[post("/avatar")]
+//! async fn avatar(form: MultipartForm<Avatar>) -> Result<String> {
+//!     let avatar = form.into_inner();
+//!     avatar.file.persist("/var/avatars/latest").await.map_err(|e| Error::internal(e.to_string()))?;
+//!     Ok(format!("stored {} bytes", avatar.file.size()))
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http::{HeaderMap, Request};
+use http_body_util::BodyExt;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::Error;
+use crate::extract::{FromRequest, PathParams, RequestBody};
+use crate::middleware::BodyLimit;
+use crate::state::AppState;
+
+pub use rapina_macros::MultipartForm;
+
+/// The media type handled by the multipart extractors.
+pub const MULTIPART_CONTENT_TYPE: &str = "multipart/form-data";
+
+const DEFAULT_MAX_TOTAL_SIZE: usize = 10 * 1024 * 1024; // 10MB
+
+/// Size limits and storage settings for multipart bodies.
+///
+/// Register it as state to override the defaults:
+///
+/// ```
+/// use rapina::prelude::*;
+/// use rapina::multipart::MultipartConfig;
+///
+/// let app = Rapina::new().state(
+///     MultipartConfig::new()
+///         .max_total_size(50 * 1024 * 1024)
+///         .max_field_size(20 * 1024 * 1024)
+///         .spill_to_disk(1024 * 1024),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultipartConfig {
+    /// Maximum size of the whole body.
+    pub max_total_size: usize,
+    /// Maximum size of a single field. Only the total is limited when `None`.
+    pub max_field_size: Option<usize>,
+    /// Files larger than this are written to a temporary file instead of
+    /// being kept in memory. Files are always kept in memory when `None`.
+    pub spill_threshold: Option<usize>,
+}
+
+impl MultipartConfig {
+    /// Creates a config with a 10MB total limit and in-memory files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size of the whole body.
+    pub fn max_total_size(mut self, max: usize) -> Self {
+        self.max_total_size = max;
+        self
+    }
+
+    /// Sets the maximum size of a single field.
+    pub fn max_field_size(mut self, max: usize) -> Self {
+        self.max_field_size = Some(max);
+        self
+    }
+
+    /// Writes uploaded files larger than `threshold` bytes to temporary files.
+    pub fn spill_to_disk(mut self, threshold: usize) -> Self {
+        self.spill_threshold = Some(threshold);
+        self
+    }
+}
+
+impl Default for MultipartConfig {
+    fn default() -> Self {
+        Self {
+            max_total_size: DEFAULT_MAX_TOTAL_SIZE,
+            max_field_size: None,
+            spill_threshold: None,
+        }
+    }
+}
+
+/// Streams the parts of a `multipart/form-data` body.
+///
+/// Fields must be read in order: [`next_field`](Self::next_field) can only
+/// be called once the previous field has been dropped or fully read.
+///
+/// Responds with 400 Bad Request when the body isn't valid multipart data,
+/// and 413 Payload Too Large when a size limit is exceeded.
+pub struct Multipart {
+    inner: multer::Multipart<'static>,
+    spill_threshold: Option<usize>,
+}
+
+impl Multipart {
+    /// Returns the next field, or `None` once the body is exhausted.
+    pub async fn next_field(&mut self) -> Result<Option<Field>, Error> {
+        let field = self.inner.next_field().await.map_err(multipart_error)?;
+        Ok(field.map(|inner| Field { inner }))
+    }
+
+    /// Reads every named field, grouping the values by field name.
+    ///
+    /// Parts with a filename become [`Part::File`], everything else is
+    /// read as text.
+    pub async fn into_parts(mut self) -> Result<HashMap<String, Vec<Part>>, Error> {
+        let mut parts: HashMap<String, Vec<Part>> = HashMap::new();
+
+        while let Some(mut field) = self.next_field().await? {
+            let Some(name) = field.name().map(str::to_string) else {
+                continue;
+            };
+
+            let part = if field.file_name().is_some() {
+                Part::File(UploadedFile::read(&mut field, self.spill_threshold).await?)
+            } else {
+                Part::Text(field.text().await?)
+            };
+            parts.entry(name).or_default().push(part);
+        }
+
+        Ok(parts)
+    }
+}
+
+impl FromRequest for Multipart {
+    async fn from_request(
+        req: Request<RequestBody>,
+        _params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let content_type = req
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok());
+
+        if !content_type
+            .map(|ct| ct.starts_with(MULTIPART_CONTENT_TYPE))
+            .unwrap_or(false)
+        {
+            return Err(Error::bad_request(format!(
+                "Expected Content-Type '{}', got '{}'",
+                MULTIPART_CONTENT_TYPE,
+                content_type.unwrap_or("none")
+            )));
+        }
+
+        let boundary = multer::parse_boundary(content_type.unwrap_or_default())
+            .map_err(|e| Error::bad_request(format!("Invalid multipart Content-Type: {}", e)))?;
+
+        let config = state.get::<MultipartConfig>().cloned().unwrap_or_default();
+        let max_total_size = match req.extensions().get::<BodyLimit>() {
+            Some(limit) => config.max_total_size.min(limit.0),
+            None => config.max_total_size,
+        };
+        let mut size_limit = multer::SizeLimit::new().whole_stream(max_total_size as u64);
+        if let Some(max) = config.max_field_size {
+            size_limit = size_limit.per_field(max as u64);
+        }
+
+        let stream = req.into_body().into_data_stream();
+        let inner = multer::Multipart::with_constraints(
+            stream,
+            boundary,
+            multer::Constraints::new().size_limit(size_limit),
+        );
+
+        Ok(Multipart {
+            inner,
+            spill_threshold: config.spill_threshold,
+        })
+    }
+}
+
+/// A single part of a multipart body.
+pub struct Field {
+    inner: multer::Field<'static>,
+}
+
+impl Field {
+    /// Returns the field name from the `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    /// Returns the filename, if the part is a file upload.
+    pub fn file_name(&self) -> Option<&str> {
+        self.inner.file_name()
+    }
+
+    /// Returns the part's `Content-Type`.
+    pub fn content_type(&self) -> Option<&str> {
+        self.inner.content_type().map(|mime| mime.as_ref())
+    }
+
+    /// Returns all headers of the part.
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+
+    /// Returns the next chunk of the part's data, or `None` at its end.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        self.inner.chunk().await.map_err(multipart_error)
+    }
+
+    /// Reads the whole part into memory.
+    pub async fn bytes(self) -> Result<Bytes, Error> {
+        self.inner.bytes().await.map_err(multipart_error)
+    }
+
+    /// Reads the whole part as UTF-8 text.
+    pub async fn text(self) -> Result<String, Error> {
+        self.inner.text().await.map_err(multipart_error)
+    }
+}
+
+fn multipart_error(err: multer::Error) -> Error {
+    match err {
+        multer::Error::FieldSizeExceeded { limit, field_name } => {
+            Error::payload_too_large(format!(
+                "multipart field '{}' exceeds the {} byte limit",
+                field_name.unwrap_or_default(),
+                limit
+            ))
+        }
+        multer::Error::StreamSizeExceeded { limit } => {
+            Error::payload_too_large(format!("multipart body exceeds the {} byte limit", limit))
+        }
+        multer::Error::StreamReadFailed(err) => {
+            crate::extract::body_error(err, "Failed to read multipart body")
+        }
+        err => Error::bad_request(format!("Invalid multipart body: {}", err)),
+    }
+}
+
+/// A file received in a multipart body.
+///
+/// Small files are kept in memory. With
+/// [`MultipartConfig::spill_to_disk`], larger ones are written to a
+/// temporary file that is deleted when the `UploadedFile` is dropped.
+/// Use [`persist`](Self::persist) to keep a copy of it.
+#[derive(Debug)]
+pub struct UploadedFile {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    size: usize,
+    data: FileData,
+}
+
+#[derive(Debug)]
+enum FileData {
+    Memory(Bytes),
+    Disk(tempfile::NamedTempFile),
+}
+
+impl UploadedFile {
+    async fn read(field: &mut Field, spill_threshold: Option<usize>) -> Result<Self, Error> {
+        let mut buffer = Vec::new();
+        let mut file: Option<(tempfile::NamedTempFile, tokio::fs::File)> = None;
+        let mut size = 0;
+
+        while let Some(chunk) = field.chunk().await? {
+            size += chunk.len();
+            match &mut file {
+                Some((_, writer)) => writer.write_all(&chunk).await.map_err(spill_error)?,
+                None if spill_threshold.is_some_and(|threshold| size > threshold) => {
+                    let temp = tempfile::NamedTempFile::new().map_err(spill_error)?;
+                    let mut writer = tokio::fs::File::from_std(temp.reopen().map_err(spill_error)?);
+                    writer.write_all(&buffer).await.map_err(spill_error)?;
+                    writer.write_all(&chunk).await.map_err(spill_error)?;
+                    buffer = Vec::new();
+                    file = Some((temp, writer));
+                }
+                None => buffer.extend_from_slice(&chunk),
+            }
+        }
+
+        let data = match file {
+            Some((temp, mut writer)) => {
+                writer.flush().await.map_err(spill_error)?;
+                FileData::Disk(temp)
+            }
+            None => FileData::Memory(Bytes::from(buffer)),
+        };
+
+        Ok(Self {
+            file_name: field.file_name().map(str::to_string),
+            content_type: field.content_type().map(str::to_string),
+            size,
+            data,
+        })
+    }
+
+    /// Returns the filename sent by the client.
+    ///
+    /// This is untrusted input; don't use it as a path without sanitizing it.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Returns the content type sent by the client.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Returns the size of the file in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the path of the temporary file, if the upload was spilled to disk.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.data {
+            FileData::Memory(_) => None,
+            FileData::Disk(temp) => Some(temp.path()),
+        }
+    }
+
+    /// Returns the file's contents, reading them from disk if needed.
+    pub async fn bytes(&self) -> std::io::Result<Bytes> {
+        match &self.data {
+            FileData::Memory(bytes) => Ok(bytes.clone()),
+            FileData::Disk(temp) => tokio::fs::read(temp.path()).await.map(Bytes::from),
+        }
+    }
+
+    /// Writes the file to `path`, copying the temporary file if it was spilled to disk.
+    pub async fn persist(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        match &self.data {
+            FileData::Memory(bytes) => tokio::fs::write(path, bytes).await,
+            FileData::Disk(temp) => tokio::fs::copy(temp.path(), path).await.map(|_| ()),
+        }
+    }
+}
+
+fn spill_error(err: std::io::Error) -> Error {
+    Error::internal(format!("failed to store uploaded file: {}", err))
+}
+
+/// A value read from a multipart body by [`Multipart::into_parts`].
+#[derive(Debug)]
+pub enum Part {
+    /// A field without a filename.
+    Text(String),
+    /// A file upload.
+    File(UploadedFile),
+}
+
+/// Types that can be built from all values sent under one field name.
+///
+/// Implemented for [`UploadedFile`], `String`, numbers and `bool`, and for
+/// `Option<T>` and `Vec<T>` of those. Used by
+/// [`#[derive(MultipartForm)]`](macro@MultipartForm).
+pub trait FromMultipartField: Sized {
+    /// Builds the value from the parts received for field `name`.
+    fn from_field(name: &str, parts: Vec<Part>) -> Result<Self, Error>;
+
+    /// JSON Schema describing the field in OpenAPI.
+    fn schema() -> serde_json::Value;
+
+    /// Whether the field must be present.
+    fn required() -> bool {
+        true
+    }
+}
+
+fn single_part(name: &str, parts: Vec<Part>) -> Result<Part, Error> {
+    let mut parts = parts.into_iter();
+    match (parts.next(), parts.next()) {
+        (Some(part), None) => Ok(part),
+        (None, _) => Err(Error::bad_request(format!(
+            "missing multipart field '{}'",
+            name
+        ))),
+        (Some(_), Some(_)) => Err(Error::bad_request(format!(
+            "multipart field '{}' was sent more than once",
+            name
+        ))),
+    }
+}
+
+impl FromMultipartField for UploadedFile {
+    fn from_field(name: &str, parts: Vec<Part>) -> Result<Self, Error> {
+        match single_part(name, parts)? {
+            Part::File(file) => Ok(file),
+            Part::Text(_) => Err(Error::bad_request(format!(
+                "multipart field '{}' must be a file",
+                name
+            ))),
+        }
+    }
+
+    fn schema() -> serde_json::Value {
+        serde_json::json!({ "type": "string", "format": "binary" })
+    }
+}
+
+macro_rules! impl_text_field {
+    ($schema_type:literal => $($ty:ty),+) => {
+        $(
+            impl FromMultipartField for $ty {
+                fn from_field(name: &str, parts: Vec<Part>) -> Result<Self, Error> {
+                    match single_part(name, parts)? {
+                        Part::Text(text) => text.parse().map_err(|_| {
+                            Error::bad_request(format!(
+                                "invalid value for multipart field '{}'",
+                                name
+                            ))
+                        }),
+                        Part::File(_) => Err(Error::bad_request(format!(
+                            "multipart field '{}' must not be a file",
+                            name
+                        ))),
+                    }
+                }
+
+                fn schema() -> serde_json::Value {
+                    serde_json::json!({ "type": $schema_type })
+                }
+            }
+        )+
+    };
+}
+
+impl_text_field!("string" => String);
+impl_text_field!("boolean" => bool);
+impl_text_field!("integer" => i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_text_field!("number" => f32, f64);
+
+impl<T: FromMultipartField> FromMultipartField for Option<T> {
+    fn from_field(name: &str, parts: Vec<Part>) -> Result<Self, Error> {
+        if parts.is_empty() {
+            Ok(None)
+        } else {
+            T::from_field(name, parts).map(Some)
+        }
+    }
+
+    fn schema() -> serde_json::Value {
+        T::schema()
+    }
+
+    fn required() -> bool {
+        false
+    }
+}
+
+impl<T: FromMultipartField> FromMultipartField for Vec<T> {
+    fn from_field(name: &str, parts: Vec<Part>) -> Result<Self, Error> {
+        parts
+            .into_iter()
+            .map(|part| T::from_field(name, vec![part]))
+            .collect()
+    }
+
+    fn schema() -> serde_json::Value {
+        serde_json::json!({ "type": "array", "items": T::schema() })
+    }
+
+    fn required() -> bool {
+        false
+    }
+}
+
+/// Types that can be built from a whole multipart body.
+///
+/// Implement it with [`#[derive(MultipartForm)]`](macro@MultipartForm).
+pub trait FromMultipart: Sized {
+    /// Reads the body and builds the value.
+    fn from_multipart(multipart: Multipart) -> impl Future<Output = Result<Self, Error>> + Send;
+
+    /// JSON Schema of the form, used for the OpenAPI `requestBody`.
+    fn schema() -> serde_json::Value;
+}
+
+/// Extracts a `multipart/form-data` body into a typed struct.
+///
+/// The struct derives [`MultipartForm`](macro@MultipartForm); each field
+/// is read from the form field with the same name (or the name given with
+/// `#[multipart(rename = "...")]`). Unknown fields are ignored.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::prelude::*;
+/// use rapina::multipart::{MultipartForm, UploadedFile};
+///
+/// #[derive(MultipartForm)]
+/// struct Gallery {
+///     title: String,
+///     #[multipart(rename = "image")]
+///     images: Vec<UploadedFile>,
+/// }
+///
+/// #[post("/galleries")]
+/// async fn create_gallery(form: MultipartForm<Gallery>) -> String {
+///     let gallery = form.into_inner();
+///     format!("{}: {} images", gallery.title, gallery.images.len())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MultipartForm<T>(pub T);
+
+impl<T> MultipartForm<T> {
+    /// Consumes the extractor and returns the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: FromMultipart + Send> FromRequest for MultipartForm<T> {
+    async fn from_request(
+        req: Request<RequestBody>,
+        params: &PathParams,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let multipart = Multipart::from_request(req, params, state).await?;
+        T::from_multipart(multipart).await.map(MultipartForm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(contents: &'static str) -> Part {
+        Part::File(UploadedFile {
+            file_name: Some("a.txt".to_string()),
+            content_type: Some("text/plain".to_string()),
+            size: contents.len(),
+            data: FileData::Memory(Bytes::from(contents)),
+        })
+    }
+
+    #[test]
+    fn test_config_default() {
+        let config = MultipartConfig::default();
+        assert_eq!(config.max_total_size, 10 * 1024 * 1024);
+        assert_eq!(config.max_field_size, None);
+        assert_eq!(config.spill_threshold, None);
+    }
+
+    #[test]
+    fn test_text_field_parses_value() {
+        let value = u32::from_field("age", vec![Part::Text("42".to_string())]).unwrap();
+        assert_eq!(value, 42);
+
+        let err = u32::from_field("age", vec![Part::Text("old".to_string())]).unwrap_err();
+        assert_eq!(err.status, 400);
+    }
+
+    #[test]
+    fn test_missing_required_field() {
+        let err = String::from_field("name", Vec::new()).unwrap_err();
+        assert_eq!(err.status, 400);
+        assert!(err.message.contains("name"));
+    }
+
+    #[test]
+    fn test_optional_field() {
+        assert_eq!(
+            Option::<String>::from_field("name", Vec::new()).unwrap(),
+            None
+        );
+        assert!(!Option::<String>::required());
+    }
+
+    #[test]
+    fn test_file_field_rejects_text() {
+        let err =
+            UploadedFile::from_field("avatar", vec![Part::Text("x".to_string())]).unwrap_err();
+        assert!(err.message.contains("must be a file"));
+    }
+
+    #[test]
+    fn test_vec_of_files() {
+        let files = Vec::<UploadedFile>::from_field("files", vec![file("a"), file("bc")]).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].size(), 2);
+    }
+
+    #[test]
+    fn test_field_schemas() {
+        assert_eq!(
+            UploadedFile::schema(),
+            serde_json::json!({ "type": "string", "format": "binary" })
+        );
+        assert_eq!(
+            Vec::<i64>::schema(),
+            serde_json::json!({ "type": "array", "items": { "type": "integer" } })
+        );
+    }
+
+    #[test]
+    fn test_size_errors_map_to_413() {
+        let err = multipart_error(multer::Error::StreamSizeExceeded { limit: 10 });
+        assert_eq!(err.status, 413);
+
+        let err = multipart_error(multer::Error::FieldSizeExceeded {
+            limit: 10,
+            field_name: Some("file".to_string()),
+        });
+        assert_eq!(err.status, 413);
+        assert!(err.message.contains("file"));
+
+        let err = multipart_error(multer::Error::IncompleteStream);
+        assert_eq!(err.status, 400);
+    }
+}
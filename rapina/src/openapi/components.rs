@@ -0,0 +1,466 @@
+//! Hoisting of named schemas into `components/schemas`
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use super::spec::{OpenApiVersion, Schema};
+
+const DEFS_PREFIX: &str = "#/$defs/";
+const COMPONENTS_PREFIX: &str = "#/components/schemas/";
+
+/// Two different schemas registered under the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SchemaCollision {
+    pub(crate) name: String,
+    pub(crate) first: String,
+    pub(crate) second: String,
+    pub(crate) renamed_to: String,
+}
+
+/// Collects the named schemas of all routes into `components/schemas`.
+///
+/// Schemas produced by `schemars::schema_for!` carry their nested types in
+/// `$defs`. Each of those, and the root type when it is a named struct or
+/// enum, is stored once under its name and referenced through
+/// `#/components/schemas/{name}`. A different schema under an existing
+/// name is a collision; it is stored under a suffixed name and reported.
+pub(crate) struct ComponentRegistry {
+    version: OpenApiVersion,
+    schemas: BTreeMap<String, Value>,
+    owners: BTreeMap<String, String>,
+    collisions: Vec<SchemaCollision>,
+}
+
+impl ComponentRegistry {
+    pub(crate) fn new(version: OpenApiVersion) -> Self {
+        Self {
+            version,
+            schemas: BTreeMap::new(),
+            owners: BTreeMap::new(),
+            collisions: Vec::new(),
+        }
+    }
+
+    /// Adds a schema that is already in OpenAPI form, such as `ErrorResponse`.
+    pub(crate) fn insert(&mut self, name: &str, schema: Value) {
+        self.schemas.insert(name.to_string(), schema);
+        self.owners.insert(name.to_string(), "rapina".to_string());
+    }
+
+    /// Registers a body schema, returning a reference when the root is named.
+    pub(crate) fn register(&mut self, schema: &Value, handler: &str) -> Schema {
+        let (mut root, defs) = split_root(schema);
+
+        match hoistable_name(schema) {
+            Some(name) => {
+                let mut defs = defs;
+                if let Some(object) = root.as_object_mut() {
+                    object.remove("title");
+                }
+                defs.insert(name.clone(), root);
+                let mapping = self.store(defs, handler);
+                Schema::Ref {
+                    reference: format!("{}{}", COMPONENTS_PREFIX, mapping[&name]),
+                }
+            }
+            None => {
+                if let Some(object) = root.as_object_mut() {
+                    object.remove("title");
+                }
+                let mapping = self.store(defs, handler);
+                rewrite_refs(&mut root, &mapping);
+                Schema::Inline(self.convert(root))
+            }
+        }
+    }
+
+    /// Hoists the `$defs` of a schema and returns its rewritten root.
+    ///
+    /// The root is left in JSON Schema form so callers can pick it apart
+    /// (into parameters, for example) before calling [`convert`](Self::convert).
+    pub(crate) fn hoist_defs(&mut self, schema: &Value, handler: &str) -> Value {
+        let (mut root, defs) = split_root(schema);
+        if let Some(object) = root.as_object_mut() {
+            object.remove("title");
+        }
+        let mapping = self.store(defs, handler);
+        rewrite_refs(&mut root, &mapping);
+        root
+    }
+
+    /// Converts a schema to the dialect of the target OpenAPI version.
+    pub(crate) fn convert(&self, schema: Value) -> Value {
+        match self.version {
+            OpenApiVersion::V3_1 => schema,
+            OpenApiVersion::V3_0 => downgrade(schema),
+        }
+    }
+
+    /// Returns the stored schemas and the collisions found along the way.
+    pub(crate) fn into_parts(self) -> (BTreeMap<String, Value>, Vec<SchemaCollision>) {
+        (self.schemas, self.collisions)
+    }
+
+    /// Stores named schemas, returning the component name chosen for each.
+    fn store(&mut self, defs: BTreeMap<String, Value>, handler: &str) -> BTreeMap<String, String> {
+        let mut mapping: BTreeMap<String, String> = defs
+            .keys()
+            .map(|name| (name.clone(), name.clone()))
+            .collect();
+
+        // Renaming one schema changes the references in the others, so
+        // repeat until every schema either matches or has its own name.
+        loop {
+            let mut changed = false;
+            for (name, schema) in &defs {
+                let target = &mapping[name];
+                let Some(existing) = self.schemas.get(target) else {
+                    continue;
+                };
+                if *existing == self.resolve(schema, &mapping) {
+                    continue;
+                }
+
+                let (renamed, seen) = self.alternative_name(name, schema, &mapping);
+                if !seen {
+                    self.collisions.push(SchemaCollision {
+                        name: name.clone(),
+                        first: self.owners.get(target).cloned().unwrap_or_default(),
+                        second: handler.to_string(),
+                        renamed_to: renamed.clone(),
+                    });
+                }
+                mapping.insert(name.clone(), renamed);
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        for (name, schema) in &defs {
+            let target = mapping[name].clone();
+            if !self.schemas.contains_key(&target) {
+                let resolved = self.resolve(schema, &mapping);
+                self.schemas.insert(target.clone(), resolved);
+                self.owners.insert(target, handler.to_string());
+            }
+        }
+
+        mapping
+    }
+
+    fn resolve(&self, schema: &Value, mapping: &BTreeMap<String, String>) -> Value {
+        let mut schema = schema.clone();
+        rewrite_refs(&mut schema, mapping);
+        self.convert(schema)
+    }
+
+    /// Finds a suffixed name for a colliding schema.
+    ///
+    /// Returns `true` alongside the name when the same schema was already
+    /// stored under it by an earlier collision.
+    fn alternative_name(
+        &self,
+        name: &str,
+        schema: &Value,
+        mapping: &BTreeMap<String, String>,
+    ) -> (String, bool) {
+        let resolved = self.resolve(schema, mapping);
+        for i in 2.. {
+            let candidate = format!("{}_{}", name, i);
+            match self.schemas.get(&candidate) {
+                Some(existing) if *existing == resolved => return (candidate, true),
+                Some(_) => {}
+                None if mapping.values().any(|v| *v == candidate) => {}
+                None => return (candidate, false),
+            }
+        }
+        unreachable!()
+    }
+}
+
+/// Separates a root schema from its `$defs`.
+fn split_root(schema: &Value) -> (Value, BTreeMap<String, Value>) {
+    let mut root = schema.clone();
+    let mut defs = BTreeMap::new();
+    if let Some(object) = root.as_object_mut() {
+        object.remove("$schema");
+        if let Some(Value::Object(nested)) = object.remove("$defs") {
+            defs.extend(nested);
+        }
+    }
+    (root, defs)
+}
+
+/// Returns the name under which a root schema is stored, if it is a named type.
+///
+/// Only structs and enums are hoisted; schemars also titles primitives,
+/// arrays and options (`uint64`, `Array_of_User`), which stay inline.
+fn hoistable_name(schema: &Value) -> Option<String> {
+    let object = schema.as_object()?;
+    let title = object.get("title")?.as_str()?;
+    let named = object.contains_key("properties")
+        || object.contains_key("enum")
+        || object.contains_key("oneOf");
+    named.then(|| title.to_string())
+}
+
+/// Points `#/$defs/{name}` references at `#/components/schemas/{mapped}`.
+fn rewrite_refs(schema: &mut Value, mapping: &BTreeMap<String, String>) {
+    match schema {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get_mut("$ref")
+                && let Some(name) = reference.strip_prefix(DEFS_PREFIX)
+            {
+                let target = mapping.get(name).map(String::as_str).unwrap_or(name);
+                *reference = format!("{}{}", COMPONENTS_PREFIX, target);
+            }
+            for value in object.values_mut() {
+                rewrite_refs(value, mapping);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                rewrite_refs(item, mapping);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrites JSON Schema 2020-12 constructs that OpenAPI 3.0 doesn't support.
+fn downgrade(schema: Value) -> Value {
+    match schema {
+        Value::Object(object) => {
+            let object = object
+                .into_iter()
+                .map(|(key, value)| (key, downgrade(value)))
+                .collect();
+            Value::Object(downgrade_object(object))
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(downgrade).collect()),
+        other => other,
+    }
+}
+
+fn downgrade_object(mut object: Map<String, Value>) -> Map<String, Value> {
+    // "type": ["string", "null"] -> "type": "string", "nullable": true
+    if let Some(Value::Array(types)) = object.get("type").cloned()
+        && types.iter().any(|t| t == "null")
+    {
+        let types: Vec<Value> = types.into_iter().filter(|t| t != "null").collect();
+        object.insert("nullable".to_string(), Value::Bool(true));
+        match types.as_slice() {
+            [single] => {
+                object.insert("type".to_string(), single.clone());
+            }
+            _ => {
+                object.remove("type");
+                let variants = types
+                    .into_iter()
+                    .map(|t| Value::Object(Map::from_iter([("type".to_string(), t)])))
+                    .collect();
+                object.insert("anyOf".to_string(), Value::Array(variants));
+            }
+        }
+    }
+
+    // "anyOf": [X, {"type": "null"}] -> X with "nullable": true
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(variants)) = object.get(key).cloned()
+            && variants.iter().any(is_null_schema)
+        {
+            let variants: Vec<Value> = variants
+                .into_iter()
+                .filter(|v| !is_null_schema(v))
+                .collect();
+            object.remove(key);
+            object.insert("nullable".to_string(), Value::Bool(true));
+            match variants.as_slice() {
+                [Value::Object(single)] if !single.contains_key("$ref") => {
+                    for (k, v) in single {
+                        object.entry(k.clone()).or_insert_with(|| v.clone());
+                    }
+                }
+                [single] => {
+                    object.insert("allOf".to_string(), Value::Array(vec![single.clone()]));
+                }
+                _ => {
+                    object.insert(key.to_string(), Value::Array(variants));
+                }
+            }
+        }
+    }
+
+    if let Some(value) = object.remove("const") {
+        object.insert("enum".to_string(), Value::Array(vec![value]));
+    }
+
+    if let Some(Value::Array(items)) = object.remove("prefixItems") {
+        object.insert(
+            "items".to_string(),
+            Value::Object(Map::from_iter([("anyOf".to_string(), Value::Array(items))])),
+        );
+    } else if matches!(object.get("items"), Some(Value::Bool(_))) {
+        object.remove("items");
+    }
+
+    for (exclusive, inclusive) in [
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+        if let Some(bound) = object.get(exclusive).filter(|v| v.is_number()).cloned() {
+            object.insert(inclusive.to_string(), bound);
+            object.insert(exclusive.to_string(), Value::Bool(true));
+        }
+    }
+
+    if let Some(Value::Array(mut examples)) = object.remove("examples")
+        && !examples.is_empty()
+    {
+        object.insert("example".to_string(), examples.swap_remove(0));
+    }
+
+    object
+}
+
+fn is_null_schema(schema: &Value) -> bool {
+    schema.get("type").is_some_and(|t| t == "null")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user_schema() -> Value {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "User",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "address": { "$ref": "#/$defs/Address" },
+            },
+            "required": ["id", "address"],
+            "$defs": {
+                "Address": {
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn test_register_hoists_named_root_and_defs() {
+        let mut registry = ComponentRegistry::new(OpenApiVersion::V3_1);
+        let schema = registry.register(&user_schema(), "get_user");
+
+        assert!(matches!(
+            schema,
+            Schema::Ref { reference } if reference == "#/components/schemas/User"
+        ));
+        let (schemas, _) = registry.into_parts();
+        assert!(schemas["User"].get("title").is_none());
+        assert_eq!(
+            schemas["User"]["properties"]["address"]["$ref"],
+            "#/components/schemas/Address"
+        );
+        assert!(schemas.contains_key("Address"));
+    }
+
+    #[test]
+    fn test_register_same_type_twice_is_deduplicated() {
+        let mut registry = ComponentRegistry::new(OpenApiVersion::V3_1);
+        registry.register(&user_schema(), "get_user");
+        registry.register(&user_schema(), "list_users");
+
+        let (schemas, collisions) = registry.into_parts();
+        assert!(collisions.is_empty());
+        assert_eq!(schemas.len(), 2);
+    }
+
+    #[test]
+    fn test_register_array_stays_inline() {
+        let mut registry = ComponentRegistry::new(OpenApiVersion::V3_1);
+        let schema = registry.register(
+            &json!({
+                "title": "Array_of_User",
+                "type": "array",
+                "items": { "$ref": "#/$defs/User" },
+                "$defs": { "User": { "type": "object", "properties": {} } },
+            }),
+            "list_users",
+        );
+
+        let Schema::Inline(schema) = schema else {
+            panic!("expected inline schema");
+        };
+        assert_eq!(schema["items"]["$ref"], "#/components/schemas/User");
+        assert!(schema.get("title").is_none());
+    }
+
+    #[test]
+    fn test_collision_is_renamed_and_reported() {
+        let mut registry = ComponentRegistry::new(OpenApiVersion::V3_1);
+        registry.register(&user_schema(), "get_user");
+        let other = json!({
+            "title": "User",
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+        });
+        let schema = registry.register(&other, "get_admin");
+
+        assert!(matches!(
+            schema,
+            Schema::Ref { reference } if reference == "#/components/schemas/User_2"
+        ));
+        // The same type reached again reuses the suffixed name
+        registry.register(&other, "list_admins");
+
+        let (schemas, collisions) = registry.into_parts();
+        assert_eq!(
+            collisions,
+            vec![SchemaCollision {
+                name: "User".to_string(),
+                first: "get_user".to_string(),
+                second: "get_admin".to_string(),
+                renamed_to: "User_2".to_string(),
+            }]
+        );
+        assert_eq!(schemas.len(), 3);
+    }
+
+    #[test]
+    fn test_downgrade_nullable() {
+        assert_eq!(
+            downgrade(json!({ "type": ["string", "null"] })),
+            json!({ "type": "string", "nullable": true })
+        );
+        assert_eq!(
+            downgrade(
+                json!({ "anyOf": [{ "$ref": "#/components/schemas/User" }, { "type": "null" }] })
+            ),
+            json!({ "allOf": [{ "$ref": "#/components/schemas/User" }], "nullable": true })
+        );
+    }
+
+    #[test]
+    fn test_downgrade_keywords() {
+        assert_eq!(
+            downgrade(json!({ "const": "a", "examples": [1, 2] })),
+            json!({ "enum": ["a"], "example": 1 })
+        );
+        assert_eq!(
+            downgrade(json!({ "type": "integer", "exclusiveMinimum": 0 })),
+            json!({ "type": "integer", "minimum": 0, "exclusiveMinimum": true })
+        );
+        assert_eq!(
+            downgrade(json!({ "type": "array", "prefixItems": [{ "type": "integer" }] })),
+            json!({ "type": "array", "items": { "anyOf": [{ "type": "integer" }] } })
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Interactive API documentation served under `/__rapina/docs`
+
+use std::sync::Arc;
+
+use http::{Request, Response, StatusCode, header};
+
+use crate::extract::{PathParams, RequestBody};
+use crate::response::BoxBody;
+use crate::state::AppState;
+
+const SWAGGER_UI_JS: &[u8] = include_bytes!("../../assets/docs/swagger-ui-bundle.js");
+const SWAGGER_UI_CSS: &[u8] = include_bytes!("../../assets/docs/swagger-ui.css");
+const REDOC_JS: &[u8] = include_bytes!("../../assets/docs/redoc.standalone.js");
+const SCALAR_JS: &[u8] = include_bytes!("../../assets/docs/scalar.standalone.js");
+
+/// The documentation UI rendered at `/__rapina/docs`.
+///
+/// All three are embedded in the binary, so the page works offline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DocsUi {
+    /// Swagger UI, with "Try it out" for every operation.
+    #[default]
+    SwaggerUi,
+    /// Redoc, a read-only three-panel reference.
+    Redoc,
+    /// Scalar API Reference, with a built-in request client.
+    Scalar,
+}
+
+/// The rendered docs page, stored in application state.
+#[derive(Debug, Clone)]
+pub(crate) struct DocsPage {
+    html: String,
+}
+
+impl DocsPage {
+    /// Renders the page for `ui`.
+    ///
+    /// When `bearer_auth` is set, the UI is told to use the spec's
+    /// `bearerAuth` scheme for "Try it out" requests.
+    pub(crate) fn new(ui: DocsUi, title: &str, version: &str, bearer_auth: bool) -> Self {
+        let (head, render) = match ui {
+            DocsUi::SwaggerUi => (
+                r#"<link rel="stylesheet" href="/__rapina/docs/swagger-ui.css">
+    <script src="/__rapina/docs/swagger-ui-bundle.js"></script>"#,
+                r##"SwaggerUIBundle({
+        dom_id: "#docs",
+        spec: spec,
+        deepLinking: true,
+        persistAuthorization: true,
+      });"##,
+            ),
+            DocsUi::Redoc => (
+                r#"<script src="/__rapina/docs/redoc.standalone.js"></script>"#,
+                r#"Redoc.init(spec, {}, document.getElementById("docs"));"#,
+            ),
+            DocsUi::Scalar => (
+                "",
+                r#"const reference = document.createElement("script");
+      reference.id = "api-reference";
+      reference.type = "application/json";
+      reference.textContent = JSON.stringify(spec);
+      reference.dataset.configuration = JSON.stringify({
+        authentication: { preferredSecurityScheme: BEARER_AUTH ? "bearerAuth" : null },
+      });
+      document.body.appendChild(reference);
+      const script = document.createElement("script");
+      script.src = "/__rapina/docs/scalar.standalone.js";
+      document.body.appendChild(script);"#,
+            ),
+        };
+
+        let html = PAGE_TEMPLATE
+            .replace("{title}", &escape_html(&format!("{} {}", title, version)))
+            .replace("{head}", head)
+            .replace("{bearer_auth}", if bearer_auth { "true" } else { "false" })
+            .replace("{render}", render);
+
+        Self { html }
+    }
+}
+
+const PAGE_TEMPLATE: &str = r#"<!doctype html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{title}</title>
+    <style>body { margin: 0; }</style>
+    {head}
+  </head>
+  <body>
+    <div id="docs"></div>
+    <script>
+      const BEARER_AUTH = {bearer_auth};
+      fetch("/__rapina/openapi.json")
+        .then((response) => response.json())
+        .then((spec) => {
+          {render}
+        });
+    </script>
+  </body>
+</html>
+"#;
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns the content type and contents of an embedded asset.
+fn asset(name: &str) -> Option<(&'static str, &'static [u8])> {
+    match name {
+        "swagger-ui-bundle.js" => Some(("text/javascript; charset=utf-8", SWAGGER_UI_JS)),
+        "swagger-ui.css" => Some(("text/css; charset=utf-8", SWAGGER_UI_CSS)),
+        "redoc.standalone.js" => Some(("text/javascript; charset=utf-8", REDOC_JS)),
+        "scalar.standalone.js" => Some(("text/javascript; charset=utf-8", SCALAR_JS)),
+        _ => None,
+    }
+}
+
+/// Handler for the docs page
+pub(crate) async fn docs_page(
+    _req: Request<RequestBody>,
+    _params: PathParams,
+    state: Arc<AppState>,
+) -> Response<BoxBody> {
+    match state.get::<DocsPage>() {
+        Some(page) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+            .body(crate::response::full(page.html.clone()))
+            .unwrap(),
+        None => not_found(),
+    }
+}
+
+/// Handler for the embedded UI assets
+pub(crate) async fn docs_asset(
+    _req: Request<RequestBody>,
+    params: PathParams,
+    _state: Arc<AppState>,
+) -> Response<BoxBody> {
+    match params.get("asset").and_then(|name| asset(name)) {
+        Some((content_type, contents)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CACHE_CONTROL, "public, max-age=86400")
+            .body(crate::response::full(contents))
+            .unwrap(),
+        None => not_found(),
+    }
+}
+
+fn not_found() -> Response<BoxBody> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(crate::response::full(r#"{"error": "Not found"}"#))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_uses_title_and_version() {
+        let page = DocsPage::new(DocsUi::SwaggerUi, "Pets <API>", "2.0.0", false);
+        assert!(page.html.contains("<title>Pets &lt;API&gt; 2.0.0</title>"));
+        assert!(page.html.contains("/__rapina/openapi.json"));
+        assert!(page.html.contains("const BEARER_AUTH = false;"));
+    }
+
+    #[test]
+    fn test_page_loads_selected_ui() {
+        let swagger = DocsPage::new(DocsUi::SwaggerUi, "API", "1.0.0", false);
+        assert!(swagger.html.contains("swagger-ui-bundle.js"));
+        assert!(!swagger.html.contains("redoc.standalone.js"));
+
+        let redoc = DocsPage::new(DocsUi::Redoc, "API", "1.0.0", false);
+        assert!(redoc.html.contains("Redoc.init"));
+
+        let scalar = DocsPage::new(DocsUi::Scalar, "API", "1.0.0", true);
+        assert!(scalar.html.contains("scalar.standalone.js"));
+        assert!(scalar.html.contains("const BEARER_AUTH = true;"));
+    }
+
+    #[test]
+    fn test_assets_are_embedded() {
+        for name in [
+            "swagger-ui-bundle.js",
+            "swagger-ui.css",
+            "redoc.standalone.js",
+            "scalar.standalone.js",
+        ] {
+            let (_, contents) = asset(name).unwrap();
+            assert!(!contents.is_empty());
+        }
+        assert!(asset("../Cargo.toml").is_none());
+    }
+}
@@ -3,9 +3,13 @@
 use std::sync::Arc;
 
 use http::{Request, Response, StatusCode};
-use hyper::body::Incoming;
 
-use crate::{extract::PathParams, openapi::OpenApiSpec, response::BoxBody, state::AppState};
+use crate::{
+    extract::{PathParams, RequestBody},
+    openapi::OpenApiSpec,
+    response::BoxBody,
+    state::AppState,
+};
 
 /// Registry for storing the OpenAPI spec
 #[derive(Debug, Clone)]
@@ -27,7 +31,7 @@ impl OpenApiRegistry {
 ///
 /// Returns the OpenAPI specification as JSON
 pub async fn openapi_spec(
-    _req: Request<Incoming>,
+    _req: Request<RequestBody>,
     _params: PathParams,
     state: Arc<AppState>,
 ) -> Response<BoxBody> {
@@ -39,15 +43,15 @@ pub async fn openapi_spec(
             Response::builder()
                 .status(StatusCode::OK)
                 .header("content-type", "application/json")
-                .body(http_body_util::Full::new(bytes::Bytes::from(json)))
+                .body(crate::response::full(json))
                 .unwrap()
         }
         None => Response::builder()
             .status(StatusCode::NOT_FOUND)
             .header("content-type", "application/json")
-            .body(http_body_util::Full::new(bytes::Bytes::from(
+            .body(crate::response::full(
                 r#"{"error": "OpenAPI spec not configured"}"#,
-            )))
+            ))
             .unwrap(),
     }
 }
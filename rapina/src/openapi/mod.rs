@@ -1,10 +1,17 @@
 // ! OpenAPI specification generation
 // !
-// ! This module provides automatic OpenAPI 3.0 spec generation
+// ! This module provides automatic OpenAPI 3.0 and 3.1 spec generation
 // ! derived from your route definitions
 
+mod components;
+#[cfg(feature = "docs")]
+pub(crate) mod docs;
 mod endpoint;
+#[doc(hidden)]
+pub mod probe;
 mod spec;
 
+#[cfg(feature = "docs")]
+pub use docs::DocsUi;
 pub use endpoint::*;
 pub use spec::*;
@@ -0,0 +1,65 @@
+//! Optional schema lookup used by the route macros.
+//!
+//! Request types don't have to implement `JsonSchema`; the macros use
+//! autoref specialization so those types are simply left out of the spec.
+
+use std::marker::PhantomData;
+
+use schemars::JsonSchema;
+
+/// Wraps a type whose schema may or may not be available.
+pub struct SchemaProbe<T>(PhantomData<T>);
+
+impl<T> SchemaProbe<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for SchemaProbe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Picked by method resolution when `T: JsonSchema`.
+pub trait WithSchema {
+    fn schema(&self) -> Option<serde_json::Value>;
+}
+
+impl<T: JsonSchema> WithSchema for SchemaProbe<T> {
+    fn schema(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(schemars::schema_for!(T)).ok()
+    }
+}
+
+/// Fallback for types without a schema.
+pub trait WithoutSchema {
+    fn schema(&self) -> Option<serde_json::Value>;
+}
+
+impl<T> WithoutSchema for &SchemaProbe<T> {
+    fn schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoSchema;
+
+    // Borrow like the macros do, so both traits are candidates
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn test_probe_with_schema() {
+        let schema = (&SchemaProbe::<u64>::new()).schema().unwrap();
+        assert_eq!(schema["type"], "integer");
+    }
+
+    #[test]
+    fn test_probe_without_schema() {
+        assert!((&SchemaProbe::<NoSchema>::new()).schema().is_none());
+    }
+}
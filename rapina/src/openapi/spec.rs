@@ -1,7 +1,11 @@
-//! OpenAPI 3.0 specification structures
+//! OpenAPI 3.0 and 3.1 specification structures
 
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use super::components::{ComponentRegistry, SchemaCollision};
+use crate::router::pattern::{Constraint, Pattern, Segment};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct OpenApiSpec {
@@ -10,6 +14,9 @@ pub struct OpenApiSpec {
     pub paths: BTreeMap<String, PathItem>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Components>,
+    /// Security requirements applied to every operation that doesn't override them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub security: Vec<SecurityRequirement>,
 }
 
 impl OpenApiSpec {
@@ -23,10 +30,94 @@ impl OpenApiSpec {
             },
             paths: BTreeMap::new(),
             components: None,
+            security: Vec::new(),
+        }
+    }
+}
+
+/// Version of the OpenAPI specification to generate
+///
+/// 3.0.3 is the default for compatibility with existing tooling. 3.1
+/// uses JSON Schema 2020-12, so the schemas generated by `schemars` are
+/// emitted as-is instead of being converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpenApiVersion {
+    /// OpenAPI 3.0.3
+    #[default]
+    V3_0,
+    /// OpenAPI 3.1.0
+    V3_1,
+}
+
+impl OpenApiVersion {
+    /// Returns the value of the document's `openapi` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OpenApiVersion::V3_0 => "3.0.3",
+            OpenApiVersion::V3_1 => "3.1.0",
         }
     }
 }
 
+/// Options for [`try_build_openapi_spec`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpenApiOptions {
+    /// Version of the OpenAPI document
+    pub version: OpenApiVersion,
+    /// Whether routes require a bearer token unless they are public
+    pub bearer_auth: bool,
+}
+
+impl OpenApiOptions {
+    /// Creates options for an OpenAPI 3.0 document without authentication.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the version of the OpenAPI document.
+    pub fn version(mut self, version: OpenApiVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Documents a `bearerAuth` security scheme required by every route
+    /// that isn't public, along with its `401` response.
+    pub fn bearer_auth(mut self, enabled: bool) -> Self {
+        self.bearer_auth = enabled;
+        self
+    }
+}
+
+/// Errors detected while building the OpenAPI spec
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpenApiError {
+    /// Two different types share a schema name, usually because they have
+    /// the same name in different modules.
+    SchemaCollision {
+        name: String,
+        first: String,
+        second: String,
+    },
+}
+
+impl fmt::Display for OpenApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenApiError::SchemaCollision {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "schema '{}' used by '{}' differs from the one used by '{}'; rename one of the types or use #[schemars(rename = \"...\")]",
+                name, second, first
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OpenApiError {}
+
 /// API metadata
 #[derive(Debug, Clone, Serialize)]
 pub struct Info {
@@ -47,6 +138,12 @@ pub struct PathItem {
     pub put: Option<Operation>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delete: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Operation>,
 }
 
 /// A single API operation (endpoint)
@@ -59,6 +156,13 @@ pub struct Operation {
     #[serde(rename = "operationId", skip_serializing_if = "Option::is_none")]
     pub operation_id: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// Overrides the global security requirements; empty for public operations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<SecurityRequirement>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub parameters: Vec<Parameter>,
     #[serde(rename = "requestBody", skip_serializing_if = "Option::is_none")]
     pub request_body: Option<RequestBody>,
@@ -79,6 +183,9 @@ impl Default for Operation {
             summary: None,
             description: None,
             operation_id: None,
+            tags: Vec::new(),
+            deprecated: false,
+            security: None,
             parameters: Vec::new(),
             request_body: None,
             responses,
@@ -146,10 +253,43 @@ pub enum Schema {
 pub struct Components {
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub schemas: BTreeMap<String, serde_json::Value>,
+    #[serde(rename = "securitySchemes", skip_serializing_if = "BTreeMap::is_empty")]
+    pub security_schemes: BTreeMap<String, SecurityScheme>,
+}
+
+/// Security requirement: scheme names mapped to required scopes
+pub type SecurityRequirement = BTreeMap<String, Vec<String>>;
+
+/// Name of the security scheme documented for [`AuthConfig`](crate::auth::AuthConfig)
+pub const BEARER_AUTH: &str = "bearerAuth";
+
+/// Security scheme definition
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityScheme {
+    #[serde(rename = "type")]
+    pub scheme_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    #[serde(rename = "bearerFormat", skip_serializing_if = "Option::is_none")]
+    pub bearer_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl SecurityScheme {
+    /// HTTP bearer authentication with JWTs, as checked by `AuthMiddleware`.
+    pub fn bearer_jwt() -> Self {
+        Self {
+            scheme_type: "http".to_string(),
+            scheme: Some("bearer".to_string()),
+            bearer_format: Some("JWT".to_string()),
+            description: None,
+        }
+    }
 }
 
 /// Create the standard Rapina error response schema
-fn error_response_schema() -> serde_json::Value {
+pub(crate) fn error_response_schema() -> serde_json::Value {
     serde_json::json!({
         "type": "object",
         "required": ["error", "trace_id"],
@@ -205,57 +345,222 @@ fn humanize_handler_name(name: &str) -> String {
     result
 }
 
+/// Builds the path parameters of a pattern, typed from the `Path<T>` schema.
+///
+/// Struct schemas are matched by property name, tuple schemas by position,
+/// and any other schema describes the single parameter. Parameters without
+/// a schema are typed from their constraint, or documented as strings.
+fn path_parameters(segments: &[Segment], schema: Option<&serde_json::Value>) -> Vec<Parameter> {
+    let params: Vec<(&str, Option<&Constraint>)> = segments
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Static(_) => None,
+            Segment::Param {
+                name, constraint, ..
+            } => Some((name.as_str(), constraint.as_ref())),
+            Segment::CatchAll(name) => Some((name.as_str(), None)),
+        })
+        .collect();
+
+    params
+        .iter()
+        .enumerate()
+        .map(|(i, (name, constraint))| {
+            let param_schema = schema.and_then(|schema| {
+                if let Some(properties) = schema.get("properties") {
+                    properties.get(*name).cloned()
+                } else if let Some(items) = schema
+                    .get("prefixItems")
+                    .and_then(serde_json::Value::as_array)
+                {
+                    items.get(i).cloned()
+                } else if params.len() == 1 {
+                    Some(schema.clone())
+                } else {
+                    None
+                }
+            });
+
+            let mut param_schema = match (param_schema, constraint) {
+                (Some(mut param_schema), Some(constraint @ Constraint::Regex { .. })) => {
+                    // Keep the regex on string parameters typed by `Path<T>`
+                    if let Some(object) = param_schema.as_object_mut()
+                        && object.get("type") == Some(&serde_json::json!("string"))
+                    {
+                        object
+                            .entry("pattern")
+                            .or_insert_with(|| constraint.schema()["pattern"].clone());
+                    }
+                    param_schema
+                }
+                (Some(param_schema), _) => param_schema,
+                (None, Some(constraint)) => constraint.schema(),
+                (None, None) => serde_json::json!({ "type": "string" }),
+            };
+            let description = param_schema
+                .as_object_mut()
+                .and_then(|object| object.remove("description"))
+                .and_then(|d| d.as_str().map(str::to_string));
+
+            Parameter {
+                name: name.to_string(),
+                location: ParameterLocation::Path,
+                description,
+                required: true,
+                schema: Some(Schema::Inline(param_schema)),
+            }
+        })
+        .collect()
+}
+
+/// Converts a concrete pattern variant to OpenAPI path templating, e.g.
+/// `/users/:id<int>/*rest` becomes `/users/{id}/{rest}`.
+fn openapi_path(segments: &[&Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Static(value) => value.clone(),
+            Segment::Param { name, .. } | Segment::CatchAll(name) => format!("{{{}}}", name),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Expands the properties of a `Query<T>` schema into query parameters.
+fn query_parameters(schema: &serde_json::Value) -> Vec<Parameter> {
+    let Some(properties) = schema
+        .get("properties")
+        .and_then(serde_json::Value::as_object)
+    else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(serde_json::Value::as_array)
+        .map(|names| names.iter().filter_map(serde_json::Value::as_str).collect())
+        .unwrap_or_default();
+
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let mut property = property.clone();
+            let description = property
+                .as_object_mut()
+                .and_then(|object| object.remove("description"))
+                .and_then(|d| d.as_str().map(str::to_string));
+
+            Parameter {
+                name: name.clone(),
+                location: ParameterLocation::Query,
+                description,
+                required: required.contains(&name.as_str()),
+                schema: Some(Schema::Inline(property)),
+            }
+        })
+        .collect()
+}
+
+/// Builds an OpenAPI 3.0.3 spec from route metadata.
+///
+/// Schemas that share a name but differ are stored under suffixed names
+/// (`User_2`) and logged; use [`try_build_openapi_spec`] to reject them.
 pub fn build_openapi_spec(
     title: &str,
     version: &str,
     routes: &[crate::introspection::RouteInfo],
 ) -> OpenApiSpec {
+    let (spec, collisions) = generate(title, version, routes, OpenApiOptions::default());
+    for collision in &collisions {
+        tracing::warn!(
+            schema = %collision.name,
+            renamed_to = %collision.renamed_to,
+            "schema used by '{}' differs from the one used by '{}'",
+            collision.second,
+            collision.first
+        );
+    }
+    spec
+}
+
+/// Builds an OpenAPI spec with the given options, failing on schema name collisions.
+pub fn try_build_openapi_spec(
+    title: &str,
+    version: &str,
+    routes: &[crate::introspection::RouteInfo],
+    options: OpenApiOptions,
+) -> Result<OpenApiSpec, OpenApiError> {
+    let (spec, collisions) = generate(title, version, routes, options);
+    match collisions.into_iter().next() {
+        Some(collision) => Err(OpenApiError::SchemaCollision {
+            name: collision.name,
+            first: collision.first,
+            second: collision.second,
+        }),
+        None => Ok(spec),
+    }
+}
+
+fn generate(
+    title: &str,
+    version: &str,
+    routes: &[crate::introspection::RouteInfo],
+    options: OpenApiOptions,
+) -> (OpenApiSpec, Vec<SchemaCollision>) {
     let mut spec = OpenApiSpec::new(title, version);
+    spec.openapi = options.version.as_str().to_string();
 
-    let mut schemas = BTreeMap::new();
-    schemas.insert("ErrorResponse".to_string(), error_response_schema());
+    let mut registry = ComponentRegistry::new(options.version);
+    registry.insert("ErrorResponse", error_response_schema());
 
-    spec.components = Some(Components { schemas });
+    let mut security_schemes = BTreeMap::new();
+    if options.bearer_auth {
+        security_schemes.insert(BEARER_AUTH.to_string(), SecurityScheme::bearer_jwt());
+        spec.security = vec![BTreeMap::from([(BEARER_AUTH.to_string(), Vec::new())])];
+    }
 
-    for route in routes {
-        // skip internal rapina routes
+    // skip internal rapina routes, and those whose patterns fail to
+    // compile, as they are never served
+    let documented = routes.iter().filter_map(|route| {
         if route.path.starts_with("/__rapina") {
-            continue;
+            return None;
         }
-        // Extract path parameters (e.g., :id -> id)
-        let params: Vec<Parameter> = route
-            .path
-            .split('/')
-            .filter(|s| s.starts_with(':'))
-            .map(|s| Parameter {
-                name: s.trim_start_matches(':').to_string(),
-                location: ParameterLocation::Path,
-                description: None,
-                required: true,
-                schema: None,
-            })
-            .collect();
+        Pattern::parse(&route.path)
+            .ok()
+            .map(|pattern| (route, pattern))
+    });
+    // Handlers registered for several methods would share an operationId,
+    // so such ids get the method appended
+    let mut id_counts: HashMap<&str, usize> = HashMap::new();
+    for (route, _) in documented.clone() {
+        *id_counts.entry(operation_id(route)).or_default() += 1;
+    }
 
-        // Convert :param to {param} for OpenAPI format
-        let openapi_path = route
-            .path
-            .split('/')
-            .map(|s| {
-                if s.starts_with(':') {
-                    format!("{{{}}}", s.trim_start_matches(':'))
-                } else {
-                    s.to_string()
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("/");
+    for (route, pattern) in documented {
+        let handler = route.handler_name.as_str();
 
+        // Extract path parameters (e.g., :id -> id)
+        let path_schema = route
+            .path_schema
+            .as_ref()
+            .map(|schema| registry.hoist_defs(schema, handler));
+        let mut params = path_parameters(pattern.segments(), path_schema.as_ref());
+        if let Some(schema) = &route.query_schema {
+            params.extend(query_parameters(&registry.hoist_defs(schema, handler)));
+        }
+        for param in &mut params {
+            if let Some(Schema::Inline(schema)) = param.schema.take() {
+                param.schema = Some(Schema::Inline(registry.convert(schema)));
+            }
+        }
         let success_response = if let Some(schema) = &route.response_schema {
             let mut content = BTreeMap::new();
             content.insert(
-                "application/json".to_string(),
+                route
+                    .response_content_type
+                    .clone()
+                    .unwrap_or_else(|| "application/json".to_string()),
                 MediaType {
-                    schema: Schema::Inline(schema.clone()),
+                    schema: registry.register(schema, handler),
                 },
             );
             Response {
@@ -269,12 +574,41 @@ pub fn build_openapi_spec(
             }
         };
 
-        let summary = humanize_handler_name(&route.handler_name);
+        let request_body = route.request_schema.as_ref().map(|schema| {
+            let mut content = BTreeMap::new();
+            content.insert(
+                route
+                    .request_content_type
+                    .clone()
+                    .unwrap_or_else(|| "application/json".to_string()),
+                MediaType {
+                    schema: registry.register(schema, handler),
+                },
+            );
+            RequestBody {
+                description: None,
+                required: true,
+                content,
+            }
+        });
+
+        let summary = route
+            .summary
+            .clone()
+            .unwrap_or_else(|| humanize_handler_name(&route.handler_name));
+        let mut operation_id = operation_id(route).to_string();
+        if id_counts[operation_id.as_str()] > 1 {
+            operation_id = format!("{}_{}", operation_id, route.method.to_lowercase());
+        }
 
         let mut operation = Operation {
             summary: Some(summary),
-            operation_id: Some(route.handler_name.clone()),
+            description: route.description.clone(),
+            operation_id: Some(operation_id),
+            tags: route.tags.clone(),
+            deprecated: route.deprecated,
             parameters: params,
+            request_body,
             ..Default::default()
         };
 
@@ -303,23 +637,77 @@ pub fn build_openapi_spec(
             });
         }
 
+        if options.bearer_auth {
+            if route.public {
+                // An empty requirement list opts the operation out of the global one
+                operation.security = Some(Vec::new());
+            } else {
+                operation
+                    .responses
+                    .entry("401".to_string())
+                    .or_insert_with(|| Response {
+                        description: "Missing or invalid bearer token".to_string(),
+                        content: error_response_ref().content,
+                    });
+            }
+        }
+
         // Add default error response for undocumented errors
         operation
             .responses
             .insert("default".to_string(), error_response_ref());
 
-        let path_item = spec.paths.entry(openapi_path).or_default();
+        // OpenAPI has no optional path parameters, so a pattern with optional
+        // segments is documented once per variant. Shorter variants get an
+        // operationId naming the parameters they leave out.
+        let variants = pattern.variants();
+        let longest = variants.len() - 1;
+        for (i, variant) in variants.iter().enumerate() {
+            let mut operation = operation.clone();
+            if i != longest {
+                let kept: Vec<&str> = variant.iter().filter_map(|s| s.param_name()).collect();
+                let omitted: Vec<&str> = pattern
+                    .segments()
+                    .iter()
+                    .filter_map(Segment::param_name)
+                    .filter(|name| !kept.contains(name))
+                    .collect();
+                operation.parameters.retain(|p| {
+                    !matches!(p.location, ParameterLocation::Path)
+                        || kept.contains(&p.name.as_str())
+                });
+                operation.operation_id = operation
+                    .operation_id
+                    .map(|id| format!("{}_without_{}", id, omitted.join("_and_")));
+            }
 
-        match route.method.to_uppercase().as_str() {
-            "GET" => path_item.get = Some(operation),
-            "POST" => path_item.post = Some(operation),
-            "PUT" => path_item.put = Some(operation),
-            "DELETE" => path_item.delete = Some(operation),
-            _ => {}
+            let path_item = spec.paths.entry(openapi_path(variant)).or_default();
+            match route.method.to_uppercase().as_str() {
+                "GET" => path_item.get = Some(operation),
+                "POST" => path_item.post = Some(operation),
+                "PUT" => path_item.put = Some(operation),
+                "DELETE" => path_item.delete = Some(operation),
+                "PATCH" => path_item.patch = Some(operation),
+                "HEAD" => path_item.head = Some(operation),
+                "OPTIONS" => path_item.options = Some(operation),
+                _ => {}
+            }
         }
     }
 
-    spec
+    let (schemas, collisions) = registry.into_parts();
+    spec.components = Some(Components {
+        schemas,
+        security_schemes,
+    });
+
+    (spec, collisions)
+}
+
+/// The operationId of a route before disambiguation: the one it was given,
+/// or its handler name.
+fn operation_id(route: &crate::introspection::RouteInfo) -> &str {
+    route.operation_id.as_deref().unwrap_or(&route.handler_name)
 }
 
 #[cfg(test)]
@@ -344,6 +732,361 @@ mod tests {
         assert!(spec.paths.contains_key("/users"));
     }
 
+    #[test]
+    fn test_build_openapi_spec_operation_docs() {
+        let mut route = RouteInfo::new("GET", "/users", "list_users", None, Vec::new());
+        route.summary = Some("List all users".to_string());
+        route.description = Some("Paginated.".to_string());
+        route.tags = vec!["Users".to_string()];
+        route.deprecated = true;
+        route.operation_id = Some("users.list".to_string());
+        let spec = build_openapi_spec("Test API", "1.0.0", &[route]);
+
+        let operation = spec.paths["/users"].get.as_ref().unwrap();
+        assert_eq!(operation.summary.as_deref(), Some("List all users"));
+        assert_eq!(operation.description.as_deref(), Some("Paginated."));
+        assert_eq!(operation.operation_id.as_deref(), Some("users.list"));
+        assert_eq!(operation.tags, vec!["Users"]);
+
+        let json = serde_json::to_value(operation).unwrap();
+        assert_eq!(json["deprecated"], true);
+    }
+
+    #[test]
+    fn test_build_openapi_spec_bearer_auth() {
+        let protected = RouteInfo::new("GET", "/me", "me", None, Vec::new());
+        let mut public = RouteInfo::new("POST", "/login", "login", None, Vec::new());
+        public.public = true;
+        let options = OpenApiOptions::new().bearer_auth(true);
+        let spec =
+            try_build_openapi_spec("Test API", "1.0.0", &[protected, public], options).unwrap();
+        let json = serde_json::to_value(&spec).unwrap();
+
+        assert_eq!(json["security"], serde_json::json!([{ "bearerAuth": [] }]));
+        let scheme = &json["components"]["securitySchemes"]["bearerAuth"];
+        assert_eq!(scheme["type"], "http");
+        assert_eq!(scheme["scheme"], "bearer");
+        assert_eq!(scheme["bearerFormat"], "JWT");
+
+        let me = &json["paths"]["/me"]["get"];
+        assert!(me.get("security").is_none());
+        assert_eq!(
+            me["responses"]["401"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ErrorResponse"
+        );
+
+        let login = &json["paths"]["/login"]["post"];
+        assert_eq!(login["security"], serde_json::json!([]));
+        assert!(login["responses"].get("401").is_none());
+    }
+
+    #[test]
+    fn test_build_openapi_spec_without_auth_has_no_security() {
+        let routes = vec![RouteInfo::new("GET", "/me", "me", None, Vec::new())];
+        let json = serde_json::to_value(build_openapi_spec("Test API", "1.0.0", &routes)).unwrap();
+
+        assert!(json.get("security").is_none());
+        assert!(json["components"].get("securitySchemes").is_none());
+        assert!(
+            json["paths"]["/me"]["get"]["responses"]
+                .get("401")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_build_openapi_spec_all_methods() {
+        let routes: Vec<_> = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"]
+            .into_iter()
+            .map(|method| RouteInfo::new(method, "/items", "items", None, Vec::new()))
+            .collect();
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+
+        let item = &spec.paths["/items"];
+        assert!(item.get.is_some());
+        assert!(item.post.is_some());
+        assert!(item.put.is_some());
+        assert!(item.patch.is_some());
+        assert!(item.delete.is_some());
+        assert!(item.head.is_some());
+        assert!(item.options.is_some());
+    }
+
+    #[test]
+    fn test_build_openapi_spec_default_operation_docs() {
+        let routes = vec![RouteInfo::new(
+            "GET",
+            "/users",
+            "list_users",
+            None,
+            Vec::new(),
+        )];
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+
+        let operation = spec.paths["/users"].get.as_ref().unwrap();
+        assert_eq!(operation.summary.as_deref(), Some("List users"));
+        assert_eq!(operation.operation_id.as_deref(), Some("list_users"));
+
+        let json = serde_json::to_value(operation).unwrap();
+        assert!(json.get("deprecated").is_none());
+        assert!(json.get("tags").is_none());
+        assert!(json.get("description").is_none());
+    }
+
+    #[test]
+    fn test_build_openapi_spec_custom_content_type() {
+        let mut route = RouteInfo::new(
+            "GET",
+            "/events",
+            "events",
+            Some(serde_json::json!({ "type": "string" })),
+            Vec::new(),
+        );
+        route.response_content_type = Some("text/event-stream".to_string());
+        let spec = build_openapi_spec("Test API", "1.0.0", &[route]);
+
+        let operation = spec.paths["/events"].get.as_ref().unwrap();
+        let content = operation.responses["200"].content.as_ref().unwrap();
+        assert!(content.contains_key("text/event-stream"));
+        assert!(!content.contains_key("application/json"));
+    }
+
+    #[test]
+    fn test_build_openapi_spec_request_body() {
+        let mut route = RouteInfo::new("POST", "/uploads", "upload", None, Vec::new());
+        route.request_schema = Some(serde_json::json!({ "type": "object" }));
+        route.request_content_type = Some("multipart/form-data".to_string());
+        let spec = build_openapi_spec("Test API", "1.0.0", &[route]);
+
+        let operation = spec.paths["/uploads"].post.as_ref().unwrap();
+        let body = operation.request_body.as_ref().unwrap();
+        assert!(body.required);
+        assert!(body.content.contains_key("multipart/form-data"));
+
+        let spec = build_openapi_spec(
+            "Test API",
+            "1.0.0",
+            &[RouteInfo::new(
+                "GET",
+                "/users",
+                "list_users",
+                None,
+                Vec::new(),
+            )],
+        );
+        assert!(
+            spec.paths["/users"]
+                .get
+                .as_ref()
+                .unwrap()
+                .request_body
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_build_openapi_spec_json_request_body() {
+        let mut route = RouteInfo::new("POST", "/users", "create_user", None, Vec::new());
+        route.request_schema = Some(serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "CreateUser",
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+        }));
+        let spec = build_openapi_spec("Test API", "1.0.0", &[route]);
+
+        let operation = spec.paths["/users"].post.as_ref().unwrap();
+        let body = operation.request_body.as_ref().unwrap();
+        assert!(matches!(
+            &body.content["application/json"].schema,
+            Schema::Ref { reference } if reference == "#/components/schemas/CreateUser"
+        ));
+
+        let schema = &spec.components.unwrap().schemas["CreateUser"];
+        assert_eq!(schema["type"], "object");
+        assert!(schema.get("$schema").is_none());
+    }
+
+    fn user_route(handler: &str, properties: serde_json::Value) -> RouteInfo {
+        RouteInfo::new(
+            "GET",
+            format!("/{}", handler),
+            handler,
+            Some(serde_json::json!({
+                "title": "User",
+                "type": "object",
+                "properties": properties,
+            })),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_build_openapi_spec_deduplicates_schemas() {
+        let id = serde_json::json!({ "id": { "type": "integer" } });
+        let routes = vec![user_route("get_user", id.clone()), user_route("me", id)];
+        let spec =
+            try_build_openapi_spec("Test API", "1.0.0", &routes, OpenApiOptions::new()).unwrap();
+
+        for path in ["/get_user", "/me"] {
+            let response = &spec.paths[path].get.as_ref().unwrap().responses["200"];
+            assert!(matches!(
+                &response.content.as_ref().unwrap()["application/json"].schema,
+                Schema::Ref { reference } if reference == "#/components/schemas/User"
+            ));
+        }
+        let schemas = spec.components.unwrap().schemas;
+        assert_eq!(schemas.len(), 2); // User and ErrorResponse
+    }
+
+    #[test]
+    fn test_build_openapi_spec_schema_collision() {
+        let routes = vec![
+            user_route(
+                "get_user",
+                serde_json::json!({ "id": { "type": "integer" } }),
+            ),
+            user_route(
+                "get_admin",
+                serde_json::json!({ "name": { "type": "string" } }),
+            ),
+        ];
+
+        let err = try_build_openapi_spec("Test API", "1.0.0", &routes, OpenApiOptions::new())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            OpenApiError::SchemaCollision {
+                name: "User".to_string(),
+                first: "get_user".to_string(),
+                second: "get_admin".to_string(),
+            }
+        );
+        assert!(err.to_string().contains("'User'"));
+
+        // The lenient builder keeps both under different names
+        let spec = build_openapi_spec("Test API", "1.0.0", &routes);
+        let schemas = spec.components.unwrap().schemas;
+        assert!(schemas.contains_key("User"));
+        assert!(schemas.contains_key("User_2"));
+    }
+
+    #[test]
+    fn test_build_openapi_spec_versions() {
+        let mut route = RouteInfo::new("GET", "/users", "list_users", None, Vec::new());
+        route.query_schema = Some(serde_json::json!({
+            "type": "object",
+            "properties": { "search": { "type": ["string", "null"] } },
+        }));
+
+        let spec = build_openapi_spec("Test API", "1.0.0", std::slice::from_ref(&route));
+        assert_eq!(spec.openapi, "3.0.3");
+        let param = &spec.paths["/users"].get.as_ref().unwrap().parameters[0];
+        assert!(matches!(
+            &param.schema,
+            Some(Schema::Inline(schema)) if *schema == serde_json::json!({ "type": "string", "nullable": true })
+        ));
+
+        let spec = try_build_openapi_spec(
+            "Test API",
+            "1.0.0",
+            &[route],
+            OpenApiOptions::new().version(OpenApiVersion::V3_1),
+        )
+        .unwrap();
+        assert_eq!(spec.openapi, "3.1.0");
+        let param = &spec.paths["/users"].get.as_ref().unwrap().parameters[0];
+        assert!(matches!(
+            &param.schema,
+            Some(Schema::Inline(schema)) if schema["type"] == serde_json::json!(["string", "null"])
+        ));
+    }
+
+    #[test]
+    fn test_build_openapi_spec_query_parameters() {
+        let mut route = RouteInfo::new("GET", "/users", "list_users", None, Vec::new());
+        route.query_schema = Some(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "page": { "type": "integer", "description": "Page number" },
+                "search": { "type": "string" },
+            },
+            "required": ["page"],
+        }));
+        let spec = build_openapi_spec("Test API", "1.0.0", &[route]);
+
+        let params = &spec.paths["/users"].get.as_ref().unwrap().parameters;
+        assert_eq!(params.len(), 2);
+        let page = params.iter().find(|p| p.name == "page").unwrap();
+        assert!(matches!(page.location, ParameterLocation::Query));
+        assert!(page.required);
+        assert_eq!(page.description.as_deref(), Some("Page number"));
+        let search = params.iter().find(|p| p.name == "search").unwrap();
+        assert!(!search.required);
+    }
+
+    #[test]
+    fn test_build_openapi_spec_typed_path_parameters() {
+        let mut route = RouteInfo::new("GET", "/users/:id", "get_user", None, Vec::new());
+        route.path_schema = Some(serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "uint64",
+            "type": "integer",
+            "format": "uint64",
+        }));
+        let untyped = RouteInfo::new("GET", "/posts/:slug", "get_post", None, Vec::new());
+        let spec = build_openapi_spec("Test API", "1.0.0", &[route, untyped]);
+
+        let params = &spec.paths["/users/{id}"].get.as_ref().unwrap().parameters;
+        let Some(Schema::Inline(schema)) = &params[0].schema else {
+            panic!("expected inline schema");
+        };
+        assert_eq!(
+            schema,
+            &serde_json::json!({ "type": "integer", "format": "uint64" })
+        );
+
+        let params = &spec.paths["/posts/{slug}"].get.as_ref().unwrap().parameters;
+        let Some(Schema::Inline(schema)) = &params[0].schema else {
+            panic!("expected inline schema");
+        };
+        assert_eq!(schema["type"], "string");
+    }
+
+    #[test]
+    fn test_path_parameters_from_tuple_and_struct_schemas() {
+        let tuple = serde_json::json!({
+            "type": "array",
+            "prefixItems": [{ "type": "integer" }, { "type": "string" }],
+        });
+        let params = path_parameters(
+            Pattern::parse("/users/:id/posts/:slug").unwrap().segments(),
+            Some(&tuple),
+        );
+        let schemas: Vec<_> = params
+            .iter()
+            .map(|p| match &p.schema {
+                Some(Schema::Inline(schema)) => schema["type"].clone(),
+                _ => panic!("expected inline schema"),
+            })
+            .collect();
+        assert_eq!(schemas, vec!["integer", "string"]);
+
+        let object = serde_json::json!({
+            "type": "object",
+            "properties": { "slug": { "type": "string" }, "id": { "type": "integer" } },
+        });
+        let params = path_parameters(
+            Pattern::parse("/users/:id/posts/:slug").unwrap().segments(),
+            Some(&object),
+        );
+        assert_eq!(params[0].name, "id");
+        assert!(matches!(
+            &params[0].schema,
+            Some(Schema::Inline(schema)) if schema["type"] == "integer"
+        ));
+    }
+
     #[test]
     fn test_build_openapi_spec_with_error_responses() {
         let errors = vec![
@@ -4,11 +4,108 @@
 //! to be converted into HTTP responses.
 
 use bytes::Bytes;
-use http::{Response, StatusCode};
-use http_body_util::Full;
+use futures_util::TryStream;
+use futures_util::TryStreamExt;
+use http::{HeaderValue, Response, StatusCode, header};
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Empty, Full, StreamBody};
+use hyper::body::Frame;
+
+/// The error type carried by response bodies.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
 /// The body type used for HTTP responses.
-pub type BoxBody = Full<Bytes>;
+///
+/// Bodies are boxed so that handlers and middleware can return either a
+/// buffered payload or a stream of chunks without changing their signature.
+/// Use [`full`] and [`empty`] to build buffered bodies, or [`Stream`] to
+/// send data as it is produced.
+pub type BoxBody = UnsyncBoxBody<Bytes, BoxError>;
+
+/// Creates a body from a complete, in-memory payload.
+pub fn full(data: impl Into<Bytes>) -> BoxBody {
+    Full::new(data.into())
+        .map_err(|never| match never {})
+        .boxed_unsync()
+}
+
+/// Creates an empty body.
+pub fn empty() -> BoxBody {
+    Empty::new().map_err(|never| match never {}).boxed_unsync()
+}
+
+/// A streaming response body.
+///
+/// Wraps any [`futures_util::Stream`] of `Result<Bytes, E>` and sends each
+/// chunk to the client as soon as it is produced, without buffering the
+/// whole payload in memory. Useful for large exports and file downloads.
+///
+/// The content type defaults to `application/octet-stream`.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::bytes::Bytes;
+/// use rapina::futures_util::stream::{self, BoxStream, StreamExt};
+/// use rapina::prelude::*;
+/// use rapina::response::Stream;
+///
+/// #[get("/export.csv")]
+/// async fn export() -> Stream<BoxStream<'static, std::io::Result<Bytes>>> {
+///     let rows = stream::iter(vec![
+///         Ok(Bytes::from("id,name\n")),
+///         Ok(Bytes::from("1,Alice\n")),
+///     ]);
+///     Stream::new(rows.boxed()).content_type("text/csv")
+/// }
+/// ```
+pub struct Stream<S> {
+    stream: S,
+    status: StatusCode,
+    content_type: HeaderValue,
+}
+
+impl<S> Stream<S> {
+    /// Creates a streaming response with a `200 OK` status.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            status: StatusCode::OK,
+            content_type: HeaderValue::from_static("application/octet-stream"),
+        }
+    }
+
+    /// Sets the response status code.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets the `Content-Type` header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    pub fn content_type(mut self, content_type: &str) -> Self {
+        self.content_type = HeaderValue::from_str(content_type).expect("invalid content type");
+        self
+    }
+}
+
+impl<S> IntoResponse for Stream<S>
+where
+    S: TryStream<Ok = Bytes> + Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    fn into_response(self) -> Response<BoxBody> {
+        let body = StreamBody::new(self.stream.map_ok(Frame::data).map_err(Into::into));
+        Response::builder()
+            .status(self.status)
+            .header(header::CONTENT_TYPE, self.content_type)
+            .body(BodyExt::boxed_unsync(body))
+            .unwrap()
+    }
+}
 
 /// Trait for types that can be converted into an HTTP response.
 ///
@@ -43,12 +140,18 @@ impl IntoResponse for Response<BoxBody> {
     }
 }
 
+impl IntoResponse for Response<Full<Bytes>> {
+    fn into_response(self) -> Response<BoxBody> {
+        self.map(|body| body.map_err(|never| match never {}).boxed_unsync())
+    }
+}
+
 impl IntoResponse for &str {
     fn into_response(self) -> Response<BoxBody> {
         Response::builder()
             .status(StatusCode::OK)
             .header("content-type", "text/plain; charset=utf-8")
-            .body(Full::new(Bytes::from(self.to_owned())))
+            .body(full(self.to_owned()))
             .unwrap()
     }
 }
@@ -58,17 +161,14 @@ impl IntoResponse for String {
         Response::builder()
             .status(StatusCode::OK)
             .header("content-type", "text/plain; charset=utf-8")
-            .body(Full::new(Bytes::from(self.to_owned())))
+            .body(full(self.to_owned()))
             .unwrap()
     }
 }
 
 impl IntoResponse for StatusCode {
     fn into_response(self) -> Response<BoxBody> {
-        Response::builder()
-            .status(self)
-            .body(Full::new(Bytes::new()))
-            .unwrap()
+        Response::builder().status(self).body(empty()).unwrap()
     }
 }
 
@@ -77,7 +177,7 @@ impl IntoResponse for (StatusCode, String) {
         Response::builder()
             .status(self.0)
             .header("content-type", "text/plain; charset=utf-8")
-            .body(Full::new(Bytes::from(self.1)))
+            .body(full(self.1))
             .unwrap()
     }
 }
@@ -95,6 +195,7 @@ impl<T: IntoResponse, E: IntoResponse> IntoResponse for std::result::Result<T, E
 mod tests {
     use super::*;
     use http_body_util::BodyExt;
+    use hyper::body::Body;
 
     #[tokio::test]
     async fn test_str_into_response() {
@@ -170,6 +271,17 @@ mod tests {
 
     #[test]
     fn test_response_into_response_identity() {
+        let original = Response::builder()
+            .status(StatusCode::ACCEPTED)
+            .body(full("test"))
+            .unwrap();
+
+        let response = original.into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_full_response_into_response() {
         let original = Response::builder()
             .status(StatusCode::ACCEPTED)
             .body(Full::new(Bytes::from("test")))
@@ -177,6 +289,43 @@ mod tests {
 
         let response = original.into_response();
         assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"test");
+    }
+
+    #[tokio::test]
+    async fn test_stream_into_response() {
+        let chunks = futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(Bytes::from("id,name\n")),
+            Ok(Bytes::from("1,Alice\n")),
+        ]);
+        let response = Stream::new(chunks).content_type("text/csv").into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "text/csv");
+        assert!(response.body().size_hint().exact().is_none());
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"id,name\n1,Alice\n");
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_is_propagated() {
+        let chunks = futures_util::stream::iter(vec![
+            Ok(Bytes::from("partial")),
+            Err(std::io::Error::other("disk failure")),
+        ]);
+        let response = Stream::new(chunks)
+            .status(StatusCode::PARTIAL_CONTENT)
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/octet-stream"
+        );
+        assert!(response.into_body().collect().await.is_err());
     }
 
     #[tokio::test]
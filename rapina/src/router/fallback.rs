@@ -0,0 +1,215 @@
+//! Responses for requests that match no route.
+//!
+//! Fallbacks are scoped by path prefix: a fallback set on a router mounted
+//! with [`Router::group`](super::Router::group) only answers paths under
+//! the group's prefix, and the most specific prefix wins.
+
+use std::path::{Path, PathBuf};
+
+use http::{Method, Request, Response, Uri, header};
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::extract::{PathParams, RequestBody};
+use crate::response::{BoxBody, IntoResponse, full};
+
+use super::HandlerFn;
+use super::pattern::Pattern;
+
+/// What to do with a request that matches no route.
+pub(crate) enum Fallback {
+    /// Calls a handler.
+    Handler(HandlerFn),
+    /// Serves a single-page app's `index.html` for GET and HEAD requests.
+    Spa(PathBuf),
+    /// Calls a mounted service with the prefix stripped from the path.
+    Mount(HandlerFn),
+}
+
+impl Fallback {
+    fn applies_to(&self, method: &Method) -> bool {
+        match self {
+            Fallback::Handler(_) | Fallback::Mount(_) => true,
+            Fallback::Spa(_) => method == Method::GET || method == Method::HEAD,
+        }
+    }
+}
+
+/// Finds the fallback with the longest prefix matching `path`.
+///
+/// Among equally specific fallbacks, the last registered one wins.
+pub(crate) fn find<'a, T>(
+    fallbacks: &'a [(String, T)],
+    path: &str,
+    applies: impl Fn(&T) -> bool,
+) -> Option<(&'a T, PathParams)> {
+    find_with_depth(fallbacks, path, applies).map(|(_, fallback, params)| (fallback, params))
+}
+
+/// Finds the not-found fallback for the given request, along with the
+/// number of path segments its prefix matched.
+pub(crate) fn find_not_found<'a>(
+    fallbacks: &'a [(String, Fallback)],
+    method: &Method,
+    path: &str,
+) -> Option<(usize, &'a Fallback, PathParams)> {
+    find_with_depth(fallbacks, path, |fallback| fallback.applies_to(method))
+}
+
+fn find_with_depth<'a, T>(
+    fallbacks: &'a [(String, T)],
+    path: &str,
+    applies: impl Fn(&T) -> bool,
+) -> Option<(usize, &'a T, PathParams)> {
+    fallbacks
+        .iter()
+        .filter(|(_, fallback)| applies(fallback))
+        .filter_map(|(prefix, fallback)| {
+            match_prefix(prefix, path).map(|(depth, params)| (depth, fallback, params))
+        })
+        .max_by_key(|(depth, _, _)| *depth)
+}
+
+/// Removes the first `depth` segments matched by a mount's prefix from a
+/// request URI, keeping the query string.
+pub(crate) fn strip_prefix(uri: &Uri, depth: usize) -> Uri {
+    // The leading empty segment is always dropped, even for a root mount
+    let rest = uri
+        .path()
+        .split('/')
+        .skip(depth.max(1))
+        .collect::<Vec<_>>()
+        .join("/");
+    let path_and_query = match uri.query() {
+        Some(query) => format!("/{}?{}", rest, query),
+        None => format!("/{}", rest),
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = path_and_query.parse().ok();
+    Uri::from_parts(parts).unwrap_or_else(|_| uri.clone())
+}
+
+/// Matches the leading segments of `path` against `prefix`, returning the
+/// number of matched segments and the parameters captured by the prefix.
+fn match_prefix(prefix: &str, path: &str) -> Option<(usize, PathParams)> {
+    let prefix = prefix.trim_end_matches('/');
+    if prefix.is_empty() {
+        return Some((0, PathParams::new()));
+    }
+
+    let depth = prefix.split('/').count();
+    let head = path.split('/').take(depth).collect::<Vec<_>>().join("/");
+    let params = Pattern::parse(prefix).ok()?.matches(&head)?;
+    Some((depth, params))
+}
+
+/// The default `404` response, a standard error body with the trace ID.
+pub(crate) fn not_found(req: &Request<RequestBody>) -> Response<BoxBody> {
+    with_trace_id(
+        Error::not_found(format!(
+            "no route matches {} {}",
+            req.method(),
+            req.uri().path()
+        )),
+        req,
+    )
+    .into_response()
+}
+
+/// The default `405` response, a standard error body with the trace ID.
+pub(crate) fn method_not_allowed(req: &Request<RequestBody>) -> Response<BoxBody> {
+    with_trace_id(
+        Error::method_not_allowed(format!(
+            "method {} is not allowed for {}",
+            req.method(),
+            req.uri().path()
+        )),
+        req,
+    )
+    .into_response()
+}
+
+/// Serves a single-page app's index file.
+pub(crate) async fn spa_index(index: &Path, req: &Request<RequestBody>) -> Response<BoxBody> {
+    match tokio::fs::read(index).await {
+        Ok(contents) => Response::builder()
+            .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+            .body(full(contents))
+            .unwrap(),
+        Err(e) => {
+            tracing::error!(path = %index.display(), error = %e, "failed to read SPA index");
+            with_trace_id(Error::internal("failed to read the application index"), req)
+                .into_response()
+        }
+    }
+}
+
+fn with_trace_id(error: Error, req: &Request<RequestBody>) -> Error {
+    match req.extensions().get::<RequestContext>() {
+        Some(ctx) => error.with_trace_id(&ctx.trace_id),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_prefix() {
+        assert_eq!(match_prefix("/", "/anything").unwrap().0, 0);
+        assert_eq!(match_prefix("/api", "/api").unwrap().0, 2);
+        assert_eq!(match_prefix("/api/", "/api/users/1").unwrap().0, 2);
+        assert!(match_prefix("/api", "/apiv2").is_none());
+        assert!(match_prefix("/api", "/").is_none());
+    }
+
+    #[test]
+    fn test_match_prefix_captures_params() {
+        let (depth, params) = match_prefix("/orgs/:org", "/orgs/acme/missing").unwrap();
+        assert_eq!(depth, 3);
+        assert_eq!(params.get("org"), Some(&"acme".to_string()));
+    }
+
+    #[test]
+    fn test_find_prefers_longest_prefix() {
+        let fallbacks = vec![
+            ("/".to_string(), "root"),
+            ("/api".to_string(), "api"),
+            ("/api/admin".to_string(), "admin"),
+        ];
+
+        let find = |path| find(&fallbacks, path, |_| true).map(|(f, _)| *f);
+        assert_eq!(find("/page"), Some("root"));
+        assert_eq!(find("/api/users"), Some("api"));
+        assert_eq!(find("/api/admin/x"), Some("admin"));
+    }
+
+    #[test]
+    fn test_find_not_found_returns_depth() {
+        let fallbacks = vec![("/legacy".to_string(), Fallback::Spa(PathBuf::new()))];
+        let (depth, _, _) = find_not_found(&fallbacks, &Method::GET, "/legacy/a/b").unwrap();
+        assert_eq!(depth, 2);
+    }
+
+    #[test]
+    fn test_strip_prefix() {
+        let uri: Uri = "/legacy/a/b?x=1".parse().unwrap();
+        assert_eq!(strip_prefix(&uri, 2), "/a/b?x=1");
+        let uri: Uri = "/legacy".parse().unwrap();
+        assert_eq!(strip_prefix(&uri, 2), "/");
+        let uri: Uri = "/a/b?x=1".parse().unwrap();
+        assert_eq!(strip_prefix(&uri, 0), "/a/b?x=1");
+        let uri: Uri = "http://example.com/v1/orgs/acme/x".parse().unwrap();
+        assert_eq!(strip_prefix(&uri, 4), "http://example.com/x");
+    }
+
+    #[test]
+    fn test_spa_only_applies_to_get_and_head() {
+        let spa = Fallback::Spa(PathBuf::from("index.html"));
+        assert!(spa.applies_to(&Method::GET));
+        assert!(spa.applies_to(&Method::HEAD));
+        assert!(!spa.applies_to(&Method::POST));
+    }
+}
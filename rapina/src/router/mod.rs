@@ -0,0 +1,1185 @@
+//! HTTP routing for Rapina applications.
+//!
+//! The [`Router`] type collects route definitions and matches incoming
+//! requests to the appropriate handlers.
+
+mod fallback;
+pub(crate) mod pattern;
+mod tree;
+pub(crate) mod url;
+
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+
+use http::{HeaderValue, Method, Request, Response, header};
+
+use crate::context::RequestContext;
+use crate::error::ErrorVariant;
+use crate::extract::{PathParams, RequestBody};
+use crate::handler::Handler;
+use crate::introspection::RouteInfo;
+use crate::middleware::{Middleware, Next};
+use crate::response::{BoxBody, BoxError, IntoResponse};
+use crate::state::AppState;
+
+use fallback::Fallback;
+pub(crate) use tree::{Match, RouteTree};
+
+pub(crate) use url::RouteUrls;
+pub use url::UrlForError;
+#[doc(hidden)]
+pub use url::typed_path;
+
+type BoxFuture = Pin<Box<dyn Future<Output = Response<BoxBody>> + Send>>;
+
+/// Methods registered by [`Router::any`].
+const ANY_METHODS: &[Method] = &[
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+    Method::HEAD,
+    Method::OPTIONS,
+];
+pub(crate) type HandlerFn =
+    Box<dyn Fn(Request<RequestBody>, PathParams, Arc<AppState>) -> BoxFuture + Send + Sync>;
+
+pub(crate) struct Route {
+    pub(crate) pattern: String,
+    pub(crate) handler_name: String,
+    pub(crate) response_schema: Option<serde_json::Value>,
+    pub(crate) response_content_type: Option<&'static str>,
+    pub(crate) request_schema: Option<serde_json::Value>,
+    pub(crate) request_content_type: Option<&'static str>,
+    pub(crate) query_schema: Option<serde_json::Value>,
+    pub(crate) path_schema: Option<serde_json::Value>,
+    pub(crate) error_responses: Vec<ErrorVariant>,
+    pub(crate) summary: Option<&'static str>,
+    pub(crate) description: Option<&'static str>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) deprecated: bool,
+    pub(crate) operation_id: Option<&'static str>,
+    pub(crate) public: bool,
+    /// Middleware from enclosing groups' layers and `#[middleware]`, outermost first.
+    middlewares: Vec<Arc<dyn Middleware>>,
+    handler: HandlerFn,
+}
+
+/// Stored in the request extensions by
+/// [`MiddlewareStack::execute`](crate::middleware::MiddlewareStack::execute)
+/// when the request matches a route, so middleware like auth can act on the
+/// route the router will dispatch to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MatchedRoute {
+    pub(crate) public: bool,
+}
+
+/// Errors detected while compiling the router.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouterError {
+    /// Two routes with the same method match exactly the same paths.
+    Duplicate {
+        method: String,
+        existing: String,
+        pattern: String,
+    },
+    /// A route pattern could not be parsed.
+    InvalidPattern { pattern: String, reason: String },
+}
+
+impl fmt::Display for RouterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouterError::Duplicate {
+                method,
+                existing,
+                pattern,
+            } => write!(
+                f,
+                "Route {} {} conflicts with already registered route {} {}",
+                method, pattern, method, existing
+            ),
+            RouterError::InvalidPattern { pattern, reason } => {
+                write!(f, "Invalid route pattern {}: {}", pattern, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RouterError {}
+
+/// The HTTP router for matching requests to handlers.
+///
+/// Use path parameters with the `:param` syntax. Static segments always
+/// take precedence over parameters, so `/users/me` wins over `/users/:id`
+/// regardless of registration order.
+///
+/// Patterns also support:
+///
+/// - constrained parameters, `:id<int>`, `:id<uuid>` or `:code<[A-Z]{3}>`,
+///   which only match values that satisfy the constraint and are tried
+///   before unconstrained ones;
+/// - optional trailing parameters, `/posts/:page?`;
+/// - catch-all segments, `/files/*path`, which capture the rest of the path.
+///
+/// Routes are compiled into a prefix tree the first time the router is
+/// used (or explicitly via [`compile`](Self::compile)). A path that matches
+/// a route registered for a different method yields
+/// `405 Method Not Allowed` with an `Allow` header.
+///
+/// Unmatched requests get a standard error body with the request's
+/// `trace_id`, unless a [`fallback`](Self::fallback),
+/// [`method_not_allowed`](Self::method_not_allowed) or
+/// [`spa_fallback`](Self::spa_fallback) handles them.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::prelude::*;
+///
+/// #[get("/")]
+/// async fn hello() -> &'static str { "Hello!" }
+///
+/// #[get("/users/:id")]
+/// async fn get_user() -> &'static str { "User" }
+///
+/// #[post("/users")]
+/// async fn create_user() -> StatusCode { StatusCode::CREATED }
+///
+/// let router = Router::new()
+///     .get("/", hello)
+///     .get("/users/:id", get_user)
+///     .post("/users", create_user);
+/// ```
+pub struct Router {
+    pub(crate) routes: Vec<(Method, Route)>,
+    fallbacks: Vec<(String, Fallback)>,
+    method_not_allowed: Vec<(String, HandlerFn)>,
+    layers: Vec<Arc<dyn Middleware>>,
+    tree: OnceLock<RouteTree>,
+}
+
+impl Router {
+    /// Creates a new empty router.
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallbacks: Vec::new(),
+            method_not_allowed: Vec::new(),
+            layers: Vec::new(),
+            tree: OnceLock::new(),
+        }
+    }
+
+    /// Adds a route with the given HTTP method, pattern, and handler name.
+    ///
+    /// The handler name is used for route introspection and documentation.
+    pub fn route_named<F, Fut, Out>(
+        mut self,
+        method: Method,
+        pattern: &str,
+        handler_name: &str,
+        response_schema: Option<serde_json::Value>,
+        error_responses: Vec<ErrorVariant>,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Request<RequestBody>, PathParams, Arc<AppState>) -> Fut
+            + Send
+            + Sync
+            + Clone
+            + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        let handler = Box::new(
+            move |req: Request<RequestBody>, params: PathParams, state: Arc<AppState>| {
+                let handler = handler.clone();
+                Box::pin(async move {
+                    let output = handler(req, params, state).await;
+                    output.into_response()
+                }) as BoxFuture
+            },
+        );
+
+        let route = Route {
+            pattern: pattern.to_string(),
+            handler_name: handler_name.to_string(),
+            response_schema,
+            response_content_type: None,
+            request_schema: None,
+            request_content_type: None,
+            query_schema: None,
+            path_schema: None,
+            error_responses,
+            summary: None,
+            description: None,
+            tags: Vec::new(),
+            deprecated: false,
+            operation_id: None,
+            public: false,
+            middlewares: Vec::new(),
+            handler,
+        };
+
+        self.routes.push((method, route));
+        self.tree = OnceLock::new();
+        self
+    }
+
+    /// Adds a route with the given HTTP method and pattern.
+    ///
+    /// The handler name defaults to "handler". Use [`route_named`](Self::route_named)
+    /// to specify a custom handler name for introspection.
+    pub fn route<F, Fut, Out>(self, method: Method, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request<RequestBody>, PathParams, Arc<AppState>) -> Fut
+            + Send
+            + Sync
+            + Clone
+            + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        self.route_named(method, pattern, "handler", None, Vec::new(), handler)
+    }
+
+    /// Adds a GET route with a handler name.
+    pub fn get_named<F, Fut, Out>(self, pattern: &str, handler_name: &str, handler: F) -> Self
+    where
+        F: Fn(Request<RequestBody>, PathParams, Arc<AppState>) -> Fut
+            + Send
+            + Sync
+            + Clone
+            + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        self.route_named(
+            Method::GET,
+            pattern,
+            handler_name,
+            None,
+            Vec::new(),
+            handler,
+        )
+    }
+
+    /// Adds a POST route with a handler name.
+    pub fn post_named<F, Fut, Out>(self, pattern: &str, handler_name: &str, handler: F) -> Self
+    where
+        F: Fn(Request<RequestBody>, PathParams, Arc<AppState>) -> Fut
+            + Send
+            + Sync
+            + Clone
+            + 'static,
+        Fut: Future<Output = Out> + Send + 'static,
+        Out: IntoResponse + 'static,
+    {
+        self.route_named(
+            Method::POST,
+            pattern,
+            handler_name,
+            None,
+            Vec::new(),
+            handler,
+        )
+    }
+
+    /// Adds a GET route with a Handler.
+    pub fn get<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.handler(Method::GET, pattern, handler)
+    }
+
+    /// Adds a POST route with a Handler.
+    pub fn post<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.handler(Method::POST, pattern, handler)
+    }
+
+    /// Adds a PUT route with a Handler.
+    pub fn put<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.handler(Method::PUT, pattern, handler)
+    }
+
+    /// Adds a DELETE route with a Handler.
+    pub fn delete<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.handler(Method::DELETE, pattern, handler)
+    }
+
+    /// Adds a PATCH route with a Handler.
+    pub fn patch<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.handler(Method::PATCH, pattern, handler)
+    }
+
+    /// Adds a HEAD route with a Handler.
+    ///
+    /// Only needed to customize HEAD responses: HEAD requests are answered
+    /// by the GET route for the same path otherwise, without the body.
+    pub fn head<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.handler(Method::HEAD, pattern, handler)
+    }
+
+    /// Adds an OPTIONS route with a Handler.
+    pub fn options<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.handler(Method::OPTIONS, pattern, handler)
+    }
+
+    /// Adds a route handling each of the given methods with the same Handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// #[put("/users/:id")]
+    /// async fn save_user() -> StatusCode { StatusCode::NO_CONTENT }
+    ///
+    /// let router = Router::new().on(&[Method::PUT, Method::PATCH], "/users/:id", save_user);
+    /// assert_eq!(router.routes().len(), 2);
+    /// ```
+    pub fn on<H: Handler>(mut self, methods: &[Method], pattern: &str, handler: H) -> Self {
+        for method in methods {
+            self = self.handler(method.clone(), pattern, handler.clone());
+        }
+        self
+    }
+
+    /// Adds a route handling GET, POST, PUT, PATCH, DELETE, HEAD and
+    /// OPTIONS with the same Handler.
+    pub fn any<H: Handler>(self, pattern: &str, handler: H) -> Self {
+        self.on(ANY_METHODS, pattern, handler)
+    }
+
+    /// Adds middleware that runs for every route of this router.
+    ///
+    /// Layers apply to all routes of the router, including those added
+    /// after the call, and stay attached to them when the router is mounted
+    /// with [`group`](Self::group). They run after global middleware, the
+    /// first added layer outermost, and before the handler's own
+    /// `#[middleware(...)]` attributes. Unmatched requests don't go
+    /// through them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rapina::middleware::TimeoutMiddleware;
+    /// use rapina::prelude::*;
+    ///
+    /// #[get("/reports")]
+    /// async fn build_report() -> &'static str { "report" }
+    ///
+    /// let admin = Router::new()
+    ///     .get("/reports", build_report)
+    ///     .layer(TimeoutMiddleware::new(Duration::from_secs(300)));
+    ///
+    /// let router = Router::new().group("/admin", admin);
+    /// assert_eq!(router.routes()[0].middleware, vec!["TimeoutMiddleware"]);
+    /// ```
+    pub fn layer<M: Middleware>(mut self, middleware: M) -> Self {
+        self.layers.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets the handler for requests that match no route.
+    ///
+    /// On a router mounted with [`group`](Self::group), the fallback only
+    /// answers paths under the group's prefix. The fallback with the most
+    /// specific prefix wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// #[get("/")]
+    /// async fn not_found() -> Error {
+    ///     Error::not_found("nothing here")
+    /// }
+    ///
+    /// let router = Router::new().fallback(not_found);
+    /// ```
+    pub fn fallback<H: Handler>(mut self, handler: H) -> Self {
+        self.fallbacks
+            .push(("/".to_string(), Fallback::Handler(boxed(handler))));
+        self
+    }
+
+    /// Sets the handler for requests whose path matches a route registered
+    /// for other methods.
+    ///
+    /// The router adds the `Allow` header to the handler's response unless
+    /// it already has one. Scoped to the group's prefix like
+    /// [`fallback`](Self::fallback).
+    pub fn method_not_allowed<H: Handler>(mut self, handler: H) -> Self {
+        self.method_not_allowed
+            .push(("/".to_string(), boxed(handler)));
+        self
+    }
+
+    /// Serves the file at `index` for unmatched GET and HEAD requests under
+    /// `prefix`, so a single-page app can handle its own client-side routes.
+    ///
+    /// The file is read on each request. Other methods fall through to the
+    /// regular not-found handling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let router = Router::new().spa_fallback("/app", "dist/index.html");
+    /// ```
+    pub fn spa_fallback(mut self, prefix: &str, index: impl Into<PathBuf>) -> Self {
+        self.fallbacks
+            .push((prefix.to_string(), Fallback::Spa(index.into())));
+        self
+    }
+
+    /// Sends requests under `prefix` to a tower service, e.g. an existing
+    /// hyper or tower application.
+    ///
+    /// The service sees the path without the prefix, so a request for
+    /// `/legacy/users` reaches it as `/users`. Routes registered on the
+    /// router take precedence; every other request under the prefix goes
+    /// to the service, whatever its method. Errors returned by the service
+    /// become `500` responses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::response::full;
+    ///
+    /// let legacy = tower::service_fn(|_req: http::Request<rapina::extract::RequestBody>| async {
+    ///     Ok::<_, std::convert::Infallible>(http::Response::new(full("legacy")))
+    /// });
+    ///
+    /// let router = Router::new().mount("/legacy", legacy);
+    /// ```
+    pub fn mount<S, ResBody>(mut self, prefix: &str, service: S) -> Self
+    where
+        S: tower::Service<Request<RequestBody>, Response = Response<ResBody>>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Future: Send,
+        S::Error: Into<BoxError>,
+        ResBody: hyper::body::Body<Data = bytes::Bytes> + Send + 'static,
+        ResBody::Error: Into<BoxError>,
+    {
+        if !prefix.starts_with('/') {
+            panic!("A mount's prefix must start with /");
+        }
+        self.fallbacks.push((
+            prefix.to_string(),
+            Fallback::Mount(crate::service::service_handler(service)),
+        ));
+        self
+    }
+
+    /// Adds a route backed by a [`Handler`], recording its documentation metadata.
+    fn handler<H: Handler>(self, method: Method, pattern: &str, handler: H) -> Self {
+        let mut router = self.route_named(
+            method,
+            pattern,
+            H::NAME,
+            H::response_schema(),
+            H::error_responses(),
+            move |req, params, state| {
+                let h = handler.clone();
+                async move { h.call(req, params, state).await }
+            },
+        );
+        if let Some((_, route)) = router.routes.last_mut() {
+            route.response_content_type = H::response_content_type();
+            route.request_schema = H::request_schema();
+            route.request_content_type = H::request_content_type();
+            route.query_schema = H::query_schema();
+            route.path_schema = H::path_schema();
+            route.summary = H::summary();
+            route.description = H::description();
+            route.tags = H::tags().iter().map(|tag| tag.to_string()).collect();
+            route.deprecated = H::deprecated();
+            route.operation_id = H::operation_id();
+            route.public = H::is_public();
+            route.middlewares = H::middlewares();
+        }
+        router
+    }
+
+    /// Returns metadata about all registered routes.
+    ///
+    /// This is useful for introspection, documentation generation,
+    /// and AI-native tooling integration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let router = Router::new()
+    ///     .get_named("/users", "list_users", |_, _, _| async { "users" })
+    ///     .post_named("/users", "create_user", |_, _, _| async { StatusCode::CREATED });
+    ///
+    /// let routes = router.routes();
+    /// assert_eq!(routes.len(), 2);
+    /// assert_eq!(routes[0].method, "GET");
+    /// assert_eq!(routes[0].path, "/users");
+    /// assert_eq!(routes[0].handler_name, "list_users");
+    /// ```
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        self.routes
+            .iter()
+            .map(|(method, route)| {
+                let mut info = RouteInfo::new(
+                    method.as_str(),
+                    &route.pattern,
+                    &route.handler_name,
+                    route.response_schema.clone(),
+                    route.error_responses.clone(),
+                );
+                info.response_content_type = route.response_content_type.map(str::to_string);
+                info.request_schema = route.request_schema.clone();
+                info.request_content_type = route.request_content_type.map(str::to_string);
+                info.query_schema = route.query_schema.clone();
+                info.path_schema = route.path_schema.clone();
+                info.summary = route.summary.map(str::to_string);
+                info.description = route.description.map(str::to_string);
+                info.tags = route.tags.clone();
+                info.deprecated = route.deprecated;
+                info.operation_id = route.operation_id.map(str::to_string);
+                info.public = route.public;
+                info.middleware = self
+                    .layers
+                    .iter()
+                    .chain(&route.middlewares)
+                    .map(|middleware| middleware.name().to_string())
+                    .collect();
+                info
+            })
+            .collect()
+    }
+
+    /// Builds the path to the route registered with the given handler name.
+    ///
+    /// Parameters are matched by name and percent-encoded. Optional
+    /// segments are left out when their parameter isn't given, and
+    /// catch-all values keep their slashes. Group prefixes are included.
+    /// When several routes share a handler name, the first registered one
+    /// is used.
+    ///
+    /// Returns an error if the route doesn't exist, a required parameter is
+    /// missing, a value doesn't satisfy its constraint or a parameter isn't
+    /// part of the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// #[get("/users/:id")]
+    /// async fn get_user(id: Path<u64>) -> String { id.into_inner().to_string() }
+    ///
+    /// let router = Router::new().group("/api", Router::new().get("/users/:id", get_user));
+    ///
+    /// assert_eq!(router.url_for("get_user", &[("id", &42)]).unwrap(), "/api/users/42");
+    /// assert!(router.url_for("get_user", &[]).is_err());
+    /// ```
+    pub fn url_for(
+        &self,
+        name: &str,
+        params: &[(&str, &dyn fmt::Display)],
+    ) -> Result<String, UrlForError> {
+        let (_, route) = self
+            .routes
+            .iter()
+            .find(|(_, route)| route.handler_name == name)
+            .ok_or_else(|| UrlForError::UnknownRoute(name.to_string()))?;
+        url::build_path(name, &route.pattern, params)
+    }
+
+    /// Adds all routes from another router with a path prefix to compose a group of endpoints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// let users_router = Router::new();
+    ///
+    /// let invoices_router = Router::new();
+    ///
+    /// let router = Router::new()
+    ///     .group("/api/users", users_router)
+    ///     .group("/api/invoices", invoices_router);
+    /// ```
+    pub fn group(mut self, prefix_pattern: &str, router: Router) -> Self {
+        if !prefix_pattern.starts_with("/") {
+            panic!("A group's prefix pattern must start with /");
+        }
+
+        for (method, mut route) in router.routes {
+            let joined_route_path = Self::join_group_route_pattern(prefix_pattern, &route.pattern);
+            route.pattern = joined_route_path;
+            route.middlewares = router
+                .layers
+                .iter()
+                .cloned()
+                .chain(route.middlewares)
+                .collect();
+            self.routes.push((method, route));
+        }
+        for (prefix, fallback) in router.fallbacks {
+            let prefix = Self::join_group_route_pattern(prefix_pattern, &prefix);
+            self.fallbacks.push((prefix, fallback));
+        }
+        for (prefix, handler) in router.method_not_allowed {
+            let prefix = Self::join_group_route_pattern(prefix_pattern, &prefix);
+            self.method_not_allowed.push((prefix, handler));
+        }
+
+        self.tree = OnceLock::new();
+        self
+    }
+
+    /// Like [`group`](Self::group), but also tags the group's routes in the
+    /// OpenAPI spec with the last static segment of the prefix.
+    ///
+    /// Routes that already have tags (from `#[tag("...")]` or a nested
+    /// tagged group) keep them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    ///
+    /// #[get("/")]
+    /// async fn list_users() -> &'static str { "users" }
+    ///
+    /// let router = Router::new()
+    ///     .group_tagged("/api/users", Router::new().get("/", list_users));
+    ///
+    /// assert_eq!(router.routes()[0].tags, vec!["users".to_string()]);
+    /// ```
+    pub fn group_tagged(self, prefix_pattern: &str, mut router: Router) -> Self {
+        let tag = prefix_pattern
+            .split('/')
+            .rev()
+            .find(|segment| !segment.is_empty() && !segment.starts_with([':', '*']));
+        if let Some(tag) = tag {
+            for (_, route) in &mut router.routes {
+                if route.tags.is_empty() {
+                    route.tags.push(tag.to_string());
+                }
+            }
+        }
+        self.group(prefix_pattern, router)
+    }
+
+    /// Compiles the routes into the matcher used by [`handle`](Self::handle).
+    ///
+    /// Called automatically by [`Rapina::listen`](crate::app::Rapina::listen).
+    /// Returns an error if a pattern is malformed, or if two routes with the
+    /// same method match the same paths, instead of letting the first one
+    /// silently win.
+    pub fn compile(&self) -> Result<(), RouterError> {
+        if self.tree.get().is_none() {
+            let tree = RouteTree::build(&self.routes)?;
+            let _ = self.tree.set(tree);
+        }
+        Ok(())
+    }
+
+    fn tree(&self) -> &RouteTree {
+        self.tree.get_or_init(|| {
+            RouteTree::build(&self.routes).unwrap_or_else(|e| panic!("invalid router: {}", e))
+        })
+    }
+
+    /// Returns the route the request would be dispatched to, if any.
+    pub(crate) fn matched_route(&self, method: &Method, path: &str) -> Option<MatchedRoute> {
+        match self.tree().find(method, path) {
+            Match::Found { index, .. } => Some(MatchedRoute {
+                public: self.routes[index].1.public,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Handles an incoming request by matching it to a route.
+    ///
+    /// HEAD requests without a HEAD route are answered by the GET route,
+    /// keeping its headers but dropping the body.
+    pub async fn handle(
+        &self,
+        req: Request<RequestBody>,
+        state: &Arc<AppState>,
+    ) -> Response<BoxBody> {
+        match self.tree().find(req.method(), req.uri().path()) {
+            Match::Found { index, params } => {
+                let (method, route) = &self.routes[index];
+                let strip_body = req.method() == Method::HEAD && method != Method::HEAD;
+                let response = if self.layers.is_empty() && route.middlewares.is_empty() {
+                    (route.handler)(req, params, state.clone()).await
+                } else {
+                    let chain: Vec<_> = self
+                        .layers
+                        .iter()
+                        .chain(&route.middlewares)
+                        .cloned()
+                        .collect();
+                    let ctx = req
+                        .extensions()
+                        .get::<RequestContext>()
+                        .cloned()
+                        .unwrap_or_default();
+                    // Boxed, since the chain's future type contains `handle`'s own
+                    Box::pin(
+                        Next::for_handler(&chain, &route.handler, params, state, &ctx).run(req),
+                    )
+                    .await
+                };
+                if strip_body {
+                    strip_response_body(response)
+                } else {
+                    response
+                }
+            }
+            Match::MethodNotAllowed { allowed } => {
+                let allow = allowed
+                    .iter()
+                    .map(Method::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mut response =
+                    match fallback::find(&self.method_not_allowed, req.uri().path(), |_| true) {
+                        Some((handler, params)) => handler(req, params, state.clone()).await,
+                        None => fallback::method_not_allowed(&req),
+                    };
+                if !response.headers().contains_key(header::ALLOW)
+                    && let Ok(value) = HeaderValue::from_str(&allow)
+                {
+                    response.headers_mut().insert(header::ALLOW, value);
+                }
+                response
+            }
+            Match::NotFound => {
+                let strip_body = req.method() == Method::HEAD;
+                let response =
+                    match fallback::find_not_found(&self.fallbacks, req.method(), req.uri().path())
+                    {
+                        Some((_, Fallback::Handler(handler), params)) => {
+                            handler(req, params, state.clone()).await
+                        }
+                        Some((_, Fallback::Spa(index), _)) => {
+                            fallback::spa_index(index, &req).await
+                        }
+                        Some((depth, Fallback::Mount(service), params)) => {
+                            let mut req = req;
+                            *req.uri_mut() = fallback::strip_prefix(req.uri(), depth);
+                            service(req, params, state.clone()).await
+                        }
+                        None => fallback::not_found(&req),
+                    };
+                if strip_body {
+                    strip_response_body(response)
+                } else {
+                    response
+                }
+            }
+        }
+    }
+
+    fn join_group_route_pattern(prefix: &str, route_path: &str) -> String {
+        let prefix = prefix.trim_end_matches('/');
+        let route_path = route_path.trim_start_matches('/');
+
+        if prefix.is_empty() {
+            format!("/{}", route_path)
+        } else if route_path.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{}/{}", prefix, route_path)
+        }
+    }
+}
+
+/// Boxes a [`Handler`] that isn't registered as a route.
+fn boxed<H: Handler>(handler: H) -> HandlerFn {
+    Box::new(move |req, params, state| handler.call(req, params, state))
+}
+
+/// Replaces the body with an empty one, keeping its length in `Content-Length`.
+fn strip_response_body(response: Response<BoxBody>) -> Response<BoxBody> {
+    use hyper::body::Body;
+
+    let (mut parts, body) = response.into_parts();
+    if !parts.headers.contains_key(header::CONTENT_LENGTH)
+        && let Some(length) = body.size_hint().exact()
+    {
+        parts
+            .headers
+            .insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
+    Response::from_parts(parts, crate::response::full(bytes::Bytes::new()))
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::StatusCode;
+
+    #[test]
+    fn test_router_new() {
+        let router = Router::new();
+        assert!(router.routes.is_empty());
+    }
+
+    #[test]
+    fn test_router_default() {
+        let router = Router::default();
+        assert!(router.routes.is_empty());
+    }
+
+    #[test]
+    fn test_router_add_get_route() {
+        let router = Router::new().route(Method::GET, "/users", |_req, _params, _state| async {
+            StatusCode::OK
+        });
+        assert_eq!(router.routes.len(), 1);
+        assert_eq!(router.routes[0].0, Method::GET);
+        assert_eq!(router.routes[0].1.pattern, "/users");
+    }
+
+    #[test]
+    fn test_router_add_post_route() {
+        let router = Router::new().route(Method::POST, "/users", |_req, _params, _state| async {
+            StatusCode::CREATED
+        });
+        assert_eq!(router.routes.len(), 1);
+        assert_eq!(router.routes[0].0, Method::POST);
+        assert_eq!(router.routes[0].1.pattern, "/users");
+    }
+
+    #[test]
+    fn test_router_add_custom_method_route() {
+        let router =
+            Router::new().route(Method::PUT, "/users/:id", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+        assert_eq!(router.routes.len(), 1);
+        assert_eq!(router.routes[0].0, Method::PUT);
+        assert_eq!(router.routes[0].1.pattern, "/users/:id");
+    }
+
+    #[test]
+    fn test_router_multiple_routes() {
+        let router = Router::new()
+            .route(Method::GET, "/users", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .route(Method::POST, "/users", |_req, _params, _state| async {
+                StatusCode::CREATED
+            })
+            .route(
+                Method::DELETE,
+                "/users/:id",
+                |_req, _params, _state| async { StatusCode::NO_CONTENT },
+            );
+
+        assert_eq!(router.routes.len(), 3);
+        assert_eq!(router.routes[0].0, Method::GET);
+        assert_eq!(router.routes[1].0, Method::POST);
+        assert_eq!(router.routes[2].0, Method::DELETE);
+    }
+
+    #[test]
+    fn test_router_chaining() {
+        let router = Router::new()
+            .route(Method::GET, "/", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/health", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        assert_eq!(router.routes.len(), 2);
+    }
+
+    #[test]
+    fn test_router_preserves_route_order() {
+        let router = Router::new()
+            .route(Method::GET, "/first", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/second", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/third", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        assert_eq!(router.routes[0].1.pattern, "/first");
+        assert_eq!(router.routes[1].1.pattern, "/second");
+        assert_eq!(router.routes[2].1.pattern, "/third");
+    }
+
+    #[test]
+    fn test_router_routes_introspection() {
+        let router = Router::new()
+            .get_named("/users", "list_users", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .post_named("/users", "create_user", |_req, _params, _state| async {
+                StatusCode::CREATED
+            });
+
+        let routes = router.routes();
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].method, "GET");
+        assert_eq!(routes[0].path, "/users");
+        assert_eq!(routes[0].handler_name, "list_users");
+        assert_eq!(routes[1].method, "POST");
+        assert_eq!(routes[1].path, "/users");
+        assert_eq!(routes[1].handler_name, "create_user");
+    }
+
+    #[test]
+    fn test_router_routes_default_handler_name() {
+        let router = Router::new().route(Method::GET, "/health", |_req, _params, _state| async {
+            StatusCode::OK
+        });
+
+        let routes = router.routes();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].handler_name, "handler");
+    }
+
+    #[test]
+    fn test_router_route_named() {
+        let router = Router::new().route_named(
+            Method::PUT,
+            "/users/:id",
+            "update_user",
+            None,
+            Vec::new(),
+            |_req, _params, _state| async { StatusCode::OK },
+        );
+
+        let routes = router.routes();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].method, "PUT");
+        assert_eq!(routes[0].path, "/users/:id");
+        assert_eq!(routes[0].handler_name, "update_user");
+    }
+
+    #[test]
+    fn test_router_get_named() {
+        let router =
+            Router::new().get_named("/items", "list_items", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let routes = router.routes();
+        assert_eq!(routes[0].method, "GET");
+        assert_eq!(routes[0].handler_name, "list_items");
+    }
+
+    #[test]
+    fn test_router_post_named() {
+        let router =
+            Router::new().post_named("/items", "create_item", |_req, _params, _state| async {
+                StatusCode::CREATED
+            });
+
+        let routes = router.routes();
+        assert_eq!(routes[0].method, "POST");
+        assert_eq!(routes[0].handler_name, "create_item");
+    }
+
+    #[test]
+    fn test_router_routes_empty() {
+        let router = Router::new();
+        assert!(router.routes().is_empty());
+    }
+
+    #[test]
+    fn test_router_routes_mixed_named_and_default() {
+        let router = Router::new()
+            .get_named("/named", "named_handler", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/default", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let routes = router.routes();
+        assert_eq!(routes[0].handler_name, "named_handler");
+        assert_eq!(routes[1].handler_name, "handler");
+    }
+
+    #[test]
+    fn test_join_group_route_pattern() {
+        assert_eq!(
+            Router::join_group_route_pattern("/api", "/users"),
+            "/api/users"
+        );
+        assert_eq!(
+            Router::join_group_route_pattern("/api/", "/users"),
+            "/api/users"
+        );
+        assert_eq!(
+            Router::join_group_route_pattern("/api", "users"),
+            "/api/users"
+        );
+        assert_eq!(
+            Router::join_group_route_pattern("/api/", "/users/"),
+            "/api/users/"
+        );
+        assert_eq!(Router::join_group_route_pattern("", "/users"), "/users");
+        assert_eq!(Router::join_group_route_pattern("/api", ""), "/api");
+    }
+
+    #[test]
+    fn test_router_compile_rejects_duplicates() {
+        let router = Router::new()
+            .get_named("/users", "list_users", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .group(
+                "/users",
+                Router::new().get_named("", "list_users_again", |_req, _params, _state| async {
+                    StatusCode::OK
+                }),
+            );
+
+        let err = router.compile().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Route GET /users conflicts with already registered route GET /users"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A group's prefix pattern must start with /")]
+    fn test_invalid_router_group_prefix_pattern() {
+        Router::new().group("api/users", Router::new());
+    }
+
+    #[test]
+    fn test_router_group() {
+        let users_router = Router::new()
+            .get_named("", "list_users", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .post_named("", "create_user", |_req, _params, _state| async {
+                StatusCode::CREATED
+            })
+            .get_named("/:id", "get_user", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let router = Router::new()
+            .get_named("/health", "health_check", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .group("/api/users", users_router);
+
+        let routes = router.routes();
+        assert_eq!(routes.len(), 4);
+        assert_eq!(routes[0].path, "/health");
+        assert_eq!(routes[1].path, "/api/users");
+        assert_eq!(routes[1].handler_name, "list_users");
+        assert_eq!(routes[2].path, "/api/users");
+        assert_eq!(routes[2].handler_name, "create_user");
+        assert_eq!(routes[3].path, "/api/users/:id");
+        assert_eq!(routes[3].handler_name, "get_user");
+    }
+
+    #[test]
+    fn test_multiple_router_groups() {
+        let users_router = Router::new()
+            .get_named("", "list_users", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .post_named("", "create_user", |_req, _params, _state| async {
+                StatusCode::CREATED
+            })
+            .get_named("/:id", "get_user", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let invoices_router = Router::new()
+            .get_named("", "list_invoices", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .get_named("/:id", "get_invoice", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let router = Router::new()
+            .get_named("/health", "health_check", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .group("/api/users", users_router)
+            .group("/api/invoices", invoices_router);
+
+        let routes = router.routes();
+        assert_eq!(routes.len(), 6);
+        assert_eq!(routes[0].path, "/health");
+        assert_eq!(routes[1].path, "/api/users");
+        assert_eq!(routes[1].handler_name, "list_users");
+        assert_eq!(routes[2].path, "/api/users");
+        assert_eq!(routes[2].handler_name, "create_user");
+        assert_eq!(routes[3].path, "/api/users/:id");
+        assert_eq!(routes[3].handler_name, "get_user");
+        assert_eq!(routes[4].path, "/api/invoices");
+        assert_eq!(routes[4].handler_name, "list_invoices");
+        assert_eq!(routes[5].path, "/api/invoices/:id");
+        assert_eq!(routes[5].handler_name, "get_invoice");
+    }
+
+    #[test]
+    fn test_router_group_tagged() {
+        let users_router = Router::new()
+            .get_named("", "list_users", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .get_named("/:id", "get_user", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+        let invoices_router =
+            Router::new().get_named("/:id", "get_invoice", |_req, _params, _state| async {
+                StatusCode::OK
+            });
+
+        let router = Router::new()
+            .get_named("/health", "health_check", |_req, _params, _state| async {
+                StatusCode::OK
+            })
+            .group_tagged("/api/v1/users/", users_router)
+            .group_tagged(
+                "/api",
+                Router::new().group_tagged("/invoices/:year", invoices_router),
+            );
+
+        let routes = router.routes();
+        assert!(routes[0].tags.is_empty());
+        assert_eq!(routes[1].tags, vec!["users"]);
+        assert_eq!(routes[2].tags, vec!["users"]);
+        assert_eq!(routes[3].path, "/api/invoices/:year/:id");
+        assert_eq!(routes[3].tags, vec!["invoices"]);
+    }
+}
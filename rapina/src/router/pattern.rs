@@ -0,0 +1,328 @@
+//! Route pattern syntax.
+//!
+//! A pattern is a `/`-separated list of segments:
+//!
+//! - `users` matches the segment literally.
+//! - `:id` matches any non-empty segment.
+//! - `:id<int>`, `:id<uuid>` or `:id<regex>` only match segments that
+//!   satisfy the constraint, e.g. `:code<[A-Z]{3}>`.
+//! - `:page?` is optional. Optional segments must come last.
+//! - `*path` matches the rest of the path, slashes included. It must be
+//!   the last segment and matches at least one character.
+
+use std::fmt;
+
+use regex::Regex;
+
+use crate::extract::PathParams;
+
+/// A constraint on the values a `:param` segment accepts.
+#[derive(Clone)]
+pub(crate) enum Constraint {
+    /// An optionally signed integer.
+    Int,
+    /// A hyphenated UUID.
+    Uuid,
+    /// A regular expression that must match the whole segment.
+    Regex { source: String, regex: Regex },
+}
+
+impl Constraint {
+    fn parse(source: &str) -> Result<Self, String> {
+        match source {
+            "int" => Ok(Constraint::Int),
+            "uuid" => Ok(Constraint::Uuid),
+            _ => Regex::new(&format!("^(?:{})$", source))
+                .map(|regex| Constraint::Regex {
+                    source: source.to_string(),
+                    regex,
+                })
+                .map_err(|e| format!("invalid constraint <{}>: {}", source, e)),
+        }
+    }
+
+    /// Returns the constraint as written in the pattern.
+    pub(crate) fn source(&self) -> &str {
+        match self {
+            Constraint::Int => "int",
+            Constraint::Uuid => "uuid",
+            Constraint::Regex { source, .. } => source,
+        }
+    }
+
+    /// Returns whether `value` satisfies the constraint.
+    pub(crate) fn matches(&self, value: &str) -> bool {
+        match self {
+            Constraint::Int => {
+                let digits = value.strip_prefix('-').unwrap_or(value);
+                !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+            }
+            Constraint::Uuid => uuid::Uuid::try_parse(value).is_ok() && value.len() == 36,
+            Constraint::Regex { regex, .. } => regex.is_match(value),
+        }
+    }
+
+    /// Returns the JSON Schema of the values the constraint accepts.
+    pub(crate) fn schema(&self) -> serde_json::Value {
+        match self {
+            Constraint::Int => serde_json::json!({ "type": "integer" }),
+            Constraint::Uuid => serde_json::json!({ "type": "string", "format": "uuid" }),
+            Constraint::Regex { source, .. } => {
+                serde_json::json!({ "type": "string", "pattern": format!("^(?:{})$", source) })
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.source())
+    }
+}
+
+impl PartialEq for Constraint {
+    fn eq(&self, other: &Self) -> bool {
+        self.source() == other.source()
+    }
+}
+
+/// A single segment of a route pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    Static(String),
+    Param {
+        name: String,
+        constraint: Option<Constraint>,
+        optional: bool,
+    },
+    CatchAll(String),
+}
+
+impl Segment {
+    fn parse(segment: &str) -> Result<Self, String> {
+        if let Some(name) = segment.strip_prefix('*') {
+            if name.is_empty() {
+                return Err("catch-all segments need a name, e.g. *path".to_string());
+            }
+            return Ok(Segment::CatchAll(name.to_string()));
+        }
+
+        let Some(param) = segment.strip_prefix(':') else {
+            return Ok(Segment::Static(segment.to_string()));
+        };
+        let (param, optional) = match param.strip_suffix('?') {
+            Some(param) => (param, true),
+            None => (param, false),
+        };
+        let (name, constraint) = match param.split_once('<') {
+            Some((name, rest)) => {
+                let source = rest
+                    .strip_suffix('>')
+                    .ok_or_else(|| format!("unclosed constraint in '{}'", segment))?;
+                (name, Some(Constraint::parse(source)?))
+            }
+            None => (param, None),
+        };
+        if name.is_empty() {
+            return Err(format!("parameter without a name in '{}'", segment));
+        }
+
+        Ok(Segment::Param {
+            name: name.to_string(),
+            constraint,
+            optional,
+        })
+    }
+
+    /// Returns the parameter name, if the segment captures one.
+    pub(crate) fn param_name(&self) -> Option<&str> {
+        match self {
+            Segment::Static(_) => None,
+            Segment::Param { name, .. } | Segment::CatchAll(name) => Some(name),
+        }
+    }
+
+    fn is_optional(&self) -> bool {
+        matches!(self, Segment::Param { optional: true, .. })
+    }
+}
+
+/// A parsed route pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Pattern {
+    segments: Vec<Segment>,
+}
+
+impl Pattern {
+    /// Parses a pattern, returning the reason it is invalid otherwise.
+    pub(crate) fn parse(pattern: &str) -> Result<Self, String> {
+        let segments = pattern
+            .split('/')
+            .map(Segment::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (i, segment) in segments.iter().enumerate() {
+            let is_last = i + 1 == segments.len();
+            if matches!(segment, Segment::CatchAll(_)) && !is_last {
+                return Err("a catch-all segment must be the last one".to_string());
+            }
+            if segment.is_optional() && !segments[i + 1..].iter().all(Segment::is_optional) {
+                return Err("optional segments must come after all other segments".to_string());
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Returns all segments, including optional ones.
+    pub(crate) fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns the concrete segment lists the pattern matches, from the
+    /// shortest (no optional segments) to the longest (all of them).
+    pub(crate) fn variants(&self) -> Vec<Vec<&Segment>> {
+        let required = self
+            .segments
+            .iter()
+            .take_while(|segment| !segment.is_optional())
+            .count();
+
+        (required..=self.segments.len())
+            .map(|len| {
+                let mut variant: Vec<&Segment> = self.segments[..len].iter().collect();
+                // Dropping every segment of `/:page?` leaves the root path
+                if variant.len() == 1 && self.segments.len() > 1 {
+                    variant.push(&ROOT);
+                }
+                variant
+            })
+            .collect()
+    }
+
+    /// Matches a request path, returning the captured parameters.
+    pub(crate) fn matches(&self, path: &str) -> Option<PathParams> {
+        let parts: Vec<&str> = path.split('/').collect();
+        self.variants()
+            .into_iter()
+            .rev()
+            .find_map(|variant| match_segments(&variant, &parts))
+    }
+}
+
+static ROOT: Segment = Segment::Static(String::new());
+
+fn match_segments(segments: &[&Segment], parts: &[&str]) -> Option<PathParams> {
+    let mut params = PathParams::new();
+    for (i, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::CatchAll(name) => {
+                let rest = parts.get(i..)?.join("/");
+                if rest.is_empty() {
+                    return None;
+                }
+                params.insert(name.as_str(), rest);
+                return Some(params);
+            }
+            Segment::Static(value) => {
+                if parts.get(i)? != value {
+                    return None;
+                }
+            }
+            Segment::Param {
+                name, constraint, ..
+            } => {
+                let part = parts.get(i)?;
+                if part.is_empty() || constraint.as_ref().is_some_and(|c| !c.matches(part)) {
+                    return None;
+                }
+                params.insert(name.as_str(), *part);
+            }
+        }
+    }
+    (segments.len() == parts.len()).then_some(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> Option<Vec<(String, String)>> {
+        Pattern::parse(pattern)
+            .unwrap()
+            .matches(path)
+            .map(|params| params.into_iter().collect())
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Option<Vec<(String, String)>> {
+        Some(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let pattern = Pattern::parse("/files/:id<int>/:name?").unwrap();
+        assert_eq!(pattern.segments()[1], Segment::Static("files".to_string()));
+        assert_eq!(
+            pattern.segments()[2],
+            Segment::Param {
+                name: "id".to_string(),
+                constraint: Some(Constraint::Int),
+                optional: false
+            }
+        );
+        assert!(pattern.segments()[3].is_optional());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_patterns() {
+        assert!(Pattern::parse("/files/*path/more").is_err());
+        assert!(Pattern::parse("/posts/:page?/comments").is_err());
+        assert!(Pattern::parse("/users/:id<[0-9>").is_err());
+        assert!(Pattern::parse("/users/:id<int").is_err());
+        assert!(Pattern::parse("/users/:").is_err());
+        assert!(Pattern::parse("/files/*").is_err());
+    }
+
+    #[test]
+    fn test_catch_all() {
+        assert_eq!(
+            matches("/files/*path", "/files/a/b.txt"),
+            pairs(&[("path", "a/b.txt")])
+        );
+        assert_eq!(matches("/files/*path", "/files/"), None);
+        assert_eq!(matches("/files/*path", "/files"), None);
+    }
+
+    #[test]
+    fn test_optional_segments() {
+        assert_eq!(matches("/posts/:page?", "/posts"), pairs(&[]));
+        assert_eq!(
+            matches("/posts/:page?", "/posts/2"),
+            pairs(&[("page", "2")])
+        );
+        assert_eq!(matches("/:lang?", "/"), pairs(&[]));
+        assert_eq!(matches("/:lang?", "/en"), pairs(&[("lang", "en")]));
+    }
+
+    #[test]
+    fn test_constraints() {
+        assert!(matches("/users/:id<int>", "/users/42").is_some());
+        assert!(matches("/users/:id<int>", "/users/-42").is_some());
+        assert!(matches("/users/:id<int>", "/users/me").is_none());
+        assert!(
+            matches(
+                "/items/:id<uuid>",
+                "/items/67e55044-10b1-426f-9247-bb680e5fe0c8"
+            )
+            .is_some()
+        );
+        assert!(matches("/items/:id<uuid>", "/items/67e55044").is_none());
+        assert!(matches("/codes/:code<[A-Z]{3}>", "/codes/ABC").is_some());
+        assert!(matches("/codes/:code<[A-Z]{3}>", "/codes/ABCD").is_none());
+    }
+}
@@ -0,0 +1,530 @@
+//! Compiled route matcher.
+//!
+//! Routes are inserted segment by segment into a prefix tree. Lookups walk
+//! the tree once per request instead of testing every registered pattern.
+//! At each level, static segments are tried first, then constrained
+//! `:param<...>` segments, then plain `:param` segments and finally
+//! `*catch_all` segments.
+
+use std::collections::HashMap;
+
+use http::Method;
+
+use crate::extract::PathParams;
+
+use super::pattern::{Constraint, Pattern, Segment};
+use super::{Route, RouterError};
+
+/// The outcome of looking up a request in the [`RouteTree`].
+#[derive(Debug, PartialEq)]
+pub(crate) enum Match {
+    /// A route matched both the path and the method.
+    Found { index: usize, params: PathParams },
+    /// The path matched at least one route, but none for this method.
+    MethodNotAllowed { allowed: Vec<Method> },
+    /// No route matched the path.
+    NotFound,
+}
+
+#[derive(Default)]
+struct Node {
+    statics: HashMap<String, Node>,
+    params: Vec<ParamNode>,
+    catch_alls: Vec<(String, Node)>,
+    endpoints: Vec<(Method, usize)>,
+}
+
+struct ParamNode {
+    name: String,
+    constraint: Option<Constraint>,
+    node: Node,
+}
+
+/// A prefix tree of route patterns, built once before serving.
+#[derive(Default)]
+pub(crate) struct RouteTree {
+    root: Node,
+}
+
+impl RouteTree {
+    /// Builds a tree from the router's routes.
+    ///
+    /// Leaves store indices into `routes`, so the tree must be rebuilt
+    /// whenever the route list changes. A pattern with optional segments
+    /// is inserted once per variant, all pointing at the same route.
+    pub(crate) fn build(routes: &[(Method, Route)]) -> Result<Self, RouterError> {
+        Self::from_patterns(
+            routes
+                .iter()
+                .map(|(method, route)| (method, route.pattern.as_str())),
+        )
+    }
+
+    /// Builds a tree from `(method, pattern)` pairs, with leaves storing
+    /// their position in the sequence.
+    pub(crate) fn from_patterns<'r>(
+        routes: impl IntoIterator<Item = (&'r Method, &'r str)>,
+    ) -> Result<Self, RouterError> {
+        let mut tree = RouteTree::default();
+        // Two patterns that only differ by parameter names (e.g. `/users/:id`
+        // and `/users/:user_id`) end up in different branches, so duplicates
+        // are tracked by their normalized shape.
+        let mut shapes: HashMap<(Method, String), &str> = HashMap::new();
+
+        for (index, (method, source)) in routes.into_iter().enumerate() {
+            let pattern = Pattern::parse(source).map_err(|reason| RouterError::InvalidPattern {
+                pattern: source.to_string(),
+                reason,
+            })?;
+
+            for variant in pattern.variants() {
+                let shape = normalize(&variant);
+                if let Some(existing) = shapes.insert((method.clone(), shape), source) {
+                    return Err(RouterError::Duplicate {
+                        method: method.to_string(),
+                        existing: existing.to_string(),
+                        pattern: source.to_string(),
+                    });
+                }
+
+                let mut node = &mut tree.root;
+                for segment in variant {
+                    node = node.child(segment);
+                }
+                node.endpoints.push((method.clone(), index));
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// Finds the route for the given method and path.
+    pub(crate) fn find(&self, method: &Method, path: &str) -> Match {
+        let segments: Vec<&str> = path.split('/').collect();
+        let mut captured = Vec::new();
+        let mut allowed = Vec::new();
+
+        match self
+            .root
+            .lookup(&segments, method, &mut captured, &mut allowed)
+        {
+            Some(index) => Match::Found {
+                index,
+                params: captured
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+            },
+            None if !allowed.is_empty() => Match::MethodNotAllowed { allowed },
+            None => Match::NotFound,
+        }
+    }
+}
+
+impl Node {
+    fn child(&mut self, segment: &Segment) -> &mut Node {
+        match segment {
+            Segment::Static(value) => self.statics.entry(value.clone()).or_default(),
+            Segment::Param {
+                name, constraint, ..
+            } => {
+                let position = match self
+                    .params
+                    .iter()
+                    .position(|p| &p.name == name && &p.constraint == constraint)
+                {
+                    Some(position) => position,
+                    None => {
+                        // Constrained parameters are tried before plain ones
+                        let position = if constraint.is_some() {
+                            self.params
+                                .iter()
+                                .position(|p| p.constraint.is_none())
+                                .unwrap_or(self.params.len())
+                        } else {
+                            self.params.len()
+                        };
+                        self.params.insert(
+                            position,
+                            ParamNode {
+                                name: name.clone(),
+                                constraint: constraint.clone(),
+                                node: Node::default(),
+                            },
+                        );
+                        position
+                    }
+                };
+                &mut self.params[position].node
+            }
+            Segment::CatchAll(name) => {
+                let position = match self.catch_alls.iter().position(|(n, _)| n == name) {
+                    Some(position) => position,
+                    None => {
+                        self.catch_alls.push((name.clone(), Node::default()));
+                        self.catch_alls.len() - 1
+                    }
+                };
+                &mut self.catch_alls[position].1
+            }
+        }
+    }
+
+    fn lookup<'t>(
+        &'t self,
+        segments: &[&str],
+        method: &Method,
+        captured: &mut Vec<(&'t str, String)>,
+        allowed: &mut Vec<Method>,
+    ) -> Option<usize> {
+        let Some((segment, rest)) = segments.split_first() else {
+            if let Some((_, index)) = self.endpoints.iter().find(|(m, _)| m == method) {
+                return Some(*index);
+            }
+            // GET routes also answer HEAD requests
+            if method == Method::HEAD
+                && let Some((_, index)) = self.endpoints.iter().find(|(m, _)| m == Method::GET)
+            {
+                return Some(*index);
+            }
+            for (m, _) in &self.endpoints {
+                if !allowed.contains(m) {
+                    allowed.push(m.clone());
+                }
+                if m == Method::GET && !allowed.contains(&Method::HEAD) {
+                    allowed.push(Method::HEAD);
+                }
+            }
+            return None;
+        };
+
+        if let Some(child) = self.statics.get(*segment)
+            && let Some(index) = child.lookup(rest, method, captured, allowed)
+        {
+            return Some(index);
+        }
+
+        if !segment.is_empty() {
+            for param in &self.params {
+                if let Some(constraint) = &param.constraint
+                    && !constraint.matches(segment)
+                {
+                    continue;
+                }
+                captured.push((&param.name, segment.to_string()));
+                if let Some(index) = param.node.lookup(rest, method, captured, allowed) {
+                    return Some(index);
+                }
+                captured.pop();
+            }
+        }
+
+        if !self.catch_alls.is_empty() {
+            let remainder = segments.join("/");
+            if remainder.is_empty() {
+                return None;
+            }
+            for (name, child) in &self.catch_alls {
+                captured.push((name, remainder.clone()));
+                if let Some(index) = child.lookup(&[], method, captured, allowed) {
+                    return Some(index);
+                }
+                captured.pop();
+            }
+        }
+
+        None
+    }
+}
+
+/// Replaces parameter names with a placeholder so equivalent patterns compare equal.
+fn normalize(segments: &[&Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Static(value) => value.clone(),
+            Segment::Param {
+                constraint: Some(constraint),
+                ..
+            } => format!(":<{}>", constraint.source()),
+            Segment::Param { .. } => ":".to_string(),
+            Segment::CatchAll(_) => "*".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::Router;
+    use http::StatusCode;
+
+    fn tree(router: &Router) -> RouteTree {
+        RouteTree::build(&router.routes).unwrap()
+    }
+
+    fn found(m: Match) -> (usize, PathParams) {
+        match m {
+            Match::Found { index, params } => (index, params),
+            other => panic!("expected a match, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_static_route() {
+        let router = Router::new()
+            .route(Method::GET, "/", |_, _, _| async { StatusCode::OK })
+            .route(Method::GET, "/health", |_, _, _| async { StatusCode::OK });
+        let tree = tree(&router);
+
+        assert_eq!(found(tree.find(&Method::GET, "/")).0, 0);
+        assert_eq!(found(tree.find(&Method::GET, "/health")).0, 1);
+        assert_eq!(tree.find(&Method::GET, "/missing"), Match::NotFound);
+    }
+
+    #[test]
+    fn test_param_route() {
+        let router = Router::new().route(
+            Method::GET,
+            "/users/:user_id/posts/:post_id",
+            |_, _, _| async { StatusCode::OK },
+        );
+        let (_, params) = found(tree(&router).find(&Method::GET, "/users/1/posts/42"));
+
+        assert_eq!(params.get("user_id"), Some(&"1".to_string()));
+        assert_eq!(params.get("post_id"), Some(&"42".to_string()));
+    }
+
+    #[test]
+    fn test_static_ranked_above_param() {
+        let router = Router::new()
+            .route(Method::GET, "/users/:id", |_, _, _| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/users/me", |_, _, _| async { StatusCode::OK });
+        let tree = tree(&router);
+
+        let (index, params) = found(tree.find(&Method::GET, "/users/me"));
+        assert_eq!(index, 1);
+        assert!(params.is_empty());
+
+        let (index, params) = found(tree.find(&Method::GET, "/users/42"));
+        assert_eq!(index, 0);
+        assert_eq!(params.get("id"), Some(&"42".to_string()));
+    }
+
+    #[test]
+    fn test_backtracks_from_static_to_param() {
+        let router = Router::new()
+            .route(Method::GET, "/users/me/profile", |_, _, _| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/users/:id/posts", |_, _, _| async {
+                StatusCode::OK
+            });
+
+        let (index, params) = found(tree(&router).find(&Method::GET, "/users/me/posts"));
+        assert_eq!(index, 1);
+        assert_eq!(params.get("id"), Some(&"me".to_string()));
+    }
+
+    #[test]
+    fn test_method_not_allowed_lists_methods() {
+        let router = Router::new()
+            .route(Method::GET, "/users", |_, _, _| async { StatusCode::OK })
+            .route(Method::POST, "/users", |_, _, _| async { StatusCode::OK });
+
+        assert_eq!(
+            tree(&router).find(&Method::DELETE, "/users"),
+            Match::MethodNotAllowed {
+                allowed: vec![Method::GET, Method::HEAD, Method::POST]
+            }
+        );
+    }
+
+    #[test]
+    fn test_head_falls_back_to_get() {
+        let router = Router::new()
+            .route(Method::GET, "/users", |_, _, _| async { StatusCode::OK })
+            .route(Method::GET, "/items", |_, _, _| async { StatusCode::OK })
+            .route(Method::HEAD, "/items", |_, _, _| async { StatusCode::OK });
+        let tree = tree(&router);
+
+        let (index, _) = found(tree.find(&Method::HEAD, "/users"));
+        assert_eq!(index, 0);
+        let (index, _) = found(tree.find(&Method::HEAD, "/items"));
+        assert_eq!(index, 2);
+    }
+
+    #[test]
+    fn test_method_falls_through_to_param_route() {
+        let router = Router::new()
+            .route(Method::GET, "/users/me", |_, _, _| async { StatusCode::OK })
+            .route(Method::DELETE, "/users/:id", |_, _, _| async {
+                StatusCode::OK
+            });
+
+        let (index, _) = found(tree(&router).find(&Method::DELETE, "/users/me"));
+        assert_eq!(index, 1);
+    }
+
+    #[test]
+    fn test_trailing_slash_is_distinct() {
+        let router = Router::new().route(Method::GET, "/users", |_, _, _| async { StatusCode::OK });
+
+        assert_eq!(tree(&router).find(&Method::GET, "/users/"), Match::NotFound);
+    }
+
+    #[test]
+    fn test_param_does_not_match_empty_segment() {
+        let router = Router::new().route(Method::GET, "/users/:id", |_, _, _| async {
+            StatusCode::OK
+        });
+
+        assert_eq!(tree(&router).find(&Method::GET, "/users/"), Match::NotFound);
+    }
+
+    #[test]
+    fn test_duplicate_route_is_rejected() {
+        let router = Router::new()
+            .route(Method::GET, "/users/:id", |_, _, _| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/users/:user_id", |_, _, _| async {
+                StatusCode::OK
+            });
+
+        let err = RouteTree::build(&router.routes).err().unwrap();
+        assert_eq!(
+            err,
+            RouterError::Duplicate {
+                method: "GET".to_string(),
+                existing: "/users/:id".to_string(),
+                pattern: "/users/:user_id".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_same_pattern_different_methods_is_allowed() {
+        let router = Router::new()
+            .route(Method::GET, "/users/:id", |_, _, _| async {
+                StatusCode::OK
+            })
+            .route(Method::PUT, "/users/:id", |_, _, _| async {
+                StatusCode::OK
+            });
+
+        assert!(RouteTree::build(&router.routes).is_ok());
+    }
+
+    #[test]
+    fn test_constrained_param_ranked_above_plain_param() {
+        let router = Router::new()
+            .route(Method::GET, "/users/:slug", |_, _, _| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/users/:id<int>", |_, _, _| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/users/me", |_, _, _| async { StatusCode::OK });
+        let tree = tree(&router);
+
+        let (index, params) = found(tree.find(&Method::GET, "/users/42"));
+        assert_eq!(index, 1);
+        assert_eq!(params.get("id"), Some(&"42".to_string()));
+
+        let (index, params) = found(tree.find(&Method::GET, "/users/alice"));
+        assert_eq!(index, 0);
+        assert_eq!(params.get("slug"), Some(&"alice".to_string()));
+
+        assert_eq!(found(tree.find(&Method::GET, "/users/me")).0, 2);
+    }
+
+    #[test]
+    fn test_constraint_rejects_value() {
+        let router = Router::new().route(Method::GET, "/users/:id<int>", |_, _, _| async {
+            StatusCode::OK
+        });
+
+        assert_eq!(
+            tree(&router).find(&Method::GET, "/users/abc"),
+            Match::NotFound
+        );
+    }
+
+    #[test]
+    fn test_catch_all_captures_rest_of_path() {
+        let router = Router::new()
+            .route(Method::GET, "/files/*path", |_, _, _| async {
+                StatusCode::OK
+            })
+            .route(Method::GET, "/files/readme", |_, _, _| async {
+                StatusCode::OK
+            });
+        let tree = tree(&router);
+
+        let (index, params) = found(tree.find(&Method::GET, "/files/docs/a/b.txt"));
+        assert_eq!(index, 0);
+        assert_eq!(params.get("path"), Some(&"docs/a/b.txt".to_string()));
+
+        assert_eq!(found(tree.find(&Method::GET, "/files/readme")).0, 1);
+        assert_eq!(tree.find(&Method::GET, "/files/"), Match::NotFound);
+        assert_eq!(tree.find(&Method::GET, "/files"), Match::NotFound);
+    }
+
+    #[test]
+    fn test_catch_all_reports_allowed_methods() {
+        let router = Router::new().route(Method::POST, "/files/*path", |_, _, _| async {
+            StatusCode::OK
+        });
+
+        assert_eq!(
+            tree(&router).find(&Method::GET, "/files/a/b"),
+            Match::MethodNotAllowed {
+                allowed: vec![Method::POST]
+            }
+        );
+    }
+
+    #[test]
+    fn test_optional_segment() {
+        let router = Router::new().route(Method::GET, "/posts/:page?", |_, _, _| async {
+            StatusCode::OK
+        });
+        let tree = tree(&router);
+
+        let (index, params) = found(tree.find(&Method::GET, "/posts"));
+        assert_eq!(index, 0);
+        assert!(params.is_empty());
+
+        let (index, params) = found(tree.find(&Method::GET, "/posts/3"));
+        assert_eq!(index, 0);
+        assert_eq!(params.get("page"), Some(&"3".to_string()));
+    }
+
+    #[test]
+    fn test_optional_segment_conflicts_with_shorter_route() {
+        let router = Router::new()
+            .route(Method::GET, "/posts", |_, _, _| async { StatusCode::OK })
+            .route(Method::GET, "/posts/:page?", |_, _, _| async {
+                StatusCode::OK
+            });
+
+        assert!(matches!(
+            RouteTree::build(&router.routes),
+            Err(RouterError::Duplicate { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        let router = Router::new().route(Method::GET, "/files/*path/raw", |_, _, _| async {
+            StatusCode::OK
+        });
+
+        let err = RouteTree::build(&router.routes).err().unwrap();
+        assert!(matches!(err, RouterError::InvalidPattern { .. }));
+    }
+}
@@ -0,0 +1,318 @@
+//! Reverse routing: building paths to routes from their handler names.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+
+use crate::error::{Error, IntoApiError};
+
+use super::Router;
+use super::pattern::{Pattern, Segment};
+
+/// Characters left as-is in a path segment: RFC 3986 unreserved characters.
+pub(crate) const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Errors returned when building a URL to a named route.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UrlForError {
+    /// No route is registered with this handler name.
+    UnknownRoute(String),
+    /// A required parameter of the route's pattern wasn't given.
+    MissingParam { route: String, param: String },
+    /// A value is empty, is a `.` or `..` segment, or doesn't satisfy the
+    /// segment's constraint.
+    InvalidParam {
+        route: String,
+        param: String,
+        value: String,
+    },
+    /// A parameter isn't part of the route's pattern.
+    UnexpectedParam { route: String, param: String },
+}
+
+impl fmt::Display for UrlForError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlForError::UnknownRoute(route) => write!(f, "No route named '{}'", route),
+            UrlForError::MissingParam { route, param } => {
+                write!(f, "Route '{}' requires parameter '{}'", route, param)
+            }
+            UrlForError::InvalidParam {
+                route,
+                param,
+                value,
+            } => write!(
+                f,
+                "Invalid value '{}' for parameter '{}' of route '{}'",
+                value, param, route
+            ),
+            UrlForError::UnexpectedParam { route, param } => {
+                write!(f, "Route '{}' has no parameter '{}'", route, param)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UrlForError {}
+
+impl IntoApiError for UrlForError {
+    fn into_api_error(self) -> Error {
+        Error::internal(self.to_string())
+    }
+}
+
+/// Route patterns by handler name, shared with the [`UrlFor`](crate::extract::UrlFor)
+/// extractor through the application state.
+#[derive(Clone, Default)]
+pub(crate) struct RouteUrls(Arc<HashMap<String, String>>);
+
+impl RouteUrls {
+    /// Collects the patterns of a router. When several routes share a
+    /// handler name, the first registered one wins, like in
+    /// [`Router::url_for`].
+    pub(crate) fn new(router: &Router) -> Self {
+        let mut patterns = HashMap::new();
+        for (_, route) in &router.routes {
+            patterns
+                .entry(route.handler_name.clone())
+                .or_insert_with(|| route.pattern.clone());
+        }
+        Self(Arc::new(patterns))
+    }
+
+    pub(crate) fn path_for(
+        &self,
+        name: &str,
+        params: &[(&str, &dyn fmt::Display)],
+    ) -> Result<String, UrlForError> {
+        let pattern = self
+            .0
+            .get(name)
+            .ok_or_else(|| UrlForError::UnknownRoute(name.to_string()))?;
+        build_path(name, pattern, params)
+    }
+}
+
+/// Fills the parameters of `pattern`, percent-encoding their values.
+///
+/// Optional segments are left out from the first one without a value.
+pub(crate) fn build_path(
+    name: &str,
+    pattern: &str,
+    params: &[(&str, &dyn fmt::Display)],
+) -> Result<String, UrlForError> {
+    let pattern =
+        Pattern::parse(pattern).map_err(|_| UrlForError::UnknownRoute(name.to_string()))?;
+    let values: Vec<(&str, String)> = params
+        .iter()
+        .map(|(param, value)| (*param, value.to_string()))
+        .collect();
+    let mut used = vec![false; values.len()];
+    let mut segments = Vec::new();
+
+    for segment in pattern.segments() {
+        let Some(param) = segment.param_name() else {
+            if let Segment::Static(value) = segment {
+                segments.push(value.clone());
+            }
+            continue;
+        };
+        let Some(position) = values.iter().position(|(name, _)| *name == param) else {
+            if matches!(segment, Segment::Param { optional: true, .. }) {
+                break;
+            }
+            return Err(UrlForError::MissingParam {
+                route: name.to_string(),
+                param: param.to_string(),
+            });
+        };
+        used[position] = true;
+        let value = &values[position].1;
+
+        let valid = !value.is_empty()
+            && !has_dot_segment(segment, value)
+            && match segment {
+                Segment::Param {
+                    constraint: Some(constraint),
+                    ..
+                } => constraint.matches(value),
+                _ => true,
+            };
+        if !valid {
+            return Err(UrlForError::InvalidParam {
+                route: name.to_string(),
+                param: param.to_string(),
+                value: value.clone(),
+            });
+        }
+        segments.push(encode(segment, value));
+    }
+
+    if let Some(position) = used.iter().position(|used| !used) {
+        return Err(UrlForError::UnexpectedParam {
+            route: name.to_string(),
+            param: values[position].0.to_string(),
+        });
+    }
+
+    Ok(join(segments))
+}
+
+/// Builds a path from positional values, for the `path` helpers generated
+/// by the route macros. Values aren't checked against constraints, and the
+/// path stops before the first omitted optional segment.
+///
+/// # Panics
+///
+/// Panics if a value is a `.` or `..` segment, which would lead to another
+/// route.
+#[doc(hidden)]
+pub fn typed_path(source: &str, values: &[Option<String>]) -> String {
+    let Ok(pattern) = Pattern::parse(source) else {
+        return source.to_string();
+    };
+    let mut values = values.iter();
+    let mut segments = Vec::new();
+
+    for segment in pattern.segments() {
+        match segment {
+            Segment::Static(value) => segments.push(value.clone()),
+            _ => match values.next() {
+                Some(Some(value)) => {
+                    if has_dot_segment(segment, value) {
+                        panic!(
+                            "`{}` can't be used as the `{}` parameter of `{}`: clients resolve \
+                             `.` and `..` segments instead of sending them",
+                            value,
+                            segment.param_name().unwrap_or_default(),
+                            source,
+                        );
+                    }
+                    segments.push(encode(segment, value))
+                }
+                _ => break,
+            },
+        }
+    }
+
+    join(segments)
+}
+
+/// Whether the value would put a `.` or `..` segment in the path, which
+/// clients resolve instead of sending. Percent-encoding doesn't help, as
+/// `%2E%2E` is resolved too.
+fn has_dot_segment(segment: &Segment, value: &str) -> bool {
+    let is_dots = |part: &str| part == "." || part == "..";
+    match segment {
+        Segment::CatchAll(_) => value.split('/').any(is_dots),
+        _ => is_dots(value),
+    }
+}
+
+/// Percent-encodes a value, keeping the slashes of catch-all values.
+fn encode(segment: &Segment, value: &str) -> String {
+    match segment {
+        Segment::CatchAll(_) => value
+            .split('/')
+            .map(|part| utf8_percent_encode(part, SEGMENT).to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+        _ => utf8_percent_encode(value, SEGMENT).to_string(),
+    }
+}
+
+fn join(segments: Vec<String>) -> String {
+    let path = segments.join("/");
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_path_fills_and_encodes_params() {
+        let path = build_path(
+            "get_file",
+            "/users/:id<int>/files/*path",
+            &[("id", &42), ("path", &"my docs/a&b.txt")],
+        )
+        .unwrap();
+        assert_eq!(path, "/users/42/files/my%20docs/a%26b.txt");
+    }
+
+    #[test]
+    fn test_build_path_rejects_dot_segments() {
+        for value in [".", ".."] {
+            assert_eq!(
+                build_path("get_user", "/users/:id", &[("id", &value)]),
+                Err(UrlForError::InvalidParam {
+                    route: "get_user".to_string(),
+                    param: "id".to_string(),
+                    value: value.to_string(),
+                })
+            );
+        }
+        assert!(matches!(
+            build_path("get_file", "/files/*path", &[("path", &"a/../../admin")]),
+            Err(UrlForError::InvalidParam { .. })
+        ));
+        assert_eq!(
+            build_path("get_file", "/files/*path", &[("path", &"a/..b/.c")]).unwrap(),
+            "/files/a/..b/.c"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "`..` can't be used as the `id` parameter of `/users/:id/posts`")]
+    fn test_typed_path_rejects_dot_segments() {
+        typed_path("/users/:id/posts", &[Some("..".to_string())]);
+    }
+
+    #[test]
+    fn test_build_path_optional_segments() {
+        assert_eq!(build_path("posts", "/posts/:page?", &[]).unwrap(), "/posts");
+        assert_eq!(
+            build_path("posts", "/posts/:page?", &[("page", &2)]).unwrap(),
+            "/posts/2"
+        );
+        assert_eq!(build_path("home", "/:lang?", &[]).unwrap(), "/");
+    }
+
+    #[test]
+    fn test_build_path_errors() {
+        assert_eq!(
+            build_path("get_user", "/users/:id", &[]),
+            Err(UrlForError::MissingParam {
+                route: "get_user".to_string(),
+                param: "id".to_string()
+            })
+        );
+        assert!(matches!(
+            build_path("get_user", "/users/:id<int>", &[("id", &"me")]),
+            Err(UrlForError::InvalidParam { .. })
+        ));
+        assert!(matches!(
+            build_path("get_user", "/users/:id", &[("id", &1), ("slug", &"x")]),
+            Err(UrlForError::UnexpectedParam { .. })
+        ));
+    }
+
+    #[test]
+    fn test_typed_path() {
+        assert_eq!(
+            typed_path("/orgs/:org/posts/:page?", &[Some("a b".to_string()), None]),
+            "/orgs/a%20b/posts"
+        );
+    }
+}
@@ -1,57 +1,418 @@
+use std::future::Future;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use hyper::Request;
 use hyper::body::Incoming;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
-use hyper_util::rt::TokioIo;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
 
-use crate::context::RequestContext;
 use crate::middleware::MiddlewareStack;
 use crate::router::Router;
+use crate::service::{RapinaService, box_request};
 use crate::state::AppState;
 
+/// How long to stop accepting connections after an accept error.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Which HTTP versions the server accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    /// Detects HTTP/1.1 or HTTP/2 from the first bytes of each connection.
+    ///
+    /// HTTP/2 over plain TCP (h2c) works with prior knowledge: clients that
+    /// start the connection with the HTTP/2 preface are served over HTTP/2.
+    #[default]
+    Auto,
+    /// Only HTTP/1.1.
+    Http1,
+    /// Only HTTP/2 (h2c with prior knowledge when not behind TLS).
+    Http2,
+}
+
+/// Connection-level settings for the HTTP server.
+///
+/// # Examples
+///
+/// ```
+/// use rapina::server::{Protocol, ServerConfig};
+/// use std::time::Duration;
+///
+/// let config = ServerConfig::new()
+///     .protocol(Protocol::Auto)
+///     .http2_max_concurrent_streams(250)
+///     .http2_keep_alive(Duration::from_secs(20), Duration::from_secs(10))
+///     .max_headers(64);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    /// The HTTP versions to accept.
+    pub protocol: Protocol,
+    /// Maximum number of concurrent HTTP/2 streams per connection.
+    pub http2_max_concurrent_streams: Option<u32>,
+    /// Interval between HTTP/2 keep-alive pings. Pings are disabled when `None`.
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// How long to wait for a keep-alive ping to be acknowledged before
+    /// closing the connection.
+    pub http2_keep_alive_timeout: Option<Duration>,
+    /// Maximum number of headers in an HTTP/1.1 request.
+    pub max_headers: Option<usize>,
+    /// Maximum size, in bytes, of the header list of an HTTP/2 request.
+    pub http2_max_header_list_size: Option<u32>,
+    /// Terminates TLS on accepted connections when set.
+    #[cfg(feature = "tls")]
+    pub tls: Option<crate::tls::TlsConfig>,
+}
+
+impl ServerConfig {
+    /// Creates a config with hyper's defaults, accepting HTTP/1.1 and HTTP/2.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the HTTP versions to accept.
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Sets the maximum number of concurrent HTTP/2 streams per connection.
+    pub fn http2_max_concurrent_streams(mut self, max: u32) -> Self {
+        self.http2_max_concurrent_streams = Some(max);
+        self
+    }
+
+    /// Enables HTTP/2 keep-alive pings every `interval`, closing the
+    /// connection if a ping isn't acknowledged within `timeout`.
+    pub fn http2_keep_alive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self.http2_keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of headers in an HTTP/1.1 request.
+    pub fn max_headers(mut self, max: usize) -> Self {
+        self.max_headers = Some(max);
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of an HTTP/2 header list.
+    pub fn http2_max_header_list_size(mut self, max: u32) -> Self {
+        self.http2_max_header_list_size = Some(max);
+        self
+    }
+
+    /// Serves HTTPS with the given certificate settings.
+    ///
+    /// ALPN advertises `h2` and `http/1.1` according to [`protocol`](Self::protocol).
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, tls: crate::tls::TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Returns the URL scheme clients use to reach the server.
+    pub(crate) fn scheme(&self) -> &'static str {
+        #[cfg(feature = "tls")]
+        if self.tls.is_some() {
+            return "https";
+        }
+        "http"
+    }
+
+    fn builders(&self) -> Builders {
+        let mut http1 = http1::Builder::new();
+        let mut auto = auto::Builder::new(TokioExecutor::new());
+
+        if let Some(max) = self.max_headers {
+            http1.max_headers(max);
+            auto.http1().max_headers(max);
+        }
+
+        let mut http2 = auto.http2();
+        http2.timer(TokioTimer::new());
+        if let Some(max) = self.http2_max_concurrent_streams {
+            http2.max_concurrent_streams(max);
+        }
+        if let Some(interval) = self.http2_keep_alive_interval {
+            http2.keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.http2_keep_alive_timeout {
+            http2.keep_alive_timeout(timeout);
+        }
+        if let Some(max) = self.http2_max_header_list_size {
+            http2.max_header_list_size(max);
+        }
+
+        if self.protocol == Protocol::Http2 {
+            auto = auto.http2_only();
+        }
+
+        Builders {
+            protocol: self.protocol,
+            http1,
+            auto,
+        }
+    }
+}
+
+/// Connection builders derived from a [`ServerConfig`].
+///
+/// hyper-util's auto builder only honors `http2_only` when serving without
+/// upgrades, and has no way to serve HTTP/1.1 only with upgrades, so
+/// HTTP/1.1-only connections go through hyper's own builder instead.
+struct Builders {
+    protocol: Protocol,
+    http1: http1::Builder,
+    auto: auto::Builder<TokioExecutor>,
+}
+
+/// A callback run once the server has stopped serving requests.
+pub type ShutdownHook = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// Controls how the server stops once the shutdown signal fires.
+pub struct ShutdownConfig {
+    /// How long in-flight requests are given to finish before their
+    /// connections are dropped.
+    pub grace_period: Duration,
+    /// Hooks run in registration order after connections are drained.
+    pub hooks: Vec<ShutdownHook>,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            grace_period: Duration::from_secs(30),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+/// Completes when the process receives SIGINT (Ctrl+C) or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Serves requests until SIGINT or SIGTERM, then shuts down gracefully.
 pub async fn serve(
     router: Router,
     state: AppState,
     middlewares: MiddlewareStack,
     addr: SocketAddr,
 ) -> std::io::Result<()> {
-    let router = Arc::new(router);
-    let state = Arc::new(state);
-    let middlewares = Arc::new(middlewares);
     let listener = TcpListener::bind(addr).await?;
-
     println!("Rapina listening on http://{}", addr);
+    serve_with_shutdown(
+        router,
+        state,
+        middlewares,
+        listener,
+        shutdown_signal(),
+        ServerConfig::default(),
+        ShutdownConfig::default(),
+    )
+    .await
+}
 
+/// Serves requests from `listener` until `signal` completes.
+///
+/// Once the signal fires, the listener is closed, idle keep-alive
+/// connections are closed, and in-flight requests get up to
+/// `shutdown.grace_period` to finish. Remaining connections are then
+/// dropped and the shutdown hooks run before this function returns.
+/// Failing to accept a connection is logged and doesn't stop the server.
+///
+/// When `config` carries TLS settings, every accepted connection goes
+/// through a TLS handshake first.
+pub async fn serve_with_shutdown(
+    router: Router,
+    state: AppState,
+    middlewares: MiddlewareStack,
+    listener: TcpListener,
+    signal: impl Future<Output = ()>,
+    config: ServerConfig,
+    shutdown: ShutdownConfig,
+) -> std::io::Result<()> {
+    #[cfg(feature = "tls")]
+    let (acceptor, reloader) = match &config.tls {
+        Some(tls) => {
+            let tls = tls.build(config.protocol).map_err(std::io::Error::other)?;
+            (
+                Some(tls.acceptor),
+                tls.reloader.map(|r| tokio::spawn(r.run())),
+            )
+        }
+        None => (None, None),
+    };
+
+    let shared = Arc::new(Shared {
+        service: RapinaService::new(router, state, middlewares),
+        builders: config.builders(),
+    });
+    let (draining_tx, draining_rx) = watch::channel(false);
+    let mut connections = JoinSet::new();
+
+    tokio::pin!(signal);
     loop {
-        let (stream, _) = listener.accept().await?;
-        let io = TokioIo::new(stream);
-        let router = router.clone();
-        let state = state.clone();
-        let middlewares = middlewares.clone();
-
-        tokio::spawn(async move {
-            let service = service_fn(move |mut req: Request<Incoming>| {
-                let router = router.clone();
-                let state = state.clone();
-                let middlewares = middlewares.clone();
-
-                // Create and inject RequestContext at request start
-                let ctx = RequestContext::new();
-                req.extensions_mut().insert(ctx.clone());
-
-                async move {
-                    let response = middlewares.execute(req, &router, &state, &ctx).await;
-                    Ok::<_, std::convert::Infallible>(response)
+        tokio::select! {
+            accepted = listener.accept() => {
+                let stream = match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        // Errors such as running out of file descriptors pass
+                        // as connections close, so accepting resumes after a
+                        // pause instead of taking the server down
+                        tracing::error!(error = %e, "failed to accept connection");
+                        tokio::select! {
+                            _ = tokio::time::sleep(ACCEPT_BACKOFF) => continue,
+                            _ = &mut signal => break,
+                        }
+                    }
+                };
+                let shared = shared.clone();
+                let draining = draining_rx.clone();
+
+                #[cfg(feature = "tls")]
+                if let Some(acceptor) = acceptor.clone() {
+                    connections.spawn(async move {
+                        let stream = match acceptor.accept(stream).await {
+                            Ok(stream) => stream,
+                            Err(e) => {
+                                tracing::debug!(error = %e, "TLS handshake failed");
+                                return;
+                            }
+                        };
+                        let mut extensions = http::Extensions::new();
+                        extensions.insert(http::uri::Scheme::HTTPS);
+                        if let Some(certs) = stream.get_ref().1.peer_certificates() {
+                            extensions.insert(crate::tls::PeerCertificate(certs.into()));
+                        }
+                        serve_connection(stream, extensions, shared, draining).await;
+                    });
+                    continue;
                 }
-            });
 
-            if let Err(e) = http1::Builder::new().serve_connection(io, service).await {
-                eprintln!("connection error: {}", e);
+                connections.spawn(serve_connection(
+                    stream,
+                    http::Extensions::new(),
+                    shared,
+                    draining,
+                ));
             }
-        });
+            // Reap finished connections so the set doesn't grow unbounded.
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            _ = &mut signal => break,
+        }
+    }
+
+    drop(listener);
+    #[cfg(feature = "tls")]
+    if let Some(reloader) = reloader {
+        reloader.abort();
+    }
+
+    let _ = draining_tx.send(true);
+    let drained = tokio::time::timeout(shutdown.grace_period, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        eprintln!(
+            "grace period elapsed, dropping {} connection(s)",
+            connections.len()
+        );
+        connections.shutdown().await;
+    }
+
+    for hook in shutdown.hooks {
+        hook().await;
+    }
+
+    Ok(())
+}
+
+/// Everything a connection needs to serve requests.
+struct Shared {
+    service: RapinaService,
+    builders: Builders,
+}
+
+/// Serves requests from a single connection until it closes.
+///
+/// `extensions` are copied into every request, which is how per-connection
+/// data such as the client's TLS certificate reaches extractors.
+async fn serve_connection<S>(
+    stream: S,
+    extensions: http::Extensions,
+    shared: Arc<Shared>,
+    mut draining: watch::Receiver<bool>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let io = TokioIo::new(stream);
+    let builders = &shared.builders;
+    let service = service_fn(|mut req: Request<Incoming>| {
+        let shared = shared.clone();
+        req.extensions_mut().extend(extensions.clone());
+
+        async move {
+            let response = shared.service.respond(box_request(req)).await;
+            Ok::<_, std::convert::Infallible>(response)
+        }
+    });
+
+    // Finishes the in-flight request (if any) once draining starts, and
+    // closes the connection instead of waiting for the next one.
+    macro_rules! drive {
+        ($conn:expr) => {{
+            let conn = $conn;
+            tokio::pin!(conn);
+            tokio::select! {
+                result = conn.as_mut() => result.map_err(|e| e.to_string()),
+                _ = draining.changed() => {
+                    conn.as_mut().graceful_shutdown();
+                    conn.await.map_err(|e| e.to_string())
+                }
+            }
+        }};
+    }
+
+    let result = match builders.protocol {
+        Protocol::Auto => drive!(builders.auto.serve_connection_with_upgrades(io, service)),
+        Protocol::Http1 => drive!(builders.http1.serve_connection(io, service).with_upgrades()),
+        Protocol::Http2 => drive!(builders.auto.serve_connection(io, service)),
+    };
+    if let Err(e) = result {
+        eprintln!("connection error: {}", e);
     }
 }
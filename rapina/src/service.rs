@@ -0,0 +1,245 @@
+//! Interoperability with tower and hyper.
+//!
+//! - [`RapinaService`] runs an application as a tower (and hyper) `Service`,
+//!   so it can be mounted in another server or wrapped in tower layers.
+//! - [`AnyBodyService`] does the same for requests with any body type.
+//! - [`TowerLayerMiddleware`](crate::middleware::TowerLayerMiddleware) runs
+//!   a tower layer as Rapina middleware.
+//! - [`Router::mount`](crate::router::Router::mount) serves a path prefix
+//!   with a tower service.
+//!
+//! Rapina handlers and middleware read a boxed
+//! [`RequestBody`](crate::extract::RequestBody). Requests with other body
+//! types are boxed on the way in, keeping their method, URI, headers and
+//! extensions.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::{Request, Response};
+use http_body_util::BodyExt;
+use hyper::body::{Body, Incoming};
+
+use crate::context::RequestContext;
+use crate::error::Error;
+use crate::extract::RequestBody;
+use crate::middleware::{BoxFuture, MiddlewareStack};
+use crate::response::{BoxBody, BoxError, IntoResponse};
+use crate::router::{HandlerFn, RouteUrls, Router};
+use crate::state::AppState;
+
+/// A Rapina application as a tower `Service`.
+///
+/// Created with [`Rapina::into_service`](crate::app::Rapina::into_service).
+/// It also implements hyper's `Service`, so it can be passed to hyper's
+/// connection builders as-is.
+///
+/// # Examples
+///
+/// ```no_run
+/// use hyper_util::rt::{TokioExecutor, TokioIo};
+/// use hyper_util::server::conn::auto;
+/// use rapina::prelude::*;
+///
+/// #[get("/")]
+/// async fn hello() -> &'static str { "Hello!" }
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let service = Rapina::new()
+///     .router(Router::new().get("/", hello))
+///     .into_service();
+///
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+/// loop {
+///     let (stream, _) = listener.accept().await?;
+///     let service = service.clone();
+///     tokio::spawn(async move {
+///         let _ = auto::Builder::new(TokioExecutor::new())
+///             .serve_connection(TokioIo::new(stream), service)
+///             .await;
+///     });
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RapinaService {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    router: Router,
+    state: Arc<AppState>,
+    middlewares: MiddlewareStack,
+}
+
+impl RapinaService {
+    pub(crate) fn new(router: Router, state: AppState, middlewares: MiddlewareStack) -> Self {
+        // Lets the `UrlFor` extractor resolve routes by handler name
+        let state = state.with(RouteUrls::new(&router));
+        Self {
+            inner: Arc::new(Inner {
+                router,
+                state: Arc::new(state),
+                middlewares,
+            }),
+        }
+    }
+
+    /// Accepts requests with any body type instead of hyper's `Incoming`.
+    pub fn any_body(self) -> AnyBodyService {
+        AnyBodyService { service: self }
+    }
+
+    /// Runs a request through the middleware stack and the router.
+    ///
+    /// A [`RequestContext`] is created unless the request already has one.
+    pub(crate) async fn respond(&self, mut req: Request<RequestBody>) -> Response<BoxBody> {
+        let ctx = match req.extensions().get::<RequestContext>() {
+            Some(ctx) => ctx.clone(),
+            None => {
+                let ctx = RequestContext::new();
+                req.extensions_mut().insert(ctx.clone());
+                ctx
+            }
+        };
+        let inner = &self.inner;
+        inner
+            .middlewares
+            .execute(req, &inner.router, &inner.state, &ctx)
+            .await
+    }
+}
+
+impl tower::Service<Request<Incoming>> for RapinaService {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Incoming>) -> Self::Future {
+        hyper::service::Service::call(self, req)
+    }
+}
+
+impl hyper::service::Service<Request<Incoming>> for RapinaService {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn call(&self, req: Request<Incoming>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move { Ok(service.respond(box_request(req)).await) })
+    }
+}
+
+/// A [`RapinaService`] that accepts requests with any body type.
+///
+/// Created with [`RapinaService::any_body`]. The body is boxed into a
+/// [`RequestBody`] and read by the application as it arrives.
+///
+/// # Examples
+///
+/// ```
+/// use http_body_util::{BodyExt, Full};
+/// use rapina::prelude::*;
+/// use tower::Service;
+///
+/// #[post("/echo")]
+/// async fn echo(body: Json<serde_json::Value>) -> Json<serde_json::Value> { body }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut service = Rapina::new()
+///     .with_introspection(false)
+///     .router(Router::new().post("/echo", echo))
+///     .into_service()
+///     .any_body();
+///
+/// let request = http::Request::post("/echo")
+///     .header("content-type", "application/json")
+///     .body(Full::new(bytes::Bytes::from("[1,2]")))
+///     .unwrap();
+/// let response = service.call(request).await.unwrap();
+/// let body = response.into_body().collect().await.unwrap().to_bytes();
+/// assert_eq!(body, "[1,2]");
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AnyBodyService {
+    service: RapinaService,
+}
+
+impl<B> tower::Service<Request<B>> for AnyBodyService
+where
+    B: Body + Send + Sync + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move { Ok(service.respond(box_request(req)).await) })
+    }
+}
+
+/// Boxes the body of a request.
+pub(crate) fn box_request<B>(req: Request<B>) -> Request<RequestBody>
+where
+    B: Body + Send + Sync + 'static,
+    B::Error: Into<BoxError>,
+{
+    req.map(RequestBody::new)
+}
+
+/// Boxes the body of a response built by a tower service.
+pub(crate) fn box_response<B>(response: Response<B>) -> Response<BoxBody>
+where
+    B: Body<Data = bytes::Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    response.map(|body| body.map_err(Into::into).boxed_unsync())
+}
+
+/// Wraps a tower service as a handler.
+pub(crate) fn service_handler<S, ResBody>(service: S) -> HandlerFn
+where
+    S: tower::Service<Request<RequestBody>, Response = Response<ResBody>>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    ResBody: Body<Data = bytes::Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    Box::new(move |req, _params, _state| {
+        let mut service = service.clone();
+        Box::pin(async move {
+            if let Err(e) = std::future::poll_fn(|cx| service.poll_ready(cx)).await {
+                return service_error(e.into());
+            }
+            match service.call(req).await {
+                Ok(response) => box_response(response),
+                Err(e) => service_error(e.into()),
+            }
+        })
+    })
+}
+
+/// The response sent when a tower service fails.
+pub(crate) fn service_error(e: BoxError) -> Response<BoxBody> {
+    tracing::error!(error = %e, "service error");
+    Error::internal("service error").into_response()
+}
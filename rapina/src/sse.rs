@@ -0,0 +1,457 @@
+//! Server-Sent Events.
+//!
+//! [`Sse`] turns a stream of [`Event`]s into a `text/event-stream` response
+//! that stays open and pushes each event to the client as it is produced.
+//! Browsers consume it with the built-in `EventSource` API.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use rapina::futures_util::stream::{self, BoxStream, StreamExt};
+//! use rapina::prelude::*;
+//! use rapina::sse::{Event, KeepAlive, LastEventId, Sse};
+//!
+//! #[derive(Serialize)]
+//! struct Tick {
+//!     count: u64,
+//! }
+//!
+//! #[get("/ticks")]
+//! async fn ticks(last: LastEventId) -> Sse<BoxStream<'static, Result<Event>>> {
+//!     let start = last.into_inner().and_then(|id| id.parse().ok()).unwrap_or(0);
+//!     let events = stream::iter(start..start + 3).map(|count| {
+//!         Ok(Event::new()
+//!             .event("tick")
+//!             .id(count.to_string())
+//!             .json_data(&Tick { count })
+//!             .unwrap())
+//!     });
+//!
+//!     Sse::new(events.boxed()).keep_alive(KeepAlive::new().interval(Duration::from_secs(10)))
+//! }
+//! ```
+
+use std::fmt::Write;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::Stream;
+use http::{HeaderValue, Response, StatusCode, header};
+use http_body_util::BodyExt;
+use hyper::body::{Body, Frame};
+use serde::Serialize;
+use tokio::time::{Instant, Sleep};
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::response::{BoxBody, BoxError, IntoResponse};
+use crate::state::AppState;
+
+/// The header browsers send when reconnecting to an event stream.
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// A single Server-Sent Event.
+///
+/// All fields are optional. Multi-line data is split into several `data:`
+/// lines, which clients join back together with newlines.
+#[derive(Debug, Clone, Default)]
+pub struct Event {
+    event: Option<String>,
+    id: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Creates an empty event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the event type, dispatched to `addEventListener(name, ...)` on the client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name contains a newline.
+    pub fn event(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        assert_single_line("event", &name);
+        self.event = Some(name);
+        self
+    }
+
+    /// Sets the event ID, sent back by the client in `Last-Event-ID` when it reconnects.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID contains a newline or a null character.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        let id = id.into();
+        assert_single_line("id", &id);
+        assert!(!id.contains('\0'), "SSE id cannot contain a null character");
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets the event data as plain text.
+    ///
+    /// Multi-line data is sent as one `data:` field per line.
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Sets the event data by serializing a value as JSON.
+    pub fn json_data<T: Serialize>(mut self, data: &T) -> Result<Self, serde_json::Error> {
+        self.data = Some(serde_json::to_string(data)?);
+        Ok(self)
+    }
+
+    /// Tells the client how long to wait before reconnecting.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Adds a comment line, which clients ignore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comment contains a newline.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        let comment = comment.into();
+        assert_single_line("comment", &comment);
+        self.comment = Some(comment);
+        self
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        let mut out = String::new();
+
+        if let Some(comment) = &self.comment {
+            let _ = writeln!(out, ":{}", comment);
+        }
+        if let Some(event) = &self.event {
+            let _ = writeln!(out, "event: {}", event);
+        }
+        if let Some(id) = &self.id {
+            let _ = writeln!(out, "id: {}", id);
+        }
+        if let Some(retry) = &self.retry {
+            let _ = writeln!(out, "retry: {}", retry.as_millis());
+        }
+        if let Some(data) = &self.data {
+            // `\r\n`, `\r` and `\n` all end a line in the SSE format
+            for line in data.replace("\r\n", "\n").split(['\r', '\n']) {
+                let _ = writeln!(out, "data: {}", line);
+            }
+        }
+        out.push('\n');
+
+        Bytes::from(out)
+    }
+}
+
+fn assert_single_line(field: &str, value: &str) {
+    assert!(
+        !value.contains(['\n', '\r']),
+        "SSE {} cannot contain newlines",
+        field
+    );
+}
+
+/// Configures the comments sent to keep an idle event stream open.
+///
+/// Proxies and load balancers often close connections that stay silent for
+/// too long. When no event has been sent for `interval`, a comment line is
+/// written instead; clients ignore it.
+#[derive(Debug, Clone)]
+pub struct KeepAlive {
+    interval: Duration,
+    comment: Bytes,
+}
+
+impl KeepAlive {
+    /// Creates a keep-alive that sends `:` comments every 15 seconds.
+    pub fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            comment: Bytes::from_static(b":\n\n"),
+        }
+    }
+
+    /// Sets how long the stream may stay idle before a comment is sent.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the text of the keep-alive comment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the text contains a newline.
+    pub fn text(mut self, text: &str) -> Self {
+        assert_single_line("keep-alive text", text);
+        self.comment = Bytes::from(format!(":{}\n\n", text));
+        self
+    }
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A Server-Sent Events response.
+///
+/// Wraps a stream of `Result<Event, E>`. Each event is written to the
+/// client as soon as the stream yields it; an error ends the response.
+/// Keep-alive comments are disabled unless configured with
+/// [`keep_alive`](Self::keep_alive).
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S> Sse<S> {
+    /// Creates an SSE response from a stream of events.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: None,
+        }
+    }
+
+    /// Sends keep-alive comments while the stream is idle.
+    pub fn keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+}
+
+impl<S, E> IntoResponse for Sse<S>
+where
+    S: Stream<Item = Result<Event, E>> + Send + 'static,
+    E: Into<BoxError>,
+{
+    fn into_response(self) -> Response<BoxBody> {
+        let body = SseBody {
+            stream: Box::pin(self.stream),
+            keep_alive: self.keep_alive.map(|keep_alive| {
+                let sleep = Box::pin(tokio::time::sleep(keep_alive.interval));
+                (keep_alive, sleep)
+            }),
+        };
+
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/event-stream"),
+            )
+            .header(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"))
+            .body(body.boxed_unsync())
+            .unwrap()
+    }
+}
+
+struct SseBody<S> {
+    stream: Pin<Box<S>>,
+    keep_alive: Option<(KeepAlive, Pin<Box<Sleep>>)>,
+}
+
+impl<S, E> Body for SseBody<S>
+where
+    S: Stream<Item = Result<Event, E>>,
+    E: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        let this = self.get_mut();
+
+        match this.stream.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(event))) => {
+                if let Some((keep_alive, sleep)) = &mut this.keep_alive {
+                    sleep.as_mut().reset(Instant::now() + keep_alive.interval);
+                }
+                Poll::Ready(Some(Ok(Frame::data(event.to_bytes()))))
+            }
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => {
+                let Some((keep_alive, sleep)) = &mut this.keep_alive else {
+                    return Poll::Pending;
+                };
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                sleep.as_mut().reset(Instant::now() + keep_alive.interval);
+                Poll::Ready(Some(Ok(Frame::data(keep_alive.comment.clone()))))
+            }
+        }
+    }
+}
+
+/// Extracts the `Last-Event-ID` header sent by reconnecting clients.
+///
+/// Holds `None` on the first connection. Use it to resume the stream from
+/// where the client left off.
+#[derive(Debug, Clone, Default)]
+pub struct LastEventId(pub Option<String>);
+
+impl LastEventId {
+    /// Consumes the extractor and returns the last event ID, if any.
+    pub fn into_inner(self) -> Option<String> {
+        self.0
+    }
+}
+
+impl FromRequestParts for LastEventId {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        let Some(value) = parts.headers.get(LAST_EVENT_ID_HEADER) else {
+            return Ok(LastEventId(None));
+        };
+        let id = value
+            .to_str()
+            .map_err(|_| Error::bad_request("Invalid Last-Event-ID header"))?;
+        Ok(LastEventId(Some(id.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+    use std::convert::Infallible;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = Event::new()
+            .event("update")
+            .id("42")
+            .retry(Duration::from_secs(3))
+            .data("hello");
+
+        assert_eq!(
+            event.to_bytes(),
+            "event: update\nid: 42\nretry: 3000\ndata: hello\n\n"
+        );
+    }
+
+    #[test]
+    fn test_multiline_data_is_split() {
+        let event = Event::new().data("first\nsecond\r\nthird");
+        assert_eq!(
+            event.to_bytes(),
+            "data: first\ndata: second\ndata: third\n\n"
+        );
+    }
+
+    #[test]
+    fn test_lone_carriage_return_splits_data() {
+        let event = Event::new().data("x\revent: admin\r\rend");
+        assert_eq!(
+            event.to_bytes(),
+            "data: x\ndata: event: admin\ndata: \ndata: end\n\n"
+        );
+    }
+
+    #[test]
+    fn test_json_data() {
+        #[derive(Serialize)]
+        struct Update {
+            id: u64,
+        }
+
+        let event = Event::new().json_data(&Update { id: 7 }).unwrap();
+        assert_eq!(event.to_bytes(), "data: {\"id\":7}\n\n");
+    }
+
+    #[test]
+    fn test_comment() {
+        let event = Event::new().comment("ping");
+        assert_eq!(event.to_bytes(), ":ping\n\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "SSE event cannot contain newlines")]
+    fn test_event_name_rejects_newline() {
+        let _ = Event::new().event("a\nb");
+    }
+
+    #[tokio::test]
+    async fn test_sse_into_response() {
+        let events = stream::iter(vec![
+            Ok::<_, Infallible>(Event::new().data("one")),
+            Ok(Event::new().data("two")),
+        ]);
+        let response = Sse::new(events).into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"data: one\n\ndata: two\n\n");
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_sent_when_idle() {
+        let events = stream::pending::<Result<Event, Infallible>>();
+        let response = Sse::new(events)
+            .keep_alive(
+                KeepAlive::new()
+                    .interval(Duration::from_millis(10))
+                    .text("keep-alive"),
+            )
+            .into_response();
+        let mut body = response.into_body();
+
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), ":keep-alive\n\n");
+    }
+
+    #[tokio::test]
+    async fn test_last_event_id_extractor() {
+        let state = Arc::new(AppState::new());
+        let (parts, _) = http::Request::builder()
+            .header("Last-Event-ID", "17")
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let id = LastEventId::from_request_parts(&parts, &PathParams::new(), &state)
+            .await
+            .unwrap();
+        assert_eq!(id.into_inner(), Some("17".to_string()));
+
+        let (parts, _) = http::Request::new(()).into_parts();
+        let id = LastEventId::from_request_parts(&parts, &PathParams::new(), &state)
+            .await
+            .unwrap();
+        assert_eq!(id.into_inner(), None);
+    }
+}
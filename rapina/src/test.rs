@@ -20,44 +20,49 @@ pub struct TestRequest {
 }
 
 impl TestRequest {
-    /// Create a new GET request
-    pub fn get(uri: &str) -> Self {
+    /// Create a new request with the given method
+    pub fn new(method: http::Method, uri: &str) -> Self {
         Self {
-            method: http::Method::GET,
+            method,
             uri: uri.to_string(),
             headers: http::HeaderMap::new(),
             body: Bytes::new(),
         }
     }
 
+    /// Create a new GET request
+    pub fn get(uri: &str) -> Self {
+        Self::new(http::Method::GET, uri)
+    }
+
     /// Create a new POST request
     pub fn post(uri: &str) -> Self {
-        Self {
-            method: http::Method::POST,
-            uri: uri.to_string(),
-            headers: http::HeaderMap::new(),
-            body: Bytes::new(),
-        }
+        Self::new(http::Method::POST, uri)
     }
 
     /// Create a new PUT request
     pub fn put(uri: &str) -> Self {
-        Self {
-            method: http::Method::PUT,
-            uri: uri.to_string(),
-            headers: http::HeaderMap::new(),
-            body: Bytes::new(),
-        }
+        Self::new(http::Method::PUT, uri)
     }
 
     /// Create a new DELETE request
     pub fn delete(uri: &str) -> Self {
-        Self {
-            method: http::Method::DELETE,
-            uri: uri.to_string(),
-            headers: http::HeaderMap::new(),
-            body: Bytes::new(),
-        }
+        Self::new(http::Method::DELETE, uri)
+    }
+
+    /// Create a new PATCH request
+    pub fn patch(uri: &str) -> Self {
+        Self::new(http::Method::PATCH, uri)
+    }
+
+    /// Create a new HEAD request
+    pub fn head(uri: &str) -> Self {
+        Self::new(http::Method::HEAD, uri)
+    }
+
+    /// Create a new OPTIONS request
+    pub fn options(uri: &str) -> Self {
+        Self::new(http::Method::OPTIONS, uri)
     }
 
     /// Add a header to the request
@@ -191,6 +196,24 @@ mod tests {
         assert!(!body.is_empty());
     }
 
+    #[test]
+    fn test_request_builder_methods() {
+        assert_eq!(
+            TestRequest::patch("/").into_parts().0.method,
+            http::Method::PATCH
+        );
+        assert_eq!(
+            TestRequest::head("/").into_parts().0.method,
+            http::Method::HEAD
+        );
+        assert_eq!(
+            TestRequest::options("/").into_parts().0.method,
+            http::Method::OPTIONS
+        );
+        let (parts, _) = TestRequest::new(http::Method::TRACE, "/trace").into_parts();
+        assert_eq!(parts.method, http::Method::TRACE);
+    }
+
     #[test]
     fn test_request_builder_with_headers() {
         let (parts, _) = TestRequest::get("/")
@@ -1,31 +1,47 @@
 //! Test client for integration testing Rapina applications.
 
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::Mutex;
 
 use bytes::Bytes;
-use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Version};
 use http_body_util::{BodyExt, Full};
 use hyper::Request;
-use hyper::body::Incoming;
-use hyper::server::conn::http1;
-use hyper::service::service_fn;
 use hyper_util::client::legacy::Client;
-use hyper_util::rt::TokioIo;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
 use serde::{Serialize, de::DeserializeOwned};
 use tokio::net::TcpListener;
 use tokio::sync::oneshot;
 
-use crate::context::RequestContext;
+use crate::auth::AuthConfig;
+use crate::error::ErrorResponse;
 use crate::introspection::RouteRegistry;
 use crate::middleware::MiddlewareStack;
 use crate::router::Router;
+use crate::server::{ServerConfig, ShutdownConfig, serve_with_shutdown};
+use crate::service::{RapinaService, box_request};
 use crate::state::AppState;
 
+use super::contract::{Contract, RouteCoverage};
+use super::cookies::CookieJar;
+use super::snapshot::Snapshot;
+
+/// Redirects followed for a single request before giving up.
+const MAX_REDIRECTS: usize = 10;
+
 /// A test client for making HTTP requests to a Rapina application.
 ///
-/// The test client spawns a lightweight HTTP server on a random port
-/// and provides a convenient API for making requests and asserting responses.
+/// [`TestClient::new`] spawns a lightweight HTTP server on a random port
+/// and sends requests over a real socket. [`TestClient::in_process`] skips
+/// the socket and calls the application directly, which is faster and
+/// can't run out of ports. Both share the same request and response API.
+///
+/// Cookies set by the application are kept in a cookie jar and sent back
+/// on later requests, so login flows work as in a browser.
+///
+/// With [`contract`](Self::contract) checks on, every response is compared
+/// with what its route documents in the OpenAPI spec.
 ///
 /// # Examples
 ///
@@ -46,27 +62,204 @@ use crate::state::AppState;
 /// }
 /// ```
 pub struct TestClient {
-    addr: SocketAddr,
-    client: Client<hyper_util::client::legacy::connect::HttpConnector, Full<Bytes>>,
-    _shutdown: oneshot::Sender<()>,
+    transport: Transport,
+    cookies: Mutex<CookieJar>,
+    auth: Option<AuthConfig>,
+    follow_redirects: bool,
+    contract: Contract,
+}
+
+/// How requests reach the application.
+enum Transport {
+    /// Over TCP, to a server running in the background.
+    Socket {
+        addr: SocketAddr,
+        client: Box<Client<HttpConnector, Full<Bytes>>>,
+        _shutdown: oneshot::Sender<()>,
+    },
+    /// Straight to the middleware stack, without a connection.
+    InProcess {
+        service: RapinaService,
+        version: Version,
+    },
 }
 
 impl TestClient {
     /// Creates a new test client from a Rapina application.
     ///
-    /// This spawns a background server on a random available port.
-    pub async fn new(app: crate::app::Rapina) -> Self {
-        Self::from_parts(app.router, app.state, app.middlewares, app.introspection).await
+    /// This spawns a background server on a random available port, using
+    /// the application's [`ServerConfig`]. The OpenAPI endpoints are served
+    /// when [`Rapina::openapi`](crate::app::Rapina::openapi) is enabled, and
+    /// authentication is enforced when [`Rapina::with_auth`](crate::app::Rapina::with_auth) is.
+    pub async fn new(mut app: crate::app::Rapina) -> Self {
+        app.mount_openapi()
+            .unwrap_or_else(|e| panic!("invalid OpenAPI spec: {}", e));
+        let auth = app.auth_config.clone();
+        app.mount_auth();
+        let mut client = Self::with_server_config(
+            app.router,
+            app.state,
+            app.middlewares,
+            app.introspection,
+            app.server_config,
+        )
+        .await;
+        if let Some(auth) = auth {
+            client.use_auth(auth);
+        }
+        client
+    }
+
+    /// Creates a test client that calls the application in-process, without
+    /// binding a port.
+    ///
+    /// The app is set up as by [`Rapina::listen`](crate::app::Rapina::listen),
+    /// and requests go through the same middleware stack and router with
+    /// an in-memory body. Nothing is sent over the network, so
+    /// connection-level behavior (TLS, WebSocket upgrades, HTTP/2 framing)
+    /// isn't exercised. Use [`new`](Self::new) for those.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rapina::prelude::*;
+    /// use rapina::testing::TestClient;
+    ///
+    /// #[get("/")]
+    /// async fn hello() -> &'static str { "Hello!" }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let app = Rapina::new().router(Router::new().get("/", hello));
+    ///
+    /// let client = TestClient::in_process(app);
+    /// let response = client.get("/").send().await;
+    ///
+    /// assert_eq!(response.text(), "Hello!");
+    /// # }
+    /// ```
+    pub fn in_process(mut app: crate::app::Rapina) -> Self {
+        let auth = app.auth_config.clone();
+        app.prepare().unwrap_or_else(|e| panic!("{}", e));
+        let contract = Contract::new(app.router.routes());
+        let mut client = Self::with_transport(
+            Transport::InProcess {
+                service: RapinaService::new(app.router, app.state, app.middlewares),
+                version: Version::HTTP_11,
+            },
+            contract,
+        );
+        if let Some(auth) = auth {
+            client.use_auth(auth);
+        }
+        client
+    }
+
+    /// Creates an in-process test client from router, state, and middlewares.
+    pub fn in_process_from_parts(
+        router: Router,
+        state: AppState,
+        middlewares: MiddlewareStack,
+        introspection: bool,
+    ) -> Self {
+        let (router, state, contract) = Self::prepare(router, state, introspection);
+        Self::with_transport(
+            Transport::InProcess {
+                service: RapinaService::new(router, state, middlewares),
+                version: Version::HTTP_11,
+            },
+            contract,
+        )
     }
 
     /// Creates a test client from router, state, and middlewares.
     pub async fn from_parts(
-        mut router: Router,
-        mut state: AppState,
+        router: Router,
+        state: AppState,
+        middlewares: MiddlewareStack,
+        introspection: bool,
+    ) -> Self {
+        Self::with_server_config(
+            router,
+            state,
+            middlewares,
+            introspection,
+            ServerConfig::default(),
+        )
+        .await
+    }
+
+    async fn with_server_config(
+        router: Router,
+        state: AppState,
         middlewares: MiddlewareStack,
         introspection: bool,
+        #[allow(unused_mut)] mut config: ServerConfig,
     ) -> Self {
-        // Apply introspection if enabled
+        // The client only speaks plain HTTP
+        #[cfg(feature = "tls")]
+        {
+            config.tls = None;
+        }
+
+        let (router, state, contract) = Self::prepare(router, state, introspection);
+
+        // Bind to a random available port
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // The server stops once the client (and this sender) is dropped
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        tokio::spawn(serve_with_shutdown(
+            router,
+            state,
+            middlewares,
+            listener,
+            async {
+                let _ = shutdown_rx.await;
+            },
+            config,
+            ShutdownConfig::default(),
+        ));
+
+        let client = Box::new(Client::builder(TokioExecutor::new()).build_http());
+
+        Self::with_transport(
+            Transport::Socket {
+                addr,
+                client,
+                _shutdown: shutdown_tx,
+            },
+            contract,
+        )
+    }
+
+    fn with_transport(transport: Transport, contract: Contract) -> Self {
+        Self {
+            transport,
+            cookies: Mutex::new(CookieJar::default()),
+            auth: None,
+            follow_redirects: false,
+            contract,
+        }
+    }
+
+    /// Signs [`TestRequestBuilder::as_user`] tokens with the app's config,
+    /// and documents `401` responses on its protected routes.
+    fn use_auth(&mut self, config: AuthConfig) {
+        self.auth = Some(config);
+        self.contract.require_auth();
+    }
+
+    /// Registers the introspection endpoint if enabled, compiles the router
+    /// and collects the routes' contract.
+    fn prepare(
+        mut router: Router,
+        mut state: AppState,
+        introspection: bool,
+    ) -> (Router, AppState, Contract) {
+        let contract = Contract::new(router.routes());
         if introspection {
             let routes = router.routes();
             state = state.with(RouteRegistry::with_routes(routes));
@@ -77,66 +270,114 @@ impl TestClient {
             );
         }
 
-        let router = Arc::new(router);
-        let state = Arc::new(state);
-        let middlewares = Arc::new(middlewares);
-
-        // Bind to a random available port
-        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-        let addr = listener.local_addr().unwrap();
+        router
+            .compile()
+            .unwrap_or_else(|e| panic!("invalid router: {}", e));
+        (router, state, contract)
+    }
 
-        // Create shutdown channel
-        let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
-
-        // Spawn the server
-        tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    result = listener.accept() => {
-                        match result {
-                            Ok((stream, _)) => {
-                                let io = TokioIo::new(stream);
-                                let router = router.clone();
-                                let state = state.clone();
-                                let middlewares = middlewares.clone();
-
-                                tokio::spawn(async move {
-                                    let service = service_fn(move |mut req: Request<Incoming>| {
-                                        let router = router.clone();
-                                        let state = state.clone();
-                                        let middlewares = middlewares.clone();
-
-                                        let ctx = RequestContext::new();
-                                        req.extensions_mut().insert(ctx.clone());
-
-                                        async move {
-                                            let response = middlewares.execute(req, &router, &state, &ctx).await;
-                                            Ok::<_, std::convert::Infallible>(response)
-                                        }
-                                    });
-
-                                    let _ = http1::Builder::new()
-                                        .serve_connection(io, service)
-                                        .await;
-                                });
-                            }
-                            Err(_) => break,
-                        }
-                    }
-                    _ = &mut shutdown_rx => {
-                        break;
-                    }
-                }
+    /// Switches the client to HTTP/2.
+    ///
+    /// Requests are sent over cleartext HTTP/2 with prior knowledge (h2c),
+    /// which the server accepts unless it is configured for HTTP/1.1 only.
+    /// In-process clients only set the version of their requests and
+    /// responses to HTTP/2; there is no connection to frame them.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let client = TestClient::new(app).await.http2();
+    /// let response = client.get("/").send().await;
+    ///
+    /// assert_eq!(response.version(), http::Version::HTTP_2);
+    /// ```
+    pub fn http2(mut self) -> Self {
+        match &mut self.transport {
+            Transport::Socket { client, .. } => {
+                **client = Client::builder(TokioExecutor::new())
+                    .http2_only(true)
+                    .build_http();
             }
-        });
+            Transport::InProcess { version, .. } => *version = Version::HTTP_2,
+        }
+        self
+    }
 
-        let client = Client::builder(hyper_util::rt::TokioExecutor::new()).build_http();
+    /// Follows redirects instead of returning them.
+    ///
+    /// `303 See Other`, and `301`/`302` answers to a POST, are followed with
+    /// a GET without body; other redirects repeat the original request. At
+    /// most 10 redirects are followed per request.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let client = TestClient::new(app).await.follow_redirects(true);
+    /// let response = client.post("/login").form(&credentials).send().await;
+    ///
+    /// // The page `/login` redirected to
+    /// assert_eq!(response.status(), StatusCode::OK);
+    /// ```
+    pub fn follow_redirects(mut self, follow: bool) -> Self {
+        self.follow_redirects = follow;
+        self
+    }
 
-        Self {
-            addr,
-            client,
-            _shutdown: shutdown_tx,
-        }
+    /// Uses `config` to sign the tokens of [`TestRequestBuilder::as_user`].
+    ///
+    /// Clients created with [`new`](Self::new) or [`in_process`](Self::in_process)
+    /// already use the app's [`AuthConfig`], if any.
+    pub fn auth_config(mut self, config: AuthConfig) -> Self {
+        self.auth = Some(config);
+        self
+    }
+
+    /// Checks every response against the documentation of the route that
+    /// served it, panicking on the first mismatch.
+    ///
+    /// Successful responses must have the documented content type and a
+    /// body matching the handler's response schema. Error responses must
+    /// use a status (and error code) the route documents, such as the
+    /// variants of its `DocumentedError` type or `401` on routes protected
+    /// by [`Rapina::with_auth`](crate::app::Rapina::with_auth), and carry
+    /// the standard error body. Requests that no route matches aren't
+    /// checked.
+    ///
+    /// Checks are on by default when the `RAPINA_CONTRACT` environment
+    /// variable is `1` or `true`, as under `rapina test --contract`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let client = TestClient::in_process(app).contract(true);
+    ///
+    /// // Panics if `get_user` doesn't document a 404 error
+    /// client.get("/users/0").send().await;
+    /// ```
+    pub fn contract(mut self, enabled: bool) -> Self {
+        self.contract.set_enabled(enabled);
+        self
+    }
+
+    /// Returns which documented status codes each route has responded with
+    /// so far, while [`contract`](Self::contract) checks are on.
+    pub fn coverage(&self) -> Vec<RouteCoverage> {
+        self.contract.coverage()
+    }
+
+    /// Returns the value of a cookie in the client's cookie jar.
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.cookies.lock().unwrap().get(name).map(str::to_string)
+    }
+
+    /// Adds a cookie to the jar, sent with every following request.
+    pub fn set_cookie(&self, name: &str, value: &str) {
+        self.cookies.lock().unwrap().set(name, value);
+    }
+
+    /// Empties the cookie jar, e.g. to act as a logged-out user.
+    pub fn clear_cookies(&self) {
+        self.cookies.lock().unwrap().clear();
     }
 
     /// Creates a GET request builder.
@@ -164,14 +405,31 @@ impl TestClient {
         self.request(Method::PATCH, path)
     }
 
+    /// Creates a HEAD request builder.
+    pub fn head(&self, path: &str) -> TestRequestBuilder<'_> {
+        self.request(Method::HEAD, path)
+    }
+
+    /// Creates an OPTIONS request builder.
+    pub fn options(&self, path: &str) -> TestRequestBuilder<'_> {
+        self.request(Method::OPTIONS, path)
+    }
+
     /// Creates a request builder with the given method and path.
     pub fn request(&self, method: Method, path: &str) -> TestRequestBuilder<'_> {
         TestRequestBuilder::new(self, method, path)
     }
 
     /// Returns the address the test server is listening on.
+    ///
+    /// # Panics
+    ///
+    /// Panics for in-process clients, which don't listen on any address.
     pub fn addr(&self) -> SocketAddr {
-        self.addr
+        match &self.transport {
+            Transport::Socket { addr, .. } => *addr,
+            Transport::InProcess { .. } => panic!("in-process test clients have no address"),
+        }
     }
 }
 
@@ -230,45 +488,237 @@ impl<'a> TestRequestBuilder<'a> {
         self
     }
 
+    /// Sends `token` in an `Authorization: Bearer` header.
+    pub fn bearer(self, token: &str) -> Self {
+        self.header("authorization", &format!("Bearer {}", token))
+    }
+
+    /// Authenticates the request as `user_id`, with a token signed by the
+    /// app's [`AuthConfig`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the app has no `AuthConfig`, see [`Rapina::with_auth`](crate::app::Rapina::with_auth)
+    /// and [`TestClient::auth_config`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let response = client.get("/me").as_user("42").send().await;
+    /// assert_eq!(response.json::<Me>().id, "42");
+    /// ```
+    pub fn as_user(self, user_id: impl Into<String>) -> Self {
+        let token = self
+            .client
+            .auth
+            .as_ref()
+            .expect("as_user needs an app configured with `with_auth`")
+            .create_token(user_id)
+            .unwrap_or_else(|e| panic!("failed to create a token: {}", e));
+        self.bearer(&token)
+    }
+
     /// Sends the request and returns the response.
+    ///
+    /// Cookies from the client's jar are added to the request, and cookies
+    /// set by the response are stored in it, including on redirects.
     pub async fn send(self) -> TestResponse {
-        let uri = format!("http://{}{}", self.client.addr, self.path);
+        self.send_checked()
+            .await
+            .unwrap_or_else(|violation| panic!("{}", violation))
+    }
 
-        let mut builder = Request::builder().method(self.method).uri(&uri);
+    /// Sends the request like [`send`](Self::send), returning a broken
+    /// contract as an error instead of panicking.
+    pub(crate) async fn send_checked(self) -> Result<TestResponse, String> {
+        let client = self.client;
+        let mut method = self.method;
+        let mut path = self.path;
+        let mut headers = self.headers;
+        let mut body = self.body;
+        let mut redirects = 0;
+
+        loop {
+            let mut request_headers = headers.clone();
+            if let Some(cookies) = client.cookies.lock().unwrap().header_for(&path) {
+                let cookies = match request_headers.get(http::header::COOKIE) {
+                    Some(own) => format!("{}; {}", own.to_str().unwrap_or(""), cookies),
+                    None => cookies,
+                };
+                request_headers.insert(
+                    http::header::COOKIE,
+                    HeaderValue::from_str(&cookies).unwrap(),
+                );
+            }
 
-        for (key, value) in self.headers.iter() {
-            builder = builder.header(key, value);
-        }
+            let response = client
+                .execute(method.clone(), &path, request_headers, body.clone())
+                .await;
+
+            client.contract.check(
+                &method,
+                &path,
+                response.status,
+                &response.headers,
+                &response.body,
+            )?;
+
+            {
+                let mut jar = client.cookies.lock().unwrap();
+                for set_cookie in response.headers.get_all(http::header::SET_COOKIE) {
+                    if let Ok(set_cookie) = set_cookie.to_str() {
+                        jar.store(set_cookie, &path);
+                    }
+                }
+            }
 
-        let request = builder.body(Full::new(self.body)).unwrap();
+            let location = response
+                .headers
+                .get(http::header::LOCATION)
+                .and_then(|location| location.to_str().ok());
+            let Some(location) =
+                location.filter(|_| client.follow_redirects && response.status.is_redirection())
+            else {
+                return Ok(response);
+            };
+            redirects += 1;
+            assert!(
+                redirects <= MAX_REDIRECTS,
+                "more than {} redirects, last to {}",
+                MAX_REDIRECTS,
+                location
+            );
 
-        let response = self.client.client.request(request).await.unwrap();
+            let status = response.status;
+            if status == StatusCode::SEE_OTHER
+                || (method == Method::POST
+                    && matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND))
+            {
+                if method != Method::HEAD {
+                    method = Method::GET;
+                }
+                body = Bytes::new();
+                headers.remove(http::header::CONTENT_TYPE);
+                headers.remove(http::header::CONTENT_LENGTH);
+            }
+            path = resolve_location(&path, location);
+        }
+    }
+}
 
-        let status = response.status();
-        let headers = response.headers().clone();
-        let body = response.into_body().collect().await.unwrap().to_bytes();
+impl TestClient {
+    /// Sends a single request, without cookies or redirects.
+    async fn execute(
+        &self,
+        method: Method,
+        path: &str,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> TestResponse {
+        match &self.transport {
+            Transport::Socket { addr, client, .. } => {
+                let uri = format!("http://{}{}", addr, path);
+                let mut request = Request::builder()
+                    .method(method)
+                    .uri(&uri)
+                    .body(Full::new(body))
+                    .unwrap();
+                *request.headers_mut() = headers;
+
+                let response = client.request(request).await.unwrap();
+                TestResponse::read(response).await
+            }
+            Transport::InProcess { service, version } => {
+                let mut headers = headers;
+                // What a client connected over the network would send
+                if !headers.contains_key(http::header::HOST) {
+                    headers.insert(http::header::HOST, HeaderValue::from_static("localhost"));
+                }
+                if (!body.is_empty() || has_body(&method))
+                    && !headers.contains_key(http::header::CONTENT_LENGTH)
+                    && !headers.contains_key(http::header::TRANSFER_ENCODING)
+                {
+                    headers.insert(http::header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+                }
 
-        TestResponse {
-            status,
-            headers,
-            body,
+                let mut request = Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .version(*version)
+                    .body(Full::new(body))
+                    .unwrap();
+                *request.headers_mut() = headers;
+
+                let mut response = service.respond(box_request(request)).await;
+                *response.version_mut() = *version;
+                set_content_length(&mut response);
+                TestResponse::read(response).await
+            }
         }
     }
 }
 
+/// Resolves a `Location` header against the path it was received for.
+///
+/// Absolute URLs keep only their path and query, as every request goes to
+/// the application under test.
+fn resolve_location(current: &str, location: &str) -> String {
+    if let Ok(uri) = location.parse::<http::Uri>()
+        && uri.scheme().is_some()
+    {
+        return uri
+            .path_and_query()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| "/".to_string());
+    }
+    if location.starts_with('/') {
+        return location.to_string();
+    }
+    let current = current.split('?').next().unwrap_or("");
+    if let Some(query) = location.strip_prefix('?') {
+        return format!("{}?{}", current, query);
+    }
+    let base = &current[..current.rfind('/').map_or(0, |end| end + 1)];
+    format!("{}{}", if base.is_empty() { "/" } else { base }, location)
+}
+
 /// Response from a test request.
 pub struct TestResponse {
     status: StatusCode,
+    version: Version,
     headers: HeaderMap,
     body: Bytes,
 }
 
 impl TestResponse {
+    pub(super) async fn read<B>(response: http::Response<B>) -> Self
+    where
+        B: hyper::body::Body,
+        B::Error: std::fmt::Debug,
+    {
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        TestResponse {
+            status,
+            version,
+            headers,
+            body,
+        }
+    }
+
     /// Returns the HTTP status code.
     pub fn status(&self) -> StatusCode {
         self.status
     }
 
+    /// Returns the HTTP version the response was received over.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
     /// Returns the response headers.
     pub fn headers(&self) -> &HeaderMap {
         &self.headers
@@ -293,6 +743,126 @@ impl TestResponse {
     pub fn try_json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
         serde_json::from_slice(&self.body)
     }
+
+    /// Deserializes the body as Rapina's error envelope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the body isn't an [`ErrorResponse`].
+    #[track_caller]
+    pub fn error_response(&self) -> ErrorResponse {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| {
+            panic!(
+                "expected an error response ({}), got {} with body: {}",
+                e,
+                self.status,
+                self.text()
+            )
+        })
+    }
+
+    /// Asserts the response status, showing the body when it doesn't match.
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(
+            self.status,
+            status,
+            "unexpected status, body: {}",
+            self.text()
+        );
+        self
+    }
+
+    /// Asserts that the response has the header `name` with `value`.
+    #[track_caller]
+    pub fn assert_header(&self, name: &str, value: &str) -> &Self {
+        let actual = self.headers.get(name).map(|v| v.to_str().unwrap_or(""));
+        assert_eq!(actual, Some(value), "unexpected `{}` header", name);
+        self
+    }
+
+    /// Asserts that the response is an [`ErrorResponse`] with `code`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// client.get("/users/0").send().await.assert_error_code("NOT_FOUND");
+    /// ```
+    #[track_caller]
+    pub fn assert_error_code(&self, code: &str) -> &Self {
+        let error = self.error_response();
+        assert_eq!(
+            error.error.code, code,
+            "unexpected error code, message: {}",
+            error.error.message
+        );
+        self
+    }
+
+    /// Asserts that the JSON body equals `expected`.
+    #[track_caller]
+    pub fn assert_json(&self, expected: serde_json::Value) -> &Self {
+        let actual: serde_json::Value = serde_json::from_slice(&self.body)
+            .unwrap_or_else(|e| panic!("body is not JSON ({}): {}", e, self.text()));
+        assert_eq!(actual, expected);
+        self
+    }
+
+    /// Starts a snapshot assertion, to include more headers or redact
+    /// more values than [`assert_snapshot`](Self::assert_snapshot) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a relative path without `..`.
+    #[track_caller]
+    pub fn snapshot(&self, name: &str) -> Snapshot<'_> {
+        Snapshot::new(self, name)
+    }
+
+    /// Asserts that the response matches the snapshot stored in
+    /// `tests/snapshots/{name}.snap`.
+    ///
+    /// The snapshot holds the status, the `content-type` header and the
+    /// pretty-printed body, with `trace_id`, UUIDs and timestamps redacted.
+    /// New and changed snapshots are reviewed and accepted with
+    /// `rapina test --update-snapshots`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// client.get("/users").send().await.assert_snapshot("users");
+    /// ```
+    #[track_caller]
+    pub fn assert_snapshot(&self, name: &str) -> &Self {
+        Snapshot::new(self, name).assert();
+        self
+    }
+}
+
+/// Whether requests with this method carry a body, even an empty one.
+fn has_body(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH)
+}
+
+/// Adds the `Content-Length` header hyper would send for a buffered body.
+fn set_content_length(response: &mut http::Response<crate::response::BoxBody>) {
+    use hyper::body::Body;
+
+    let status = response.status();
+    if status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+        || response
+            .headers()
+            .contains_key(http::header::CONTENT_LENGTH)
+    {
+        return;
+    }
+    if let Some(length) = response.body().size_hint().exact() {
+        response
+            .headers_mut()
+            .insert(http::header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
 }
 
 #[cfg(test)]
@@ -494,4 +1064,69 @@ mod tests {
         assert!(addr.port() > 0);
         assert_eq!(addr.ip().to_string(), "127.0.0.1");
     }
+
+    #[tokio::test]
+    async fn test_in_process_client() {
+        let app = Rapina::new()
+            .with_introspection(false)
+            .router(
+                Router::new().route(http::Method::POST, "/echo", |req, _, _| async move {
+                    let host = req.headers()[http::header::HOST]
+                        .to_str()
+                        .unwrap()
+                        .to_string();
+                    let body = req.into_body().collect().await.unwrap().to_bytes();
+                    format!("{} {}", host, String::from_utf8_lossy(&body))
+                }),
+            );
+
+        let client = TestClient::in_process(app);
+        let response = client.post("/echo").body("ping").send().await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text(), "localhost ping");
+        assert_eq!(response.headers()["content-length"], "14");
+        assert_eq!(response.version(), Version::HTTP_11);
+
+        let response = client.get("/nonexistent").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_in_process_client_with_middleware_and_introspection() {
+        let app = Rapina::new()
+            .with_introspection(true)
+            .middleware(crate::middleware::TraceIdMiddleware::new())
+            .router(Router::new().route(http::Method::GET, "/", |_, _, _| async { "ok" }));
+
+        let client = TestClient::in_process(app).http2();
+        let response = client.get("/").send().await;
+        assert!(response.headers().contains_key("x-trace-id"));
+        assert_eq!(response.version(), Version::HTTP_2);
+
+        let response = client.get("/__rapina/routes").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "in-process test clients have no address")]
+    async fn test_in_process_client_has_no_addr() {
+        let app = Rapina::new()
+            .with_introspection(false)
+            .router(Router::new());
+
+        TestClient::in_process(app).addr();
+    }
+
+    #[test]
+    fn test_resolve_location() {
+        assert_eq!(resolve_location("/a/b", "/c"), "/c");
+        assert_eq!(resolve_location("/a/b", "c?x=1"), "/a/c?x=1");
+        assert_eq!(resolve_location("/a/b?y=2", "?x=1"), "/a/b?x=1");
+        assert_eq!(resolve_location("/login", "home"), "/home");
+        assert_eq!(
+            resolve_location("/login", "http://example.com/home?x=1"),
+            "/home?x=1"
+        );
+    }
 }
@@ -0,0 +1,519 @@
+//! Contract testing: checks responses against what their route documents.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode, header};
+use serde::Serialize;
+
+use crate::introspection::RouteInfo;
+use crate::openapi::error_response_schema;
+use crate::router::{Match, RouteTree};
+
+use super::schema;
+
+/// Turns contract checks on for every test client when set to `1` or `true`.
+pub(crate) const CONTRACT_ENV: &str = "RAPINA_CONTRACT";
+
+/// File the coverage of contract-checked clients is appended to, as one
+/// JSON [`RouteCoverage`] per line. Set by `rapina test --contract`.
+pub(crate) const REPORT_ENV: &str = "RAPINA_CONTRACT_REPORT";
+
+/// How much of a route's documented responses the tests exercised.
+///
+/// Returned by [`TestClient::coverage`](super::TestClient::coverage).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteCoverage {
+    /// The HTTP method of the route.
+    pub method: String,
+    /// The route's path pattern.
+    pub path: String,
+    /// The name of the route's handler.
+    pub handler_name: String,
+    /// Documented status codes. `200` stands for the success response,
+    /// which any `2xx` status covers, as in the OpenAPI spec.
+    pub documented: Vec<u16>,
+    /// Status codes of the responses received so far.
+    pub observed: Vec<u16>,
+}
+
+impl RouteCoverage {
+    /// Documented status codes that no response had.
+    pub fn missing(&self) -> Vec<u16> {
+        self.documented
+            .iter()
+            .copied()
+            .filter(|&documented| {
+                !self
+                    .observed
+                    .iter()
+                    .any(|&observed| covers(documented, observed))
+            })
+            .collect()
+    }
+}
+
+fn covers(documented: u16, observed: u16) -> bool {
+    documented == observed || (documented == 200 && (200..300).contains(&observed))
+}
+
+/// The documented responses of an application's routes.
+pub(crate) struct Contract {
+    enabled: bool,
+    routes: Vec<RouteContract>,
+    tree: RouteTree,
+    /// Status codes received per route, by index into `routes`.
+    observed: Mutex<Vec<BTreeSet<u16>>>,
+}
+
+struct RouteContract {
+    info: RouteInfo,
+    requires_auth: bool,
+}
+
+impl Contract {
+    /// Builds the contract of `routes`, skipping Rapina's own endpoints.
+    ///
+    /// Checks start enabled when [`CONTRACT_ENV`] is set.
+    pub(crate) fn new(routes: Vec<RouteInfo>) -> Self {
+        let routes: Vec<RouteContract> = routes
+            .into_iter()
+            .filter(|route| !is_internal(&route.path))
+            .map(|info| RouteContract {
+                info,
+                requires_auth: false,
+            })
+            .collect();
+        let methods: Vec<Method> = routes
+            .iter()
+            .map(|route| Method::from_bytes(route.info.method.as_bytes()).unwrap_or_default())
+            .collect();
+        // Same routes as the compiled router, so this can't fail
+        let tree = RouteTree::from_patterns(
+            methods
+                .iter()
+                .zip(routes.iter().map(|route| route.info.path.as_str())),
+        )
+        .unwrap_or_default();
+
+        Self {
+            enabled: std::env::var(CONTRACT_ENV).is_ok_and(|value| value == "1" || value == "true"),
+            observed: Mutex::new(vec![BTreeSet::new(); routes.len()]),
+            routes,
+            tree,
+        }
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Documents `401 Unauthorized` on every route that isn't public.
+    pub(crate) fn require_auth(&mut self) {
+        for route in &mut self.routes {
+            route.requires_auth = !route.info.public;
+        }
+    }
+
+    /// Checks a response to `method` and `path` against the matched route's
+    /// documentation, returning what doesn't match.
+    ///
+    /// Responses that no route matched, such as fallbacks, aren't checked.
+    pub(crate) fn check(
+        &self,
+        method: &Method,
+        path: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &Bytes,
+    ) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let path = path.split('?').next().unwrap_or(path);
+        let Match::Found { index, .. } = self.tree.find(method, path) else {
+            return Ok(());
+        };
+        self.observed.lock().unwrap()[index].insert(status.as_u16());
+
+        let route = &self.routes[index];
+        let violations = route.violations(method, status, headers, body);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "{} {} ({}) broke its documented contract with a {} response:\n  - {}",
+            route.info.method,
+            route.info.path,
+            route.info.handler_name,
+            status,
+            violations.join("\n  - ")
+        ))
+    }
+
+    pub(crate) fn coverage(&self) -> Vec<RouteCoverage> {
+        let observed = self.observed.lock().unwrap();
+        self.routes
+            .iter()
+            .zip(observed.iter())
+            .map(|(route, observed)| RouteCoverage {
+                method: route.info.method.clone(),
+                path: route.info.path.clone(),
+                handler_name: route.info.handler_name.clone(),
+                documented: route.documented().into_iter().collect(),
+                observed: observed.iter().copied().collect(),
+            })
+            .collect()
+    }
+}
+
+impl Drop for Contract {
+    fn drop(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        let Some(report) = std::env::var_os(REPORT_ENV) else {
+            return;
+        };
+        let mut lines = String::new();
+        for coverage in self.coverage() {
+            if let Ok(line) = serde_json::to_string(&coverage) {
+                lines.push_str(&line);
+                lines.push('\n');
+            }
+        }
+        // One write per client, so concurrent test binaries don't interleave lines
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&report)
+            .and_then(|mut file| file.write_all(lines.as_bytes()));
+        if let Err(e) = written {
+            eprintln!("failed to write the contract report: {}", e);
+        }
+    }
+}
+
+/// Errors Rapina itself answers routes with: extractor rejections, body
+/// limits and rate limiting. They're accepted without being documented.
+const FRAMEWORK_ERRORS: &[(u16, &str)] = &[
+    (400, "BAD_REQUEST"),
+    (413, "PAYLOAD_TOO_LARGE"),
+    (422, "VALIDATION_ERROR"),
+    (429, "RATE_LIMITED"),
+];
+
+impl RouteContract {
+    /// Error codes documented per status code.
+    fn error_codes(&self) -> BTreeMap<u16, BTreeSet<&str>> {
+        let mut codes: BTreeMap<u16, BTreeSet<&str>> = BTreeMap::new();
+        for error in &self.info.error_responses {
+            codes.entry(error.status).or_default().insert(error.code);
+        }
+        if self.requires_auth {
+            codes.entry(401).or_default().insert("UNAUTHORIZED");
+        }
+        codes
+    }
+
+    fn documented(&self) -> BTreeSet<u16> {
+        let mut documented: BTreeSet<u16> = self.error_codes().into_keys().collect();
+        documented.insert(200);
+        documented
+    }
+
+    fn violations(
+        &self,
+        method: &Method,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &Bytes,
+    ) -> Vec<String> {
+        let mut violations = Vec::new();
+        let mut codes = self.error_codes();
+        for (status, code) in FRAMEWORK_ERRORS {
+            codes.entry(*status).or_default().insert(code);
+        }
+        let has_body = *method != Method::HEAD
+            && status != StatusCode::NO_CONTENT
+            && status != StatusCode::NOT_MODIFIED;
+
+        if status.is_success() {
+            if let Some(schema) = &self.info.response_schema
+                && has_body
+            {
+                let content_type = self
+                    .info
+                    .response_content_type
+                    .as_deref()
+                    .unwrap_or("application/json");
+                check_body(&mut violations, schema, content_type, headers, body);
+            }
+        } else if status.is_client_error() || status.is_server_error() {
+            let documented_codes = codes.get(&status.as_u16());
+            if documented_codes.is_none() {
+                violations.push(format!(
+                    "status {} is not documented (documented: {})",
+                    status.as_u16(),
+                    join(self.documented())
+                ));
+            }
+            if has_body
+                && let Some(error) = check_body(
+                    &mut violations,
+                    &error_response_schema(),
+                    "application/json",
+                    headers,
+                    body,
+                )
+                && let Some(documented_codes) = documented_codes
+                && let Some(code) = error["error"]["code"].as_str()
+                && !documented_codes.contains(code)
+            {
+                violations.push(format!(
+                    "error code `{}` is not documented for status {} (documented: {})",
+                    code,
+                    status.as_u16(),
+                    join(documented_codes)
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Checks the content type and, for JSON, the body against `schema`.
+/// Returns the parsed body when it matches.
+fn check_body(
+    violations: &mut Vec<String>,
+    schema: &serde_json::Value,
+    content_type: &str,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Option<serde_json::Value> {
+    let actual = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(essence);
+    let expected = essence(content_type);
+    match actual {
+        Some(actual) if actual == expected => {}
+        Some(actual) => {
+            violations.push(format!(
+                "content type is `{}`, documented as `{}`",
+                actual, expected
+            ));
+            return None;
+        }
+        None => {
+            violations.push(format!("no content type, documented as `{}`", expected));
+            return None;
+        }
+    }
+    if expected != "application/json" && !expected.ends_with("+json") {
+        return None;
+    }
+
+    let value: serde_json::Value = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) => {
+            violations.push(format!("body is not valid JSON: {}", e));
+            return None;
+        }
+    };
+    match schema::validate(schema, &value) {
+        Ok(()) => Some(value),
+        Err(e) => {
+            violations.push(format!("body doesn't match the documented schema at {}", e));
+            None
+        }
+    }
+}
+
+/// The media type without parameters, lowercased.
+fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn join<T: ToString>(items: impl IntoIterator<Item = T>) -> String {
+    items
+        .into_iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Rapina's own endpoints, which aren't part of the documented API.
+fn is_internal(path: &str) -> bool {
+    path.starts_with("/__rapina") || path.starts_with("/.__rapina")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorVariant;
+    use http::HeaderValue;
+
+    fn contract() -> Contract {
+        let mut route = RouteInfo::new(
+            "GET",
+            "/users/:id",
+            "get_user",
+            Some(serde_json::json!({
+                "type": "object",
+                "required": ["id"],
+                "properties": {"id": {"type": "integer"}}
+            })),
+            vec![ErrorVariant {
+                status: 404,
+                code: "NOT_FOUND",
+                description: "User not found",
+            }],
+        );
+        route.public = true;
+        let mut contract = Contract::new(vec![route]);
+        contract.set_enabled(true);
+        contract
+    }
+
+    fn json_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        headers
+    }
+
+    fn check(contract: &Contract, status: u16, body: &'static str) -> Result<(), String> {
+        contract.check(
+            &Method::GET,
+            "/users/1?full=true",
+            StatusCode::from_u16(status).unwrap(),
+            &json_headers(),
+            &Bytes::from(body),
+        )
+    }
+
+    #[test]
+    fn test_accepts_documented_responses() {
+        let contract = contract();
+        assert_eq!(check(&contract, 200, r#"{"id":1}"#), Ok(()));
+        assert_eq!(
+            check(
+                &contract,
+                404,
+                r#"{"error":{"code":"NOT_FOUND","message":"no"},"trace_id":"t"}"#
+            ),
+            Ok(())
+        );
+        // Unmatched paths aren't checked
+        let unmatched = contract.check(
+            &Method::GET,
+            "/other",
+            StatusCode::IM_A_TEAPOT,
+            &HeaderMap::new(),
+            &Bytes::new(),
+        );
+        assert_eq!(unmatched, Ok(()));
+    }
+
+    #[test]
+    fn test_reports_violations() {
+        let contract = contract();
+
+        let error = check(&contract, 200, r#"{"id":"1"}"#).unwrap_err();
+        assert!(error.starts_with("GET /users/:id (get_user) broke its documented contract"));
+        assert!(
+            error.contains("at $.id: expected integer, got string"),
+            "{}",
+            error
+        );
+
+        let error = check(
+            &contract,
+            409,
+            r#"{"error":{"code":"CONFLICT","message":"no"},"trace_id":"t"}"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("status 409 is not documented (documented: 200, 404)"));
+
+        let error = check(
+            &contract,
+            404,
+            r#"{"error":{"code":"GONE","message":"no"},"trace_id":"t"}"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("error code `GONE` is not documented for status 404"));
+
+        let error = contract
+            .check(
+                &Method::GET,
+                "/users/1",
+                StatusCode::OK,
+                &HeaderMap::new(),
+                &Bytes::from("{}"),
+            )
+            .unwrap_err();
+        assert!(error.contains("no content type, documented as `application/json`"));
+    }
+
+    #[test]
+    fn test_accepts_framework_errors() {
+        let contract = contract();
+        for (status, code) in [
+            (400, "BAD_REQUEST"),
+            (413, "PAYLOAD_TOO_LARGE"),
+            (422, "VALIDATION_ERROR"),
+            (429, "RATE_LIMITED"),
+        ] {
+            let body = format!(
+                r#"{{"error":{{"code":"{}","message":"no"}},"trace_id":"t"}}"#,
+                code
+            );
+            let result = contract.check(
+                &Method::GET,
+                "/users/1",
+                StatusCode::from_u16(status).unwrap(),
+                &json_headers(),
+                &Bytes::from(body),
+            );
+            assert_eq!(result, Ok(()), "{}", status);
+        }
+
+        let error = check(
+            &contract,
+            422,
+            r#"{"error":{"code":"CONFLICT","message":"no"},"trace_id":"t"}"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("error code `CONFLICT` is not documented for status 422"));
+    }
+
+    #[test]
+    fn test_coverage() {
+        let mut contract = contract();
+        contract.require_auth();
+        check(&contract, 201, r#"{"id":1}"#).unwrap();
+
+        let coverage = contract.coverage();
+        assert_eq!(coverage[0].documented, vec![200, 404]);
+        assert_eq!(coverage[0].observed, vec![201]);
+        assert_eq!(coverage[0].missing(), vec![404]);
+    }
+
+    #[test]
+    fn test_disabled_contract_checks_nothing() {
+        let mut contract = contract();
+        contract.set_enabled(false);
+        assert_eq!(check(&contract, 500, "oops"), Ok(()));
+        assert!(contract.coverage()[0].observed.is_empty());
+    }
+}
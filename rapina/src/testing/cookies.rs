@@ -0,0 +1,284 @@
+//! Cookie storage for the test client.
+
+use std::time::{Duration, SystemTime};
+
+/// Cookies set by the application, sent back on later requests.
+///
+/// Follows the parts of RFC 6265 that matter against a single host:
+/// `Path` scoping and expiry through `Max-Age` or `Expires`. `Domain`,
+/// `Secure` and `SameSite` are ignored.
+#[derive(Debug, Default)]
+pub(crate) struct CookieJar {
+    cookies: Vec<StoredCookie>,
+}
+
+#[derive(Debug)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    path: String,
+    expires: Option<SystemTime>,
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+impl CookieJar {
+    /// Stores a cookie from a `Set-Cookie` header received for `request_path`.
+    ///
+    /// A cookie that is already expired removes the stored one.
+    pub(crate) fn store(&mut self, set_cookie: &str, request_path: &str) {
+        let mut attributes = set_cookie.split(';');
+        let Some((name, value)) = attributes.next().and_then(|pair| pair.split_once('=')) else {
+            return;
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+
+        let now = SystemTime::now();
+        let mut path = None;
+        let mut max_age = None;
+        let mut expires = None;
+        for attribute in attributes {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "path" if value.starts_with('/') => path = Some(value.to_string()),
+                "max-age" => {
+                    // Zero or negative values expire the cookie right away
+                    max_age = value
+                        .parse::<i64>()
+                        .ok()
+                        .map(|secs| match u64::try_from(secs) {
+                            Ok(secs) if secs > 0 => now + Duration::from_secs(secs),
+                            _ => SystemTime::UNIX_EPOCH,
+                        })
+                }
+                "expires" => expires = parse_cookie_date(value),
+                _ => {}
+            }
+        }
+
+        let cookie = StoredCookie {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            path: path.unwrap_or_else(|| default_path(request_path)),
+            // Max-Age wins over Expires
+            expires: max_age.or(expires),
+        };
+        self.cookies
+            .retain(|stored| stored.name != cookie.name || stored.path != cookie.path);
+        if !cookie.is_expired(now) {
+            self.cookies.push(cookie);
+        }
+    }
+
+    /// Returns the `Cookie` header value for a request to `path`, if any
+    /// cookie applies.
+    pub(crate) fn header_for(&self, path: &str) -> Option<String> {
+        let now = SystemTime::now();
+        let mut cookies: Vec<&StoredCookie> = self
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now) && path_matches(&cookie.path, path))
+            .collect();
+        // More specific paths first, as browsers do
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        let header = cookies
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Returns the value of the named cookie.
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        let now = SystemTime::now();
+        self.cookies
+            .iter()
+            .find(|cookie| cookie.name == name && !cookie.is_expired(now))
+            .map(|cookie| cookie.value.as_str())
+    }
+
+    /// Stores a cookie sent on every path.
+    pub(crate) fn set(&mut self, name: &str, value: &str) {
+        self.store(&format!("{}={}; Path=/", name, value), "/");
+    }
+
+    /// Removes every cookie.
+    pub(crate) fn clear(&mut self) {
+        self.cookies.clear();
+    }
+}
+
+/// The path a cookie gets without a `Path` attribute: the request path up
+/// to, but not including, its last `/`.
+fn default_path(request_path: &str) -> String {
+    let path = request_path.split('?').next().unwrap_or("");
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => path[..end].to_string(),
+    }
+}
+
+fn path_matches(cookie_path: &str, request_path: &str) -> bool {
+    let request_path = request_path.split('?').next().unwrap_or("");
+    match request_path.strip_prefix(cookie_path) {
+        Some(rest) => rest.is_empty() || cookie_path.ends_with('/') || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Parses an `Expires` value with the lenient algorithm of RFC 6265
+/// section 5.1.1, which accepts the date formats found in the wild.
+fn parse_cookie_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+    let tokens = value
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != ':')
+        .filter(|token| !token.is_empty());
+    for token in tokens {
+        let digits = |max_len: usize| {
+            (token.len() <= max_len && token.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| token.parse::<u64>().ok())
+                .flatten()
+        };
+        if time.is_none() && token.contains(':') {
+            let parts: Vec<u64> = token
+                .split(':')
+                .filter(|part| (1..=2).contains(&part.len()))
+                .filter_map(|part| part.parse().ok())
+                .collect();
+            if let [hour, minute, second] = parts[..] {
+                time = Some((hour, minute, second));
+                continue;
+            }
+        }
+        if day.is_none()
+            && let Some(value) = digits(2)
+        {
+            day = Some(value);
+        } else if month.is_none()
+            && let Some(index) = token.get(..3).and_then(|prefix| {
+                MONTHS
+                    .iter()
+                    .position(|month| month.eq_ignore_ascii_case(prefix))
+            })
+        {
+            month = Some(index as u64 + 1);
+        } else if year.is_none()
+            && token.len() >= 2
+            && let Some(value) = digits(4)
+        {
+            year = Some(value);
+        }
+    }
+
+    let (hour, minute, second) = time?;
+    let (day, month, mut year) = (day?, month?, year?);
+    match year {
+        0..=69 => year += 2000,
+        70..=99 => year += 1900,
+        _ => {}
+    }
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if year < 1601 || day == 0 || day > month_days || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    // Days since the epoch of the civil date, counting years from March
+    let (y, m) = if month <= 2 {
+        (year as i64 - 1, month + 9)
+    } else {
+        (year as i64, month - 3)
+    };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m as i64 + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let secs = days * 86_400 + (hour * 3600 + minute * 60 + second) as i64;
+    // Dates before the epoch have expired all the same
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).unwrap_or(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stores_and_sends_cookies() {
+        let mut jar = CookieJar::default();
+        jar.store("session=abc; Path=/; HttpOnly", "/login");
+        jar.store("theme=dark", "/settings/page");
+
+        assert_eq!(jar.get("session"), Some("abc"));
+        assert_eq!(jar.header_for("/users").as_deref(), Some("session=abc"));
+        assert_eq!(
+            jar.header_for("/settings/page?x=1").as_deref(),
+            Some("theme=dark; session=abc")
+        );
+    }
+
+    #[test]
+    fn test_replaces_and_expires_cookies() {
+        let mut jar = CookieJar::default();
+        jar.store("session=abc; Path=/", "/");
+        jar.store("session=def; Path=/", "/");
+        assert_eq!(jar.header_for("/").as_deref(), Some("session=def"));
+
+        jar.store("session=; Path=/; Max-Age=0", "/");
+        assert_eq!(jar.get("session"), None);
+
+        jar.store("token=x; Expires=Thu, 01 Jan 1970 00:00:00 GMT", "/");
+        assert_eq!(jar.header_for("/"), None);
+    }
+
+    #[test]
+    fn test_path_matching() {
+        assert!(path_matches("/", "/anything"));
+        assert!(path_matches("/api", "/api"));
+        assert!(path_matches("/api", "/api/users"));
+        assert!(!path_matches("/api", "/apiv2"));
+        assert_eq!(default_path("/a/b/c"), "/a/b");
+        assert_eq!(default_path("/login"), "/");
+    }
+
+    #[test]
+    fn test_parses_cookie_dates() {
+        let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777);
+        for date in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+        ] {
+            assert_eq!(parse_cookie_date(date), Some(expected), "{}", date);
+        }
+        assert_eq!(
+            parse_cookie_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_164_800))
+        );
+        assert_eq!(parse_cookie_date("Fri, 30 Feb 2024 00:00:00 GMT"), None);
+        assert_eq!(parse_cookie_date("Sun, 06 Nov 1994 24:00:00 GMT"), None);
+        assert_eq!(parse_cookie_date("tomorrow"), None);
+    }
+}
@@ -0,0 +1,716 @@
+//! Property-based API fuzzing driven by the application's OpenAPI spec.
+
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+
+use fastrand::Rng;
+use futures_util::FutureExt;
+use http::{Method, StatusCode};
+use percent_encoding::utf8_percent_encode;
+use serde_json::{Value, json};
+
+use crate::app::Rapina;
+use crate::error::ErrorResponse;
+use crate::openapi::{
+    OpenApiOptions, OpenApiVersion, ParameterLocation, Schema, try_build_openapi_spec,
+};
+use crate::router::url::SEGMENT;
+
+use super::TestClient;
+use super::generate::{Generator, simplifications};
+use super::schema::validate_in;
+
+/// Cases generated for each operation unless [`Fuzzer::cases`] is set.
+const DEFAULT_CASES: usize = 64;
+
+/// Requests spent shrinking a failing case.
+const SHRINK_BUDGET: usize = 500;
+
+/// Parameter values that are malformed for most types.
+const MALFORMED_PARAMS: &[&str] = &["fuzz", "1.5", "", "true", "[]", "%"];
+
+/// Generates valid and malformed requests for every route of an
+/// application from its OpenAPI spec, and runs them in-process.
+///
+/// A run fails when a handler panics or answers with a `5xx`, or when
+/// malformed input isn't rejected with a `400` or `422` [`ErrorResponse`]
+/// (or a `404` for a malformed path parameter). With contract checks on,
+/// as under `rapina test --contract`, responses that break their route's
+/// [`contract`](TestClient::contract) fail it too. Failing cases are shrunk
+/// to a minimal request before they are reported.
+///
+/// Path and query parameters and JSON bodies are generated from their
+/// schemas; form bodies are only sent valid, and other bodies not at all.
+/// When the app is configured with [`with_auth`](Rapina::with_auth), every
+/// request is authenticated.
+///
+/// Requires the `fuzz` feature, typically enabled on the dev-dependency.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rapina::testing::Fuzzer;
+///
+/// #[tokio::test]
+/// async fn fuzz_api() {
+///     Fuzzer::new(app()).cases(200).run().await.assert_ok();
+/// }
+/// ```
+pub struct Fuzzer {
+    client: TestClient,
+    /// The spec as JSON, which schema `$ref`s point into.
+    root: Value,
+    endpoints: Vec<Endpoint>,
+    authenticated: bool,
+    cases: usize,
+    seed: u64,
+}
+
+/// An operation of the spec.
+struct Endpoint {
+    method: Method,
+    path: String,
+    params: Vec<Param>,
+    body: Option<Body>,
+}
+
+struct Param {
+    name: String,
+    in_path: bool,
+    required: bool,
+    schema: Value,
+}
+
+struct Body {
+    form: bool,
+    schema: Value,
+}
+
+/// Generated input for an [`Endpoint`].
+#[derive(Clone)]
+struct Case {
+    /// Parameter values as sent, `None` when left out.
+    params: Vec<Option<String>>,
+    body: Option<CaseBody>,
+    /// Whether the input was broken on purpose and must be rejected.
+    malformed: bool,
+}
+
+#[derive(Clone)]
+enum CaseBody {
+    Value(Value),
+    /// Text that isn't valid JSON.
+    Raw(String),
+}
+
+/// Why a case failed; shrinking keeps failures of the same kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FailureKind {
+    Panic,
+    ServerError,
+    Accepted,
+    Unstructured,
+    Contract,
+}
+
+impl Fuzzer {
+    /// Creates a fuzzer for the routes of `app`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the app's OpenAPI spec can't be built.
+    pub fn new(app: Rapina) -> Self {
+        let routes = app.router.routes();
+        let authenticated = app.auth_config.is_some();
+        let options = OpenApiOptions::new()
+            .version(OpenApiVersion::V3_1)
+            .bearer_auth(authenticated);
+        let spec = try_build_openapi_spec("fuzz", "0", &routes, options)
+            .unwrap_or_else(|e| panic!("invalid OpenAPI spec: {}", e));
+
+        let mut endpoints = Vec::new();
+        for (path, item) in &spec.paths {
+            let operations = [
+                (Method::GET, &item.get),
+                (Method::POST, &item.post),
+                (Method::PUT, &item.put),
+                (Method::DELETE, &item.delete),
+                (Method::PATCH, &item.patch),
+                (Method::HEAD, &item.head),
+                (Method::OPTIONS, &item.options),
+            ];
+            for (method, operation) in operations {
+                let Some(operation) = operation else {
+                    continue;
+                };
+                let params = operation
+                    .parameters
+                    .iter()
+                    .filter(|p| !matches!(p.location, ParameterLocation::Header))
+                    .map(|p| {
+                        let in_path = matches!(p.location, ParameterLocation::Path);
+                        let mut schema = p
+                            .schema
+                            .as_ref()
+                            .map_or(json!({"type": "string"}), to_value);
+                        // An empty segment doesn't match the route at all
+                        if in_path
+                            && let Some(object) = schema.as_object_mut()
+                            && object.get("type") == Some(&json!("string"))
+                        {
+                            object.entry("minLength").or_insert(json!(1));
+                        }
+                        Param {
+                            name: p.name.clone(),
+                            in_path,
+                            required: p.required,
+                            schema,
+                        }
+                    })
+                    .collect();
+                let body = operation.request_body.as_ref().and_then(|body| {
+                    let (form, media) = match body.content.get("application/json") {
+                        Some(media) => (false, media),
+                        None => (true, body.content.get("application/x-www-form-urlencoded")?),
+                    };
+                    Some(Body {
+                        form,
+                        schema: to_value(&media.schema),
+                    })
+                });
+                endpoints.push(Endpoint {
+                    method,
+                    path: path.clone(),
+                    params,
+                    body,
+                });
+            }
+        }
+
+        Self {
+            client: TestClient::in_process(app),
+            root: serde_json::to_value(&spec).expect("OpenAPI spec is serializable"),
+            endpoints,
+            authenticated,
+            cases: DEFAULT_CASES,
+            seed: fastrand::u64(..),
+        }
+    }
+
+    /// Sets the number of cases generated for each operation.
+    ///
+    /// Defaults to 64; half of the cases are malformed.
+    pub fn cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Seeds the generator, to reproduce the cases of an earlier run.
+    ///
+    /// Defaults to a random seed, which [`FuzzReport`] includes.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs the cases against every operation.
+    ///
+    /// Each operation stops at its first failure, which is shrunk and
+    /// added to the report.
+    pub async fn run(&self) -> FuzzReport {
+        let mut rng = Rng::with_seed(self.seed);
+        let mut report = FuzzReport {
+            seed: self.seed,
+            requests: 0,
+            failures: Vec::new(),
+        };
+
+        for endpoint in &self.endpoints {
+            for i in 0..self.cases {
+                let case = match i % 2 {
+                    0 => self.valid_case(endpoint, &mut rng),
+                    _ => self
+                        .malformed_case(endpoint, &mut rng)
+                        .unwrap_or_else(|| self.valid_case(endpoint, &mut rng)),
+                };
+                report.requests += 1;
+                if let Err((kind, failure)) = self.check(endpoint, &case).await {
+                    let (failure, requests) = self.shrink(endpoint, case, kind, failure).await;
+                    report.requests += requests;
+                    report.failures.push(*failure);
+                    break;
+                }
+            }
+        }
+        report
+    }
+
+    fn valid_case(&self, endpoint: &Endpoint, rng: &mut Rng) -> Case {
+        let mut generator = Generator::new(&self.root, rng);
+        let params = endpoint
+            .params
+            .iter()
+            .map(|param| {
+                if !param.required && generator.rng().bool() {
+                    return None;
+                }
+                param_text(&generator.valid(&param.schema))
+            })
+            .collect();
+        let body = endpoint
+            .body
+            .as_ref()
+            .map(|body| CaseBody::Value(generator.valid(&body.schema)));
+        Case {
+            params,
+            body,
+            malformed: false,
+        }
+    }
+
+    /// A valid case with one parameter or the body broken.
+    fn malformed_case(&self, endpoint: &Endpoint, rng: &mut Rng) -> Option<Case> {
+        let mut case = self.valid_case(endpoint, rng);
+        case.malformed = true;
+        let json_body = endpoint.body.as_ref().filter(|body| !body.form);
+
+        for _ in 0..8 {
+            let target = rng.usize(..=endpoint.params.len());
+            let Some(param) = endpoint.params.get(target) else {
+                let (Some(body), Some(CaseBody::Value(value))) = (json_body, &case.body) else {
+                    continue;
+                };
+                let broken = if rng.u8(..4) == 0 {
+                    Some(CaseBody::Raw(malformed_json(value, rng)))
+                } else {
+                    Generator::new(&self.root, rng)
+                        .invalid(&body.schema, value)
+                        .map(CaseBody::Value)
+                };
+                if broken.is_some() {
+                    case.body = broken;
+                    return Some(case);
+                }
+                continue;
+            };
+
+            let original = case.params[target].take();
+            if !param.in_path && param.required && rng.bool() {
+                return Some(case);
+            }
+            let value = MALFORMED_PARAMS[rng.usize(..MALFORMED_PARAMS.len())];
+            if !self.param_accepts(param, value) {
+                case.params[target] = Some(value.to_string());
+                return Some(case);
+            }
+            case.params[target] = original;
+        }
+        None
+    }
+
+    /// Whether a parameter value, as sent, matches its schema.
+    ///
+    /// Values are read the way extractors parse them from text: as numbers
+    /// or booleans when they parse as one, and as strings otherwise.
+    fn param_accepts(&self, param: &Param, text: &str) -> bool {
+        let accepts = |value: &Value| validate_in(&self.root, &param.schema, value).is_ok();
+        accepts(&json!(text)) || typed_param(text).is_some_and(|value| accepts(&value))
+    }
+
+    /// Whether every part of a case matches its schema.
+    fn accepts(&self, endpoint: &Endpoint, case: &Case) -> bool {
+        let params = endpoint
+            .params
+            .iter()
+            .zip(&case.params)
+            .all(|(param, value)| match value {
+                Some(text) => self.param_accepts(param, text),
+                None => !param.required,
+            });
+        let body = match (&endpoint.body, &case.body) {
+            (Some(body), Some(CaseBody::Value(value))) => {
+                body.form || validate_in(&self.root, &body.schema, value).is_ok()
+            }
+            (_, Some(CaseBody::Raw(_))) => false,
+            _ => true,
+        };
+        params && body
+    }
+
+    /// Sends a case and checks the response.
+    async fn check(
+        &self,
+        endpoint: &Endpoint,
+        case: &Case,
+    ) -> Result<(), (FailureKind, Box<FuzzFailure>)> {
+        let request = render(endpoint, case);
+        let mut builder = self.client.request(endpoint.method.clone(), &request.uri);
+        if let Some(content_type) = &request.content_type {
+            builder = builder.header("content-type", content_type);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+        if self.authenticated {
+            builder = builder.as_user("fuzz");
+        }
+
+        let fail = |kind, reason: String, status, response| {
+            Err((
+                kind,
+                Box::new(FuzzFailure {
+                    route: format!("{} {}", endpoint.method, endpoint.path),
+                    reason,
+                    request: request.clone(),
+                    status,
+                    response,
+                }),
+            ))
+        };
+
+        let response = match AssertUnwindSafe(builder.send_checked())
+            .catch_unwind()
+            .await
+        {
+            Ok(Ok(response)) => response,
+            Ok(Err(violation)) => {
+                return fail(FailureKind::Contract, violation, None, String::new());
+            }
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                return fail(
+                    FailureKind::Panic,
+                    format!("the handler panicked: {}", message),
+                    None,
+                    String::new(),
+                );
+            }
+        };
+        let status = response.status();
+        if status.is_server_error() {
+            return fail(
+                FailureKind::ServerError,
+                format!("responded with {}", status),
+                Some(status),
+                response.text(),
+            );
+        }
+
+        if !case.malformed || self.accepts(endpoint, case) {
+            return Ok(());
+        }
+        let bad_path = endpoint
+            .params
+            .iter()
+            .zip(&case.params)
+            .any(|(param, value)| {
+                param.in_path
+                    && value
+                        .as_ref()
+                        .is_some_and(|v| !self.param_accepts(param, v))
+            });
+        let rejected = status == StatusCode::BAD_REQUEST
+            || status == StatusCode::UNPROCESSABLE_ENTITY
+            || (bad_path && status == StatusCode::NOT_FOUND);
+        if !rejected {
+            return fail(
+                FailureKind::Accepted,
+                format!("answered malformed input with {}", status),
+                Some(status),
+                response.text(),
+            );
+        }
+        if response.try_json::<ErrorResponse>().is_err() {
+            return fail(
+                FailureKind::Unstructured,
+                format!(
+                    "rejected malformed input with {} but no `ErrorResponse` body",
+                    status
+                ),
+                Some(status),
+                response.text(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Greedily replaces a failing case with smaller ones that fail the
+    /// same way, returning the last failure and the requests sent.
+    async fn shrink(
+        &self,
+        endpoint: &Endpoint,
+        mut case: Case,
+        kind: FailureKind,
+        mut failure: Box<FuzzFailure>,
+    ) -> (Box<FuzzFailure>, usize) {
+        // Any input is a bug when it panics or errors; rejection failures
+        // need input that stays malformed
+        let keep_malformed = matches!(kind, FailureKind::Accepted | FailureKind::Unstructured);
+        let mut requests = 0;
+
+        'shrink: while requests < SHRINK_BUDGET {
+            for candidate in smaller_cases(endpoint, &case) {
+                if keep_malformed && self.accepts(endpoint, &candidate) {
+                    continue;
+                }
+                requests += 1;
+                if let Err((candidate_kind, candidate_failure)) =
+                    self.check(endpoint, &candidate).await
+                    && candidate_kind == kind
+                {
+                    case = candidate;
+                    failure = candidate_failure;
+                    continue 'shrink;
+                }
+                if requests >= SHRINK_BUDGET {
+                    break;
+                }
+            }
+            break;
+        }
+        (failure, requests)
+    }
+}
+
+/// Smaller variants of a case: without optional parts, or with a single
+/// simpler parameter or body.
+fn smaller_cases(endpoint: &Endpoint, case: &Case) -> Vec<Case> {
+    let mut smaller = Vec::new();
+    for (i, value) in case.params.iter().enumerate() {
+        let Some(text) = value else {
+            continue;
+        };
+        if !endpoint.params[i].required {
+            let mut candidate = case.clone();
+            candidate.params[i] = None;
+            smaller.push(candidate);
+        }
+        for simpler in simplifications(&json!(text)) {
+            let mut candidate = case.clone();
+            candidate.params[i] = param_text(&simpler);
+            smaller.push(candidate);
+        }
+    }
+    match &case.body {
+        Some(CaseBody::Value(value)) => {
+            for simpler in simplifications(value) {
+                let mut candidate = case.clone();
+                candidate.body = Some(CaseBody::Value(simpler));
+                smaller.push(candidate);
+            }
+        }
+        Some(CaseBody::Raw(text)) => {
+            for simpler in simplifications(&json!(text)) {
+                if let Some(text) = simpler.as_str()
+                    && serde_json::from_str::<Value>(text).is_err()
+                {
+                    let mut candidate = case.clone();
+                    candidate.body = Some(CaseBody::Raw(text.to_string()));
+                    smaller.push(candidate);
+                }
+            }
+        }
+        None => {}
+    }
+    smaller
+}
+
+/// Builds the request a case is sent as.
+fn render(endpoint: &Endpoint, case: &Case) -> FuzzRequest {
+    let mut path = endpoint.path.clone();
+    let mut query = Vec::new();
+    for (param, value) in endpoint.params.iter().zip(&case.params) {
+        if param.in_path {
+            let segment = value.as_deref().unwrap_or("");
+            let segment = utf8_percent_encode(segment, SEGMENT).to_string();
+            path = path.replace(&format!("{{{}}}", param.name), &segment);
+        } else if let Some(value) = value {
+            query.push((param.name.as_str(), value.as_str()));
+        }
+    }
+    if !query.is_empty() {
+        path.push('?');
+        path.push_str(&serde_urlencoded::to_string(&query).unwrap_or_default());
+    }
+
+    let (content_type, body) = match (&endpoint.body, &case.body) {
+        (Some(body), Some(CaseBody::Value(value))) if body.form => {
+            let fields: Vec<(&String, String)> = value
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(|(name, value)| Some((name, param_text(value)?)))
+                .collect();
+            (
+                "application/x-www-form-urlencoded",
+                serde_urlencoded::to_string(&fields).unwrap_or_default(),
+            )
+        }
+        (_, Some(CaseBody::Value(value))) => ("application/json", value.to_string()),
+        (_, Some(CaseBody::Raw(text))) => ("application/json", text.clone()),
+        (_, None) => {
+            return FuzzRequest {
+                method: endpoint.method.clone(),
+                uri: path,
+                content_type: None,
+                body: None,
+            };
+        }
+    };
+    FuzzRequest {
+        method: endpoint.method.clone(),
+        uri: path,
+        content_type: Some(content_type.to_string()),
+        body: Some(body),
+    }
+}
+
+fn to_value(schema: &Schema) -> Value {
+    match schema {
+        Schema::Ref { reference } => json!({ "$ref": reference }),
+        Schema::Inline(schema) => schema.clone(),
+    }
+}
+
+/// A value as text in a path, query string or form; `None` for `null`.
+fn param_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Parameter text as the number or boolean it parses as.
+fn typed_param(text: &str) -> Option<Value> {
+    if let Ok(n) = text.parse::<i64>() {
+        Some(json!(n))
+    } else if let Ok(n) = text.parse::<u64>() {
+        Some(json!(n))
+    } else if let Ok(n) = text.parse::<f64>()
+        && n.is_finite()
+    {
+        Some(json!(n))
+    } else {
+        text.parse::<bool>().ok().map(Value::Bool)
+    }
+}
+
+/// `value` as JSON text that no longer parses.
+fn malformed_json(value: &Value, rng: &mut Rng) -> String {
+    let text = value.to_string();
+    let broken = match rng.u8(..3) {
+        0 if text.len() > 1 => text[..text.len() - 1].to_string(),
+        1 => format!("{},", text),
+        _ => format!("{{{}", text),
+    };
+    if serde_json::from_str::<Value>(&broken).is_ok() {
+        "{".to_string()
+    } else {
+        broken
+    }
+}
+
+/// The result of a [`Fuzzer`] run.
+#[derive(Debug)]
+pub struct FuzzReport {
+    /// The seed the cases were generated from.
+    pub seed: u64,
+    /// Requests sent, including while shrinking.
+    pub requests: usize,
+    /// The shrunk failure of each failing operation.
+    pub failures: Vec<FuzzFailure>,
+}
+
+impl FuzzReport {
+    /// Returns true if no operation failed.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Panics with the failures, if any.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{}", self);
+        }
+    }
+}
+
+impl fmt::Display for FuzzReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(
+                f,
+                "no failures in {} requests (seed {})",
+                self.requests, self.seed
+            );
+        }
+        writeln!(
+            f,
+            "{} failing route(s) in {} requests (reproduce with `.seed({})`):",
+            self.failures.len(),
+            self.requests,
+            self.seed
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+/// A failing case, shrunk to a minimal request.
+#[derive(Debug, Clone)]
+pub struct FuzzFailure {
+    /// The method and path pattern of the operation, e.g. `GET /users/{id}`.
+    pub route: String,
+    /// What went wrong.
+    pub reason: String,
+    /// The request that fails.
+    pub request: FuzzRequest,
+    /// The response status; `None` when the handler panicked.
+    pub status: Option<StatusCode>,
+    /// The response body.
+    pub response: String,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: {}", self.route, self.reason)?;
+        for line in self.request.to_string().lines() {
+            writeln!(f, "    {}", line)?;
+        }
+        if let Some(status) = self.status {
+            writeln!(f, "  response: {} {}", status, self.response)?;
+        }
+        Ok(())
+    }
+}
+
+/// A generated request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzRequest {
+    pub method: Method,
+    /// The path and query string.
+    pub uri: String,
+    pub content_type: Option<String>,
+    pub body: Option<String>,
+}
+
+impl fmt::Display for FuzzRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", self.method, self.uri)?;
+        if let Some(content_type) = &self.content_type {
+            writeln!(f, "content-type: {}", content_type)?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, "\n{}", body)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,662 @@
+//! Random JSON values from JSON Schemas, for fuzzing.
+//!
+//! [`Generator::valid`] builds values a schema accepts and
+//! [`Generator::invalid`] breaks them, while [`simplifications`] lists
+//! smaller variants of a value to shrink failing cases with.
+
+use fastrand::Rng;
+use serde_json::{Map, Value, json};
+
+use super::schema::validate_in;
+
+/// Nesting past which optional properties and array items are left out.
+const MAX_DEPTH: usize = 6;
+
+/// Nesting past which generation gives up, for recursive schemas.
+const MAX_REF_DEPTH: usize = 32;
+
+/// Characters mixed into generated strings to exercise decoding.
+const SPECIAL_CHARS: &[char] = &[
+    ' ', '"', '\'', '\\', '/', '%', '?', '&', '#', '+', '=', '<', '\n', 'é', '日', '🦀',
+];
+
+/// Values put in place of valid ones of another type to break them.
+fn wrong_values() -> [Value; 7] {
+    [
+        json!("fuzz"),
+        json!(-1),
+        json!(1.5),
+        json!(true),
+        Value::Null,
+        json!([]),
+        json!({}),
+    ]
+}
+
+/// The JSON type of `value`, telling integers from other numbers.
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+pub(crate) struct Generator<'a> {
+    /// The document `$ref`s point into.
+    root: &'a Value,
+    rng: &'a mut Rng,
+}
+
+impl<'a> Generator<'a> {
+    pub(crate) fn new(root: &'a Value, rng: &'a mut Rng) -> Self {
+        Self { root, rng }
+    }
+
+    pub(crate) fn rng(&mut self) -> &mut Rng {
+        self.rng
+    }
+
+    /// A random value `schema` accepts.
+    pub(crate) fn valid(&mut self, schema: &Value) -> Value {
+        self.value(schema, 0)
+    }
+
+    /// A variant of `value` that `schema` rejects, if one is found.
+    ///
+    /// Replaces a random part of the value with one of another type, or
+    /// removes an object property or array item. Values are never broken
+    /// by bounds alone (`minLength`, `maximum`, ...), which plain
+    /// deserialization doesn't enforce.
+    pub(crate) fn invalid(&mut self, schema: &Value, value: &Value) -> Option<Value> {
+        let mut pointers = Vec::new();
+        collect_pointers(value, String::new(), &mut pointers);
+
+        for _ in 0..32 {
+            let pointer = &pointers[self.rng.usize(..pointers.len())];
+            let mut candidate = value.clone();
+            if !pointer.is_empty() && self.rng.u8(..3) == 0 {
+                remove(&mut candidate, pointer);
+            } else if let Some(slot) = candidate.pointer_mut(pointer) {
+                let wrong = wrong_values();
+                let replacement = &wrong[self.rng.usize(..wrong.len())];
+                if json_type(replacement) == json_type(slot) {
+                    continue;
+                }
+                *slot = replacement.clone();
+            }
+            if validate_in(self.root, schema, &candidate).is_err() {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    fn value(&mut self, schema: &Value, depth: usize) -> Value {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Bool(false) => return Value::Null,
+            _ => return self.scalar(),
+        };
+        if depth > MAX_REF_DEPTH {
+            return Value::Null;
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let root = self.root;
+            return match reference
+                .strip_prefix('#')
+                .and_then(|pointer| root.pointer(pointer))
+            {
+                Some(target) => self.value(target, depth + 1),
+                None => Value::Null,
+            };
+        }
+        if let Some(value) = schema.get("const") {
+            return value.clone();
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array)
+            && !options.is_empty()
+        {
+            return options[self.rng.usize(..options.len())].clone();
+        }
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for sub in all {
+                match self.value(sub, depth + 1) {
+                    Value::Object(properties) => merged.extend(properties),
+                    other => return other,
+                }
+            }
+            return Value::Object(merged);
+        }
+        for combinator in ["anyOf", "oneOf"] {
+            if let Some(alternatives) = schema.get(combinator).and_then(Value::as_array)
+                && !alternatives.is_empty()
+            {
+                let alternative = &alternatives[self.rng.usize(..alternatives.len())];
+                return self.value(alternative, depth + 1);
+            }
+        }
+
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => inferred_type(schema).into_iter().collect(),
+        };
+        // Optional values are mostly present
+        let non_null: Vec<&str> = types.iter().copied().filter(|t| *t != "null").collect();
+        let type_name = if non_null.is_empty() {
+            types.first().copied()
+        } else if non_null.len() < types.len() && self.rng.u8(..5) == 0 {
+            Some("null")
+        } else {
+            Some(non_null[self.rng.usize(..non_null.len())])
+        };
+
+        match type_name {
+            Some("null") => Value::Null,
+            Some("boolean") => Value::Bool(self.rng.bool()),
+            Some("integer") => self.integer(schema),
+            Some("number") => self.number(schema),
+            Some("string") => Value::String(self.string(schema)),
+            Some("array") => self.array(schema, depth),
+            Some("object") => self.object(schema, depth),
+            _ => self.scalar(),
+        }
+    }
+
+    fn scalar(&mut self) -> Value {
+        match self.rng.u8(..3) {
+            0 => Value::String(self.text(0, 8)),
+            1 => json!(self.rng.i64(-100..=100)),
+            _ => Value::Bool(self.rng.bool()),
+        }
+    }
+
+    fn integer(&mut self, schema: &Map<String, Value>) -> Value {
+        let (mut lo, mut hi) = integer_range(schema.get("format").and_then(Value::as_str));
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+        if let Some(min) = bound("minimum") {
+            lo = lo.max(min.ceil() as i128);
+        }
+        if let Some(min) = bound("exclusiveMinimum") {
+            lo = lo.max(min.floor() as i128 + 1);
+        }
+        if let Some(max) = bound("maximum") {
+            hi = hi.min(max.floor() as i128);
+        }
+        if let Some(max) = bound("exclusiveMaximum") {
+            hi = hi.min(max.ceil() as i128 - 1);
+        }
+        if lo > hi {
+            return json!(lo as i64);
+        }
+
+        // Mostly small numbers, sometimes the bounds
+        let mut n = match self.rng.u8(..8) {
+            0 => lo,
+            1 => hi,
+            _ => {
+                let (from, to) = (lo.max(-1000), hi.min(1000));
+                let (from, to) = if from <= to {
+                    (from, to)
+                } else {
+                    (lo, hi.min(lo + 1000))
+                };
+                from + self.rng.u64(..=(to - from) as u64) as i128
+            }
+        };
+        if let Some(step) = schema.get("multipleOf").and_then(Value::as_u64)
+            && step > 0
+        {
+            let step = step as i128;
+            n -= n.rem_euclid(step);
+            if n < lo {
+                n += step;
+            }
+        }
+
+        if n >= 0 {
+            json!(n as u64)
+        } else {
+            json!(n as i64)
+        }
+    }
+
+    fn number(&mut self, schema: &Map<String, Value>) -> Value {
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+        let lo = bound("minimum")
+            .or(bound("exclusiveMinimum").map(|min| min + 0.001))
+            .unwrap_or(-1e6);
+        let hi = bound("maximum")
+            .or(bound("exclusiveMaximum").map(|max| max - 0.001))
+            .unwrap_or(1e6);
+        if lo > hi {
+            return json!(lo);
+        }
+        let n = match self.rng.u8(..8) {
+            0 => lo,
+            1 => hi,
+            _ => {
+                let (from, to) = (lo.max(-1000.0), hi.min(1000.0));
+                let (from, to) = if from <= to { (from, to) } else { (lo, hi) };
+                let n = from + self.rng.f64() * (to - from);
+                ((n * 1000.0).round() / 1000.0).clamp(lo, hi)
+            }
+        };
+        json!(n)
+    }
+
+    fn string(&mut self, schema: &Map<String, Value>) -> String {
+        if let Some(format) = schema.get("format").and_then(Value::as_str)
+            && let Some(value) = self.formatted(format)
+        {
+            return value;
+        }
+
+        let min = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max = schema
+            .get("maxLength")
+            .and_then(Value::as_u64)
+            .map_or(min + 16, |max| (max as usize).min(min + 16))
+            .max(min);
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str)
+            && let Ok(regex) = regex::Regex::new(pattern)
+        {
+            // Random guesses, which are enough for simple character classes
+            for _ in 0..512 {
+                let candidate = self.text(min, max);
+                if regex.is_match(&candidate) {
+                    return candidate;
+                }
+            }
+        }
+        self.text(min, max)
+    }
+
+    fn formatted(&mut self, format: &str) -> Option<String> {
+        let value = match format {
+            "uuid" => {
+                let mut bytes = [0u8; 16];
+                bytes.iter_mut().for_each(|byte| *byte = self.rng.u8(..));
+                uuid::Builder::from_random_bytes(bytes)
+                    .into_uuid()
+                    .to_string()
+            }
+            "date-time" => format!("{}T{}Z", self.date(), self.time()),
+            "date" => self.date(),
+            "time" => self.time(),
+            "email" => format!("{}@example.com", self.word()),
+            "uri" | "url" => format!("https://example.com/{}", self.word()),
+            "hostname" => format!("{}.example.com", self.word()),
+            "ipv4" => format!(
+                "{}.{}.{}.{}",
+                self.rng.u8(..),
+                self.rng.u8(..),
+                self.rng.u8(..),
+                self.rng.u8(..)
+            ),
+            "ipv6" => "::1".to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn date(&mut self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}",
+            self.rng.u32(1970..=2100),
+            self.rng.u32(1..=12),
+            self.rng.u32(1..=28)
+        )
+    }
+
+    fn time(&mut self) -> String {
+        format!(
+            "{:02}:{:02}:{:02}",
+            self.rng.u32(..24),
+            self.rng.u32(..60),
+            self.rng.u32(..60)
+        )
+    }
+
+    /// A short lowercase word.
+    fn word(&mut self) -> String {
+        let len = self.rng.usize(1..=8);
+        (0..len).map(|_| self.rng.lowercase()).collect()
+    }
+
+    /// A string of `min..=max` characters from a random alphabet, which is
+    /// sometimes sprinkled with characters that need escaping.
+    fn text(&mut self, min: usize, max: usize) -> String {
+        let len = self.rng.usize(min..=max);
+        let alphabet = self.rng.u8(..5);
+        (0..len)
+            .map(|_| match alphabet {
+                0 => self.rng.lowercase(),
+                1 => self.rng.uppercase(),
+                2 => self.rng.digit(10),
+                3 => self.rng.alphanumeric(),
+                _ if self.rng.u8(..5) == 0 => SPECIAL_CHARS[self.rng.usize(..SPECIAL_CHARS.len())],
+                _ => self.rng.alphanumeric(),
+            })
+            .collect()
+    }
+
+    fn array(&mut self, schema: &Map<String, Value>, depth: usize) -> Value {
+        let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max = schema
+            .get("maxItems")
+            .and_then(Value::as_u64)
+            .map_or(min + 3, |max| (max as usize).min(min + 3))
+            .max(min);
+        let prefix = schema
+            .get("prefixItems")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let items = schema.get("items");
+
+        let len = if !prefix.is_empty() {
+            prefix.len().max(min)
+        } else if depth >= MAX_DEPTH {
+            min
+        } else {
+            self.rng.usize(min..=max)
+        };
+        let unique = schema.get("uniqueItems") == Some(&Value::Bool(true));
+        let mut values = Vec::with_capacity(len);
+        for i in 0..len {
+            let item = match (prefix.get(i), items) {
+                (Some(item), _) => self.value(item, depth + 1),
+                (None, Some(Value::Bool(false))) => break,
+                (None, Some(item)) => self.value(item, depth + 1),
+                (None, None) => self.scalar(),
+            };
+            if !unique || !values.contains(&item) {
+                values.push(item);
+            }
+        }
+        Value::Array(values)
+    }
+
+    fn object(&mut self, schema: &Map<String, Value>, depth: usize) -> Value {
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut object = Map::new();
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(properties) = properties {
+            for (name, property) in properties {
+                if required.contains(&name.as_str()) || (depth < MAX_DEPTH && self.rng.bool()) {
+                    let value = self.value(property, depth + 1);
+                    object.insert(name.clone(), value);
+                }
+            }
+        }
+        for name in required {
+            if !object.contains_key(name) {
+                let value = self.scalar();
+                object.insert(name.to_string(), value);
+            }
+        }
+        // Maps, e.g. `HashMap<String, T>`
+        if properties.is_none()
+            && depth < MAX_DEPTH
+            && let Some(values @ Value::Object(_)) = schema.get("additionalProperties")
+        {
+            for _ in 0..self.rng.usize(..=2) {
+                let key = self.word();
+                let value = self.value(values, depth + 1);
+                object.insert(key, value);
+            }
+        }
+        Value::Object(object)
+    }
+}
+
+/// The type of a schema without `type`, from the keywords it uses.
+fn inferred_type(schema: &Map<String, Value>) -> Option<&'static str> {
+    let has = |keys: &[&str]| keys.iter().any(|key| schema.contains_key(*key));
+    if has(&["properties", "required", "additionalProperties"]) {
+        Some("object")
+    } else if has(&["items", "prefixItems", "minItems", "maxItems"]) {
+        Some("array")
+    } else if has(&["minLength", "maxLength", "pattern", "format"]) {
+        Some("string")
+    } else if has(&["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum"]) {
+        Some("number")
+    } else {
+        None
+    }
+}
+
+/// The values of an integer `format`, as emitted by schemars.
+fn integer_range(format: Option<&str>) -> (i128, i128) {
+    match format {
+        Some("int8") => (i8::MIN.into(), i8::MAX.into()),
+        Some("int16") => (i16::MIN.into(), i16::MAX.into()),
+        Some("int32") => (i32::MIN.into(), i32::MAX.into()),
+        Some("uint8") => (0, u8::MAX.into()),
+        Some("uint16") => (0, u16::MAX.into()),
+        Some("uint32") => (0, u32::MAX.into()),
+        Some("uint64" | "uint") => (0, u64::MAX.into()),
+        _ => (i64::MIN.into(), i64::MAX.into()),
+    }
+}
+
+/// JSON pointers to every part of `value`, the whole value first.
+fn collect_pointers(value: &Value, pointer: String, pointers: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                collect_pointers(child, format!("{}/{}", pointer, escaped), pointers);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                collect_pointers(child, format!("{}/{}", pointer, i), pointers);
+            }
+        }
+        _ => {}
+    }
+    pointers.push(pointer);
+}
+
+/// Removes the object property or array item at `pointer`.
+fn remove(value: &mut Value, pointer: &str) {
+    let Some((parent, last)) = pointer.rsplit_once('/') else {
+        return;
+    };
+    let key = last.replace("~1", "/").replace("~0", "~");
+    match value.pointer_mut(parent) {
+        Some(Value::Object(object)) => {
+            object.remove(&key);
+        }
+        Some(Value::Array(items)) => {
+            if let Ok(index) = key.parse::<usize>()
+                && index < items.len()
+            {
+                items.remove(index);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Smaller variants of `value`, the most aggressive first.
+///
+/// Every variant is strictly simpler, so repeatedly taking one ends.
+pub(crate) fn simplifications(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Null | Value::Bool(false) => Vec::new(),
+        Value::Bool(true) => vec![Value::Bool(false)],
+        Value::Number(n) => {
+            let mut smaller = Vec::new();
+            if let Some(n) = n.as_i64() {
+                if n != 0 {
+                    smaller.push(json!(0));
+                }
+                if n.abs() > 1 {
+                    smaller.push(json!(n / 2));
+                }
+            } else if let Some(n) = n.as_u64() {
+                smaller.extend([json!(0), json!(n / 2)]);
+            } else if let Some(n) = n.as_f64() {
+                if n != 0.0 {
+                    smaller.push(json!(0));
+                }
+                if n.fract() != 0.0 {
+                    smaller.push(json!(n.trunc()));
+                }
+            }
+            smaller
+        }
+        Value::String(s) => {
+            let chars: Vec<char> = s.chars().collect();
+            let mut smaller = Vec::new();
+            if !chars.is_empty() {
+                smaller.push(json!(""));
+            }
+            if chars.len() > 2 {
+                smaller.push(json!(chars[..chars.len() / 2].iter().collect::<String>()));
+            }
+            if chars.len() > 1 {
+                smaller.push(json!(chars[1..].iter().collect::<String>()));
+                smaller.push(json!(chars[..chars.len() - 1].iter().collect::<String>()));
+            }
+            smaller
+        }
+        Value::Array(items) => {
+            let mut smaller = Vec::new();
+            if !items.is_empty() {
+                smaller.push(json!([]));
+            }
+            if items.len() > 2 {
+                smaller.push(Value::Array(items[..items.len() / 2].to_vec()));
+            }
+            for i in 0..items.len() {
+                let mut fewer = items.clone();
+                fewer.remove(i);
+                smaller.push(Value::Array(fewer));
+            }
+            for (i, item) in items.iter().enumerate() {
+                for simpler in simplifications(item) {
+                    let mut items = items.clone();
+                    items[i] = simpler;
+                    smaller.push(Value::Array(items));
+                }
+            }
+            smaller
+        }
+        Value::Object(object) => {
+            let mut smaller = Vec::new();
+            for key in object.keys() {
+                let mut fewer = object.clone();
+                fewer.remove(key);
+                smaller.push(Value::Object(fewer));
+            }
+            for (key, child) in object {
+                for simpler in simplifications(child) {
+                    let mut object = object.clone();
+                    object.insert(key.clone(), simpler);
+                    smaller.push(Value::Object(object));
+                }
+            }
+            smaller
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::schema::validate;
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct Order {
+        #[schemars(extend("format" = "uuid"))]
+        id: String,
+        quantity: u8,
+        #[schemars(length(min = 2, max = 4))]
+        code: String,
+        note: Option<String>,
+        items: Vec<Item>,
+        status: Status,
+    }
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct Item {
+        price: f64,
+        tags: std::collections::HashMap<String, i32>,
+    }
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    enum Status {
+        Open,
+        Closed { reason: String },
+    }
+
+    fn order_schema() -> Value {
+        serde_json::to_value(schemars::schema_for!(Order)).unwrap()
+    }
+
+    #[test]
+    fn test_generates_valid_values() {
+        let schema = order_schema();
+        let mut rng = Rng::with_seed(7);
+        let mut generator = Generator::new(&schema, &mut rng);
+        for _ in 0..200 {
+            let value = generator.valid(&schema);
+            assert_eq!(validate(&schema, &value), Ok(()), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_generates_invalid_values() {
+        let schema = order_schema();
+        let mut rng = Rng::with_seed(7);
+        let mut generator = Generator::new(&schema, &mut rng);
+        for _ in 0..100 {
+            let value = generator.valid(&schema);
+            let invalid = generator.invalid(&schema, &value).unwrap();
+            assert!(validate(&schema, &invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let schema = order_schema();
+        let generate = |seed| {
+            let mut rng = Rng::with_seed(seed);
+            Generator::new(&schema, &mut rng).valid(&schema)
+        };
+        assert_eq!(generate(3), generate(3));
+    }
+
+    #[test]
+    fn test_simplifications_shrink_to_nothing() {
+        let mut value = json!({"a": [1, "text", {"b": true}], "c": -7.5});
+        let mut steps = 0;
+        while let Some(simpler) = simplifications(&value).into_iter().next() {
+            value = simpler;
+            steps += 1;
+        }
+        assert_eq!(value, json!({}));
+        assert!(steps <= 2, "{}", steps);
+        assert_eq!(simplifications(&json!(-9))[1], json!(-4));
+        assert_eq!(simplifications(&json!("abcd"))[1], json!("ab"));
+    }
+}
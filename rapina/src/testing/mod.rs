@@ -1,8 +1,22 @@
 //! Testing utilities for Rapina applications.
 //!
 //! This module provides a test client for integration testing without
-//! starting a full HTTP server.
+//! starting a full HTTP server and response [`Snapshot`]s. With the `fuzz`
+//! feature, a `Fuzzer` also generates requests from the application's
+//! OpenAPI spec.
 
 mod client;
+mod contract;
+mod cookies;
+#[cfg(feature = "fuzz")]
+mod fuzz;
+#[cfg(feature = "fuzz")]
+mod generate;
+mod schema;
+mod snapshot;
 
 pub use client::{TestClient, TestRequestBuilder, TestResponse};
+pub use contract::RouteCoverage;
+#[cfg(feature = "fuzz")]
+pub use fuzz::{FuzzFailure, FuzzReport, FuzzRequest, Fuzzer};
+pub use snapshot::Snapshot;
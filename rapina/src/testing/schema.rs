@@ -0,0 +1,459 @@
+//! JSON Schema validation for the schemas Rapina generates.
+//!
+//! Supports the keywords schemars emits for Rust types: `type`, `enum`,
+//! `const`, `$ref`, the `allOf`/`anyOf`/`oneOf`/`not` combinators, object
+//! and array keywords, numeric and string bounds, and the `uuid` format.
+//! Other keywords and formats are treated as annotations and ignored.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+/// Nested `$ref`s followed before giving up on a recursive schema.
+const MAX_DEPTH: usize = 64;
+
+/// Where and why a value doesn't match a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SchemaError {
+    /// Location of the offending value, as `$.field[0]`.
+    pub(crate) path: String,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Checks `value` against `schema`.
+///
+/// `$ref`s are resolved against `schema` itself, so it must be the root
+/// schema holding the `$defs` (or `definitions`).
+pub(crate) fn validate(schema: &Value, value: &Value) -> Result<(), SchemaError> {
+    validate_in(schema, schema, value)
+}
+
+/// Checks `value` against `schema`, a part of the `root` document that
+/// its `$ref`s point into, such as an OpenAPI spec.
+pub(crate) fn validate_in(root: &Value, schema: &Value, value: &Value) -> Result<(), SchemaError> {
+    Validator { root }.check(schema, value, "$", 0)
+}
+
+struct Validator<'s> {
+    root: &'s Value,
+}
+
+impl Validator<'_> {
+    fn check(
+        &self,
+        schema: &Value,
+        value: &Value,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), SchemaError> {
+        let fail = |message: String| {
+            Err(SchemaError {
+                path: path.to_string(),
+                message,
+            })
+        };
+
+        let schema = match schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => return fail("no value is allowed here".to_string()),
+            Value::Object(schema) => schema,
+            _ => return Ok(()),
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            if depth >= MAX_DEPTH {
+                return fail(format!("too many nested references at `{}`", reference));
+            }
+            let Some(target) = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+            else {
+                return fail(format!("unresolved reference `{}`", reference));
+            };
+            self.check(target, value, path, depth + 1)?;
+        }
+
+        if let Some(types) = schema.get("type") {
+            let allowed: Vec<&str> = match types {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+                return fail(format!(
+                    "expected {}, got {}",
+                    allowed.join(" or "),
+                    type_name(value)
+                ));
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array)
+            && !options.contains(value)
+        {
+            return fail(format!(
+                "{} is not one of {}",
+                value,
+                Value::from(options.clone())
+            ));
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != value
+        {
+            return fail(format!("expected {}, got {}", expected, value));
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.check(sub, value, path, depth + 1)?;
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array)
+            && !any
+                .iter()
+                .any(|sub| self.check(sub, value, path, depth + 1).is_ok())
+        {
+            return Err(self.closest(any, value, path, depth));
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = one
+                .iter()
+                .filter(|sub| self.check(sub, value, path, depth + 1).is_ok())
+                .count();
+            match matching {
+                1 => {}
+                0 => return Err(self.closest(one, value, path, depth)),
+                n => return fail(format!("matches {} schemas of `oneOf` instead of one", n)),
+            }
+        }
+        if let Some(not) = schema.get("not")
+            && self.check(not, value, path, depth + 1).is_ok()
+        {
+            return fail("matches a schema it must not match".to_string());
+        }
+
+        match value {
+            Value::Number(number) => {
+                if let Some(n) = number.as_f64() {
+                    check_number(schema, n).or_else(fail)?;
+                }
+            }
+            Value::String(string) => check_string(schema, string).or_else(fail)?,
+            Value::Array(items) => self.check_array(schema, items, path, depth)?,
+            Value::Object(object) => self.check_object(schema, object, path, depth)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The error of the alternative that got furthest into `value`.
+    fn closest(
+        &self,
+        alternatives: &[Value],
+        value: &Value,
+        path: &str,
+        depth: usize,
+    ) -> SchemaError {
+        let errors: Vec<SchemaError> = alternatives
+            .iter()
+            .filter_map(|sub| self.check(sub, value, path, depth + 1).err())
+            .collect();
+        match errors.iter().max_by_key(|error| error.path.len()) {
+            Some(error) if error.path != path => error.clone(),
+            _ => SchemaError {
+                path: path.to_string(),
+                message: format!("{} matches none of the allowed schemas", type_name(value)),
+            },
+        }
+    }
+
+    fn check_array(
+        &self,
+        schema: &Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        depth: usize,
+    ) -> Result<(), SchemaError> {
+        let fail = |message: String| {
+            Err(SchemaError {
+                path: path.to_string(),
+                message,
+            })
+        };
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && (items.len() as u64) < min
+        {
+            return fail(format!(
+                "expected at least {} items, got {}",
+                min,
+                items.len()
+            ));
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && (items.len() as u64) > max
+        {
+            return fail(format!(
+                "expected at most {} items, got {}",
+                max,
+                items.len()
+            ));
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            for (i, item) in items.iter().enumerate() {
+                if items[..i].contains(item) {
+                    return fail(format!("item {} is a duplicate", i));
+                }
+            }
+        }
+
+        // `prefixItems` (or a list of `items` in older drafts) describes a tuple
+        let (prefix, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest),
+            (None, Some(Value::Array(prefix))) => {
+                (prefix.as_slice(), schema.get("additionalItems"))
+            }
+            (_, rest) => (&[][..], rest),
+        };
+        for (i, item) in items.iter().enumerate() {
+            let item_path = format!("{}[{}]", path, i);
+            match prefix.get(i) {
+                Some(sub) => self.check(sub, item, &item_path, depth + 1)?,
+                None => {
+                    if let Some(sub) = rest {
+                        self.check(sub, item, &item_path, depth + 1)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), SchemaError> {
+        let fail = |message: String| {
+            Err(SchemaError {
+                path: path.to_string(),
+                message,
+            })
+        };
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    return fail(format!("missing required property `{}`", name));
+                }
+            }
+        }
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64)
+            && (object.len() as u64) < min
+        {
+            return fail(format!("expected at least {} properties", min));
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64)
+            && (object.len() as u64) > max
+        {
+            return fail(format!("expected at most {} properties", max));
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, value) in object {
+            let property_path = format!("{}.{}", path, name);
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(sub) => self.check(sub, value, &property_path, depth + 1)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return fail(format!("unexpected property `{}`", name));
+                    }
+                    Some(sub) => self.check(sub, value, &property_path, depth + 1)?,
+                    None => {}
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_number(schema: &Map<String, Value>, n: f64) -> Result<(), String> {
+    let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+    if let Some(min) = bound("minimum")
+        && n < min
+    {
+        return Err(format!("{} is less than the minimum of {}", n, min));
+    }
+    if let Some(max) = bound("maximum")
+        && n > max
+    {
+        return Err(format!("{} is greater than the maximum of {}", n, max));
+    }
+    if let Some(min) = bound("exclusiveMinimum")
+        && n <= min
+    {
+        return Err(format!("{} is not greater than {}", n, min));
+    }
+    if let Some(max) = bound("exclusiveMaximum")
+        && n >= max
+    {
+        return Err(format!("{} is not less than {}", n, max));
+    }
+    if let Some(step) = bound("multipleOf")
+        && step > 0.0
+        && (n / step).fract() != 0.0
+    {
+        return Err(format!("{} is not a multiple of {}", n, step));
+    }
+    Ok(())
+}
+
+fn check_string(schema: &Map<String, Value>, string: &str) -> Result<(), String> {
+    let length = string.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+        && length < min
+    {
+        return Err(format!(
+            "expected at least {} characters, got {}",
+            min, length
+        ));
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+        && length > max
+    {
+        return Err(format!(
+            "expected at most {} characters, got {}",
+            max, length
+        ));
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str)
+        && let Ok(regex) = regex::Regex::new(pattern)
+        && !regex.is_match(string)
+    {
+        return Err(format!(
+            "{:?} doesn't match the pattern `{}`",
+            string, pattern
+        ));
+    }
+    if schema.get("format").and_then(Value::as_str) == Some("uuid")
+        && uuid::Uuid::try_parse(string).is_err()
+    {
+        return Err(format!("{:?} is not a UUID", string));
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => match value {
+            Value::Number(n) => {
+                n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|n| n.fract() == 0.0)
+            }
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct User {
+        id: u64,
+        name: String,
+        email: Option<String>,
+        role: Role,
+        tags: Vec<String>,
+    }
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    enum Role {
+        Admin,
+        Member,
+    }
+
+    fn user_schema() -> Value {
+        serde_json::to_value(schemars::schema_for!(User)).unwrap()
+    }
+
+    #[test]
+    fn test_accepts_matching_values() {
+        let schema = user_schema();
+        let user = json!({"id": 1, "name": "Ann", "email": null, "role": "Admin", "tags": []});
+        assert_eq!(validate(&schema, &user), Ok(()));
+        assert_eq!(validate(&json!(true), &user), Ok(()));
+    }
+
+    #[test]
+    fn test_reports_mismatches_with_their_path() {
+        let schema = user_schema();
+        let error = |value: Value| validate(&schema, &value).unwrap_err().to_string();
+
+        assert_eq!(
+            error(json!({"id": 1, "name": "Ann", "role": "Admin", "tags": [1]})),
+            "$.tags[0]: expected string, got integer"
+        );
+        assert_eq!(
+            error(json!({"id": 1, "role": "Admin", "tags": []})),
+            "$: missing required property `name`"
+        );
+        assert_eq!(
+            error(json!({"id": -1, "name": "Ann", "role": "Admin", "tags": []})),
+            "$.id: -1 is less than the minimum of 0"
+        );
+        assert_eq!(
+            error(json!({"id": 1, "name": "Ann", "role": "Owner", "tags": []})),
+            r#"$.role: "Owner" is not one of ["Admin","Member"]"#
+        );
+    }
+
+    #[test]
+    fn test_combinators_and_tuples() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "pair": {"prefixItems": [{"type": "string"}, {"type": "integer"}], "items": false},
+                "either": {"oneOf": [{"type": "string"}, {"type": "integer", "maximum": 9}]}
+            },
+            "additionalProperties": false
+        });
+        assert_eq!(
+            validate(&schema, &json!({"pair": ["a", 1], "either": 3})),
+            Ok(())
+        );
+        assert!(validate(&schema, &json!({"pair": ["a", 1, 2]})).is_err());
+        assert!(validate(&schema, &json!({"either": 10})).is_err());
+        assert_eq!(
+            validate(&schema, &json!({"other": 1})).unwrap_err().message,
+            "unexpected property `other`"
+        );
+    }
+}
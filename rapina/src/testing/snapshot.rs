@@ -0,0 +1,592 @@
+//! Snapshot assertions for test responses.
+//!
+//! A snapshot is a text rendering of a response (its status, selected
+//! headers and pretty-printed body) stored under `tests/snapshots/`.
+//! Volatile values are redacted before comparing, so a snapshot only
+//! changes when the response does.
+
+use std::fmt::Write as _;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::TestResponse;
+
+/// Environment variable that makes changed snapshots write a pending
+/// `.snap.new` file instead of failing, set by `rapina test --update-snapshots`.
+const UPDATE_ENV: &str = "RAPINA_UPDATE_SNAPSHOTS";
+
+/// Unchanged lines shown around each change in a diff.
+const DIFF_CONTEXT: usize = 3;
+
+static UUID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b").unwrap()
+});
+
+static TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?").unwrap()
+});
+
+/// A snapshot assertion on a [`TestResponse`], created by
+/// [`TestResponse::snapshot`].
+///
+/// By default the snapshot holds the status, the `content-type` header and
+/// the body, with the `trace_id` of error responses, UUIDs and RFC 3339
+/// timestamps redacted.
+///
+/// # Examples
+///
+/// ```ignore
+/// client
+///     .get("/orders")
+///     .send()
+///     .await
+///     .snapshot("orders")
+///     .header("cache-control")
+///     .redact("$.items[*].price")
+///     .assert();
+/// ```
+pub struct Snapshot<'r> {
+    response: &'r TestResponse,
+    name: String,
+    headers: Vec<String>,
+    redactions: Vec<(Selector, String)>,
+    redact_patterns: bool,
+}
+
+impl<'r> Snapshot<'r> {
+    #[track_caller]
+    pub(crate) fn new(response: &'r TestResponse, name: &str) -> Self {
+        let path = Path::new(name);
+        if name.is_empty()
+            || path
+                .components()
+                .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            panic!("invalid snapshot name {:?}", name);
+        }
+
+        Self {
+            response,
+            name: name.to_string(),
+            headers: vec!["content-type".to_string()],
+            redactions: vec![(
+                Selector::parse("$.trace_id").unwrap(),
+                "[trace_id]".to_string(),
+            )],
+            redact_patterns: true,
+        }
+    }
+
+    /// Includes a response header in the snapshot.
+    ///
+    /// Only `content-type` is included by default.
+    pub fn header(mut self, name: &str) -> Self {
+        self.headers.push(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Replaces the JSON body values `selector` matches with `"[redacted]"`.
+    ///
+    /// Selectors are paths into the body: `$.field`, `$.items[0]`,
+    /// `$.items[*].id` for a field of every item, `$.*` for every field
+    /// and `$..id` for `id` fields at any depth.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the selector is invalid.
+    #[track_caller]
+    pub fn redact(self, selector: &str) -> Self {
+        self.redact_as(selector, "[redacted]")
+    }
+
+    /// Replaces the JSON body values `selector` matches with `placeholder`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the selector is invalid.
+    #[track_caller]
+    pub fn redact_as(mut self, selector: &str, placeholder: &str) -> Self {
+        let parsed = Selector::parse(selector)
+            .unwrap_or_else(|e| panic!("invalid redaction selector {:?}: {}", selector, e));
+        self.redactions.push((parsed, placeholder.to_string()));
+        self
+    }
+
+    /// Sets whether UUIDs and RFC 3339 timestamps are replaced with
+    /// `[uuid]` and `[timestamp]` wherever they appear.
+    ///
+    /// Defaults to true.
+    pub fn redact_patterns(mut self, redact: bool) -> Self {
+        self.redact_patterns = redact;
+        self
+    }
+
+    /// Renders the snapshot text of the response.
+    pub fn render(&self) -> String {
+        let response = self.response;
+        let mut text = format!("{}\n", response.status());
+        for name in &self.headers {
+            for value in response.headers().get_all(name.as_str()) {
+                let value = value.to_str().unwrap_or("<binary>");
+                let _ = writeln!(text, "{}: {}", name, self.redact_text(value));
+            }
+        }
+        text.push('\n');
+
+        let body = response.bytes();
+        if body.is_empty() {
+            return text;
+        }
+        if let Ok(mut json) = serde_json::from_slice::<Value>(body) {
+            for (selector, placeholder) in &self.redactions {
+                selector.replace(&mut json, placeholder);
+            }
+            if self.redact_patterns {
+                self.redact_strings(&mut json);
+            }
+            text.push_str(&serde_json::to_string_pretty(&json).unwrap());
+            text.push('\n');
+        } else if let Ok(body) = std::str::from_utf8(body) {
+            text.push_str(&self.redact_text(body));
+            if !body.ends_with('\n') {
+                text.push('\n');
+            }
+        } else {
+            let _ = writeln!(text, "<{} bytes of binary data>", body.len());
+        }
+        text
+    }
+
+    /// Asserts the response matches `tests/snapshots/{name}.snap`.
+    ///
+    /// With `RAPINA_UPDATE_SNAPSHOTS` set, as `rapina test
+    /// --update-snapshots` does, a new or changed snapshot is written to
+    /// `{name}.snap.new` for review instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a diff if the snapshot is missing or differs.
+    #[track_caller]
+    pub fn assert(&self) {
+        let update = std::env::var_os(UPDATE_ENV).is_some_and(|value| !value.is_empty());
+        if let Err(message) = self.check(&snapshot_dir(), update) {
+            panic!("{}", message);
+        }
+    }
+
+    fn check(&self, dir: &Path, update: bool) -> Result<(), String> {
+        let path = dir.join(format!("{}.snap", self.name));
+        let pending = dir.join(format!("{}.snap.new", self.name));
+        let actual = self.render();
+
+        let expected = match std::fs::read_to_string(&path) {
+            Ok(expected) => Some(expected.replace("\r\n", "\n")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+        };
+        if expected.as_deref() == Some(actual.as_str()) {
+            // A change from an earlier run was reverted
+            let _ = std::fs::remove_file(&pending);
+            return Ok(());
+        }
+
+        if update {
+            if let Some(parent) = pending.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+            }
+            return std::fs::write(&pending, &actual)
+                .map_err(|e| format!("failed to write {}: {}", pending.display(), e));
+        }
+        match expected {
+            None => Err(format!(
+                "snapshot `{}` doesn't exist yet; run `rapina test --update-snapshots` to review and accept it:\n\n{}",
+                self.name, actual
+            )),
+            Some(expected) => Err(format!(
+                "snapshot `{}` doesn't match {}:\n\n{}\nrun `rapina test --update-snapshots` to review and accept the change",
+                self.name,
+                path.display(),
+                diff(&expected, &actual)
+            )),
+        }
+    }
+
+    fn redact_text(&self, text: &str) -> String {
+        if !self.redact_patterns {
+            return text.to_string();
+        }
+        let text = TIMESTAMP.replace_all(text, "[timestamp]");
+        UUID.replace_all(&text, "[uuid]").into_owned()
+    }
+
+    fn redact_strings(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.redact_text(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_strings(item)),
+            Value::Object(object) => object
+                .values_mut()
+                .for_each(|value| self.redact_strings(value)),
+            _ => {}
+        }
+    }
+}
+
+/// `tests/snapshots/` in the package under test.
+fn snapshot_dir() -> PathBuf {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join("tests")
+        .join("snapshots")
+}
+
+/// A path to values in a JSON document.
+#[derive(Debug, PartialEq)]
+struct Selector(Vec<Step>);
+
+#[derive(Debug, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+    /// Every field or item.
+    Any,
+    /// A field at any depth.
+    Descendant(String),
+}
+
+impl Selector {
+    fn parse(selector: &str) -> Result<Self, String> {
+        let mut rest = selector
+            .strip_prefix('$')
+            .ok_or("selectors start with `$`")?;
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                let (key, after) = split_key(after);
+                if key.is_empty() || key == "*" {
+                    return Err("`..` must be followed by a field name".to_string());
+                }
+                steps.push(Step::Descendant(key.to_string()));
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let (key, after) = split_key(after);
+                steps.push(match key {
+                    "" => return Err("`.` must be followed by a field name".to_string()),
+                    "*" => Step::Any,
+                    key => Step::Key(key.to_string()),
+                });
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let (inner, after) = after.split_once(']').ok_or("unclosed `[`")?;
+                let quoted = inner
+                    .strip_prefix('"')
+                    .and_then(|key| key.strip_suffix('"'))
+                    .or_else(|| {
+                        inner
+                            .strip_prefix('\'')
+                            .and_then(|key| key.strip_suffix('\''))
+                    });
+                steps.push(match (inner, quoted) {
+                    (_, Some(key)) => Step::Key(key.to_string()),
+                    ("*", None) => Step::Any,
+                    (index, None) => Step::Index(
+                        index
+                            .parse()
+                            .map_err(|_| format!("`[{}]` isn't an index", index))?,
+                    ),
+                });
+                rest = after;
+            } else {
+                return Err(format!("unexpected `{}`", rest));
+            }
+        }
+        Ok(Self(steps))
+    }
+
+    /// Replaces the values the selector matches in `value`.
+    fn replace(&self, value: &mut Value, placeholder: &str) {
+        replace(&self.0, value, placeholder);
+    }
+}
+
+/// Splits a field name off the start of a selector.
+fn split_key(selector: &str) -> (&str, &str) {
+    let end = selector.find(['.', '[']).unwrap_or(selector.len());
+    selector.split_at(end)
+}
+
+fn replace(steps: &[Step], value: &mut Value, placeholder: &str) {
+    let Some((step, rest)) = steps.split_first() else {
+        *value = Value::String(placeholder.to_string());
+        return;
+    };
+    match step {
+        Step::Key(key) => {
+            if let Some(child) = value.get_mut(key.as_str()) {
+                replace(rest, child, placeholder);
+            }
+        }
+        Step::Index(index) => {
+            if let Some(child) = value.get_mut(*index) {
+                replace(rest, child, placeholder);
+            }
+        }
+        Step::Any => children(value).for_each(|child| replace(rest, child, placeholder)),
+        Step::Descendant(key) => {
+            if let Some(child) = value.get_mut(key.as_str()) {
+                replace(rest, child, placeholder);
+            }
+            children(value).for_each(|child| replace(steps, child, placeholder));
+        }
+    }
+}
+
+fn children(value: &mut Value) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+    match value {
+        Value::Object(object) => Box::new(object.values_mut()),
+        Value::Array(items) => Box::new(items.iter_mut()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+/// A line of a diff.
+#[derive(Debug, PartialEq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines of `old` and `new`, matched by their longest common subsequence.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut lines: Vec<Line<'a>> = old[..prefix].iter().map(|line| Line::Same(line)).collect();
+    if a.len() * b.len() > 4_000_000 {
+        // Too large to match up; show it as replaced
+        lines.extend(a.iter().map(|line| Line::Removed(line)));
+        lines.extend(b.iter().map(|line| Line::Added(line)));
+    } else {
+        // common[i][j] is the length of the LCS of a[i..] and b[j..]
+        let mut common = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                common[i][j] = if a[i] == b[j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                lines.push(Line::Same(a[i]));
+                i += 1;
+                j += 1;
+            } else if i < a.len() && (j == b.len() || common[i + 1][j] >= common[i][j + 1]) {
+                lines.push(Line::Removed(a[i]));
+                i += 1;
+            } else {
+                lines.push(Line::Added(b[j]));
+                j += 1;
+            }
+        }
+    }
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| Line::Same(line)),
+    );
+    lines
+}
+
+/// A diff of `old` and `new` showing changed lines with some context.
+fn diff(old: &str, new: &str) -> String {
+    let lines = diff_lines(old, new);
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Line::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut text = String::new();
+    let mut skipped = false;
+    for (i, line) in lines.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&change| i.abs_diff(change) <= DIFF_CONTEXT);
+        if !near_change {
+            if !skipped {
+                text.push_str("  ...\n");
+                skipped = true;
+            }
+            continue;
+        }
+        skipped = false;
+        let _ = match line {
+            Line::Same(line) => writeln!(text, "  {}", line),
+            Line::Removed(line) => writeln!(text, "- {}", line),
+            Line::Added(line) => writeln!(text, "+ {}", line),
+        };
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::Full;
+    use serde_json::json;
+
+    async fn json_response(body: Value) -> TestResponse {
+        let response = http::Response::builder()
+            .header("content-type", "application/json")
+            .header("etag", "\"abc\"")
+            .body(Full::new(bytes::Bytes::from(body.to_string())))
+            .unwrap();
+        TestResponse::read(response).await
+    }
+
+    #[test]
+    fn test_parse_selectors() {
+        assert_eq!(
+            Selector::parse("$.items[*].id").unwrap(),
+            Selector(vec![
+                Step::Key("items".to_string()),
+                Step::Any,
+                Step::Key("id".to_string()),
+            ])
+        );
+        assert_eq!(
+            Selector::parse("$..created_at['a.b'][2]").unwrap(),
+            Selector(vec![
+                Step::Descendant("created_at".to_string()),
+                Step::Key("a.b".to_string()),
+                Step::Index(2),
+            ])
+        );
+        assert_eq!(Selector::parse("$").unwrap(), Selector(Vec::new()));
+        assert!(Selector::parse("items").is_err());
+        assert!(Selector::parse("$.items[x]").is_err());
+        assert!(Selector::parse("$.items[0").is_err());
+        assert!(Selector::parse("$..").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_render_redacts() {
+        let response = json_response(json!({
+            "trace_id": "abc",
+            "items": [
+                {"id": "3f0b1e6c-9c5a-4f0e-8f4e-2b1d7c9a0e11", "price": 5, "link": "/items/3F0B1E6C-9C5A-4F0E-8F4E-2B1D7C9A0E11"},
+                {"id": "x", "price": 7, "meta": {"price": 1}}
+            ],
+            "created_at": "2024-05-01T10:20:30.123Z",
+        })).await;
+        let snapshot = Snapshot::new(&response, "items")
+            .header("ETag")
+            .redact("$.items[*].price")
+            .redact_as("$..meta", "[meta]");
+
+        assert_eq!(
+            snapshot.render(),
+            r#"200 OK
+content-type: application/json
+etag: "abc"
+
+{
+  "created_at": "[timestamp]",
+  "items": [
+    {
+      "id": "[uuid]",
+      "link": "/items/[uuid]",
+      "price": "[redacted]"
+    },
+    {
+      "id": "x",
+      "meta": "[meta]",
+      "price": "[redacted]"
+    }
+  ],
+  "trace_id": "[trace_id]"
+}
+"#
+        );
+
+        let kept = Snapshot::new(&response, "items").redact_patterns(false);
+        assert!(kept.render().contains("2024-05-01T10:20:30.123Z"));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "invalid snapshot name")]
+    async fn test_snapshot_name_stays_in_the_directory() {
+        let response = json_response(json!({})).await;
+        Snapshot::new(&response, "../escape");
+    }
+
+    #[tokio::test]
+    async fn test_check_and_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let response = json_response(json!({"name": "Ann"})).await;
+        let snapshot = Snapshot::new(&response, "users/ann");
+        let path = dir.path().join("users/ann.snap");
+        let pending = dir.path().join("users/ann.snap.new");
+
+        let error = snapshot.check(dir.path(), false).unwrap_err();
+        assert!(error.contains("doesn't exist yet"), "{}", error);
+        assert!(!pending.exists());
+
+        snapshot.check(dir.path(), true).unwrap();
+        std::fs::rename(&pending, &path).unwrap();
+        snapshot.check(dir.path(), false).unwrap();
+
+        let changed = json_response(json!({"name": "Bob"})).await;
+        let error = Snapshot::new(&changed, "users/ann")
+            .check(dir.path(), false)
+            .unwrap_err();
+        assert!(
+            error.contains("-   \"name\": \"Ann\"\n+   \"name\": \"Bob\""),
+            "{}",
+            error
+        );
+
+        Snapshot::new(&changed, "users/ann")
+            .check(dir.path(), true)
+            .unwrap();
+        assert!(pending.exists());
+        // Matching again drops the stale pending snapshot
+        snapshot.check(dir.path(), true).unwrap();
+        assert!(!pending.exists());
+    }
+
+    #[test]
+    fn test_diff_shows_context() {
+        let old = (1..=20).map(|n| format!("{}\n", n)).collect::<String>();
+        let new = old.replace("10\n", "ten\n");
+        assert_eq!(
+            diff(&old, &new),
+            "  ...\n  7\n  8\n  9\n- 10\n+ ten\n  11\n  12\n  13\n  ...\n"
+        );
+        assert_eq!(
+            diff_lines("a\nb\n", "b\nc\n"),
+            vec![Line::Removed("a"), Line::Same("b"), Line::Added("c")]
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! TLS termination with rustls.
+//!
+//! Requires the `tls` feature. Certificates and keys are loaded from PEM
+//! files and reloaded when the files change on disk, so renewed
+//! certificates are picked up without restarting the server or dropping
+//! open connections.
+//!
+//! # Quick Start
+//!
+//! ```rust,ignore
+//! use rapina::prelude::*;
+//! use rapina::tls::TlsConfig;
+//!
+//! #[tokio::main]
+//! async fn main() -> std::io::Result<()> {
+//!     Rapina::new()
+//!         .router(router)
+//!         .listen_tls("0.0.0.0:443", TlsConfig::new("cert.pem", "key.pem"))
+//!         .await
+//! }
+//! ```
+//!
+//! # Client certificates (mTLS)
+//!
+//! ```rust,ignore
+//! use rapina::tls::{PeerCertificate, TlsConfig};
+//!
+//! #[get("/whoami")]
+//! async fn whoami(cert: PeerCertificate) -> String {
+//!     format!("{} byte certificate", cert.der().len())
+//! }
+//!
+//! let tls = TlsConfig::new("cert.pem", "key.pem").require_client_cert("clients-ca.pem");
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::crypto::{CryptoProvider, ring};
+use tokio_rustls::rustls::pki_types::pem::{self, PemObject};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::{
+    ClientHello, ResolvesServerCert, VerifierBuilderError, WebPkiClientVerifier,
+};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::{self, RootCertStore};
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::server::Protocol;
+use crate::state::AppState;
+
+const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Whether and how clients must present a certificate.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ClientAuth {
+    /// Client certificates are not requested.
+    #[default]
+    None,
+    /// Client certificates are requested and verified against the CA bundle
+    /// at this path, but clients without one may still connect.
+    Optional(PathBuf),
+    /// Clients must present a certificate signed by the CA bundle at this path.
+    Required(PathBuf),
+}
+
+/// TLS settings for [`Rapina::listen_tls`](crate::app::Rapina::listen_tls).
+///
+/// # Examples
+///
+/// ```
+/// use rapina::tls::TlsConfig;
+/// use std::time::Duration;
+///
+/// let config = TlsConfig::new("cert.pem", "key.pem")
+///     .require_client_cert("clients-ca.pem")
+///     .reload_interval(Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// Path to the PEM certificate chain, leaf certificate first.
+    pub cert_path: PathBuf,
+    /// Path to the PEM private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_path: PathBuf,
+    /// Client certificate verification.
+    pub client_auth: ClientAuth,
+    /// How often the certificate and key files are checked for changes.
+    /// Reloading is disabled when `None`.
+    pub reload_interval: Option<Duration>,
+}
+
+impl TlsConfig {
+    /// Creates a config serving the certificate chain and key at the given paths.
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            client_auth: ClientAuth::None,
+            reload_interval: Some(DEFAULT_RELOAD_INTERVAL),
+        }
+    }
+
+    /// Requires clients to present a certificate signed by one of the CAs in
+    /// the PEM bundle at `ca_path`.
+    pub fn require_client_cert(mut self, ca_path: impl Into<PathBuf>) -> Self {
+        self.client_auth = ClientAuth::Required(ca_path.into());
+        self
+    }
+
+    /// Verifies client certificates against the PEM bundle at `ca_path` when
+    /// presented, while still accepting clients without one.
+    pub fn request_client_cert(mut self, ca_path: impl Into<PathBuf>) -> Self {
+        self.client_auth = ClientAuth::Optional(ca_path.into());
+        self
+    }
+
+    /// Sets how often the certificate and key files are checked for changes.
+    pub fn reload_interval(mut self, interval: Duration) -> Self {
+        self.reload_interval = Some(interval);
+        self
+    }
+
+    /// Disables certificate reloading.
+    pub fn no_reload(mut self) -> Self {
+        self.reload_interval = None;
+        self
+    }
+
+    /// Loads the certificates and builds the acceptor, advertising ALPN for
+    /// the protocols the server accepts.
+    pub(crate) fn build(&self, protocol: Protocol) -> Result<Tls, TlsError> {
+        let provider = Arc::new(ring::default_provider());
+        let resolver = Arc::new(ReloadingResolver {
+            current: RwLock::new(Arc::new(self.load_certified_key(&provider)?)),
+            modified: RwLock::new(self.modified()),
+        });
+
+        let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(TlsError::Rustls)?;
+        let builder = match &self.client_auth {
+            ClientAuth::None => builder.with_no_client_auth(),
+            ClientAuth::Optional(ca_path) => {
+                let verifier = WebPkiClientVerifier::builder_with_provider(
+                    Arc::new(load_roots(ca_path)?),
+                    provider.clone(),
+                )
+                .allow_unauthenticated()
+                .build()
+                .map_err(TlsError::ClientVerifier)?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            ClientAuth::Required(ca_path) => {
+                let verifier = WebPkiClientVerifier::builder_with_provider(
+                    Arc::new(load_roots(ca_path)?),
+                    provider.clone(),
+                )
+                .build()
+                .map_err(TlsError::ClientVerifier)?;
+                builder.with_client_cert_verifier(verifier)
+            }
+        };
+
+        let mut server_config = builder.with_cert_resolver(resolver.clone());
+        server_config.alpn_protocols = match protocol {
+            Protocol::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+            Protocol::Http1 => vec![b"http/1.1".to_vec()],
+            Protocol::Http2 => vec![b"h2".to_vec()],
+        };
+
+        Ok(Tls {
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+            reloader: self.reload_interval.map(|interval| Reloader {
+                config: self.clone(),
+                provider,
+                resolver,
+                interval,
+            }),
+        })
+    }
+
+    fn load_certified_key(&self, provider: &CryptoProvider) -> Result<CertifiedKey, TlsError> {
+        let certs = load_certs(&self.cert_path)?;
+        let key = PrivateKeyDer::from_pem_file(&self.key_path).map_err(|e| match e {
+            pem::Error::NoItemsFound => TlsError::MissingPrivateKey(self.key_path.clone()),
+            e => TlsError::Pem(self.key_path.clone(), e),
+        })?;
+        CertifiedKey::from_der(certs, key, provider).map_err(TlsError::Rustls)
+    }
+
+    fn modified(&self) -> Option<(SystemTime, SystemTime)> {
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        Some((modified(&self.cert_path)?, modified(&self.key_path)?))
+    }
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| TlsError::Pem(path.to_path_buf(), e))?;
+    if certs.is_empty() {
+        return Err(TlsError::MissingCertificate(path.to_path_buf()));
+    }
+    Ok(certs)
+}
+
+fn load_roots(path: &Path) -> Result<RootCertStore, TlsError> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(path)? {
+        roots.add(cert).map_err(TlsError::Rustls)?;
+    }
+    Ok(roots)
+}
+
+/// Errors raised while loading TLS certificates and keys.
+#[derive(Debug)]
+pub enum TlsError {
+    /// A PEM file could not be read or parsed.
+    Pem(PathBuf, pem::Error),
+    /// The certificate file contains no certificates.
+    MissingCertificate(PathBuf),
+    /// The key file contains no private key.
+    MissingPrivateKey(PathBuf),
+    /// The client CA bundle can't be used to verify client certificates.
+    ClientVerifier(VerifierBuilderError),
+    /// rustls rejected the certificate, key or configuration.
+    Rustls(rustls::Error),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::Pem(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            TlsError::MissingCertificate(path) => {
+                write!(f, "no certificates found in {}", path.display())
+            }
+            TlsError::MissingPrivateKey(path) => {
+                write!(f, "no private key found in {}", path.display())
+            }
+            TlsError::ClientVerifier(e) => write!(f, "invalid client CA bundle: {}", e),
+            TlsError::Rustls(e) => write!(f, "TLS error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+/// A built TLS acceptor plus the task that keeps its certificate fresh.
+pub(crate) struct Tls {
+    pub(crate) acceptor: TlsAcceptor,
+    pub(crate) reloader: Option<Reloader>,
+}
+
+/// Serves whichever certificate was loaded last.
+#[derive(Debug)]
+struct ReloadingResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+    modified: RwLock<Option<(SystemTime, SystemTime)>>,
+}
+
+impl ResolvesServerCert for ReloadingResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+/// Polls the certificate and key files and swaps in new ones when they change.
+pub(crate) struct Reloader {
+    config: TlsConfig,
+    provider: Arc<CryptoProvider>,
+    resolver: Arc<ReloadingResolver>,
+    interval: Duration,
+}
+
+impl Reloader {
+    pub(crate) async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let modified = self.config.modified();
+            if modified.is_none() || modified == *self.resolver.modified.read().unwrap() {
+                continue;
+            }
+
+            // Only new handshakes see the new certificate; established
+            // connections keep the one they negotiated.
+            match self.config.load_certified_key(&self.provider) {
+                Ok(key) => {
+                    *self.resolver.current.write().unwrap() = Arc::new(key);
+                    *self.resolver.modified.write().unwrap() = modified;
+                    tracing::info!(cert = %self.config.cert_path.display(), "reloaded TLS certificate");
+                }
+                // A renewal may write the certificate and key separately, so
+                // a mismatched pair is retried on the next tick.
+                Err(e) => tracing::warn!(error = %e, "failed to reload TLS certificate"),
+            }
+        }
+    }
+}
+
+/// The certificate chain presented by the client over mutual TLS.
+///
+/// Responds with 401 Unauthorized when the connection isn't TLS or the
+/// client didn't present a certificate, which can only happen with
+/// [`TlsConfig::request_client_cert`].
+#[derive(Debug, Clone)]
+pub struct PeerCertificate(pub(crate) Arc<[CertificateDer<'static>]>);
+
+impl PeerCertificate {
+    /// Returns the DER encoding of the client's own (leaf) certificate.
+    pub fn der(&self) -> &[u8] {
+        &self.0[0]
+    }
+
+    /// Returns the full chain presented by the client, leaf first.
+    pub fn chain(&self) -> &[CertificateDer<'static>] {
+        &self.0
+    }
+}
+
+impl FromRequestParts for PeerCertificate {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        parts
+            .extensions
+            .get::<PeerCertificate>()
+            .cloned()
+            .ok_or_else(|| Error::unauthorized("client certificate required"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_self_signed(dir: &Path) -> (PathBuf, PathBuf) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.signing_key.serialize_pem()).unwrap();
+        (cert_path, key_path)
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = TlsConfig::new("cert.pem", "key.pem");
+        assert_eq!(config.client_auth, ClientAuth::None);
+        assert_eq!(config.reload_interval, Some(DEFAULT_RELOAD_INTERVAL));
+        assert_eq!(config.no_reload().reload_interval, None);
+    }
+
+    #[test]
+    fn test_build_loads_certificate() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert, key) = write_self_signed(dir.path());
+
+        let tls = TlsConfig::new(cert, key).build(Protocol::Auto).unwrap();
+        assert_eq!(
+            tls.acceptor.config().alpn_protocols,
+            vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+        );
+        assert!(tls.reloader.is_some());
+    }
+
+    #[test]
+    fn test_alpn_follows_protocol() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert, key) = write_self_signed(dir.path());
+
+        let tls = TlsConfig::new(cert, key).build(Protocol::Http1).unwrap();
+        assert_eq!(
+            tls.acceptor.config().alpn_protocols,
+            vec![b"http/1.1".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_missing_file_is_reported() {
+        let err = TlsConfig::new("/nonexistent/cert.pem", "/nonexistent/key.pem")
+            .build(Protocol::Auto)
+            .err()
+            .unwrap();
+        assert!(matches!(err, TlsError::Pem(..)));
+        assert!(err.to_string().contains("/nonexistent/cert.pem"));
+    }
+
+    #[test]
+    fn test_empty_key_file_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert, key) = write_self_signed(dir.path());
+        std::fs::write(&key, "").unwrap();
+
+        let err = TlsConfig::new(cert, &key)
+            .build(Protocol::Auto)
+            .err()
+            .unwrap();
+        assert!(matches!(err, TlsError::MissingPrivateKey(path) if path == key));
+    }
+
+    #[test]
+    fn test_mismatched_key_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert, _) = write_self_signed(dir.path());
+        let other = tempfile::tempdir().unwrap();
+        let (_, other_key) = write_self_signed(other.path());
+
+        let err = TlsConfig::new(cert, other_key)
+            .build(Protocol::Auto)
+            .err()
+            .unwrap();
+        assert!(matches!(err, TlsError::Rustls(_)));
+    }
+}
@@ -0,0 +1,465 @@
+//! WebSocket support.
+//!
+//! The [`WebSocketUpgrade`] extractor validates the handshake of an upgrade
+//! request and switches the connection to the WebSocket protocol. Because
+//! the upgrade request is an ordinary `GET`, it runs through the middleware
+//! stack like any other request, so authentication and other extractors
+//! such as [`CurrentUser`](crate::auth::CurrentUser) keep working.
+//!
+//! The `#[ws]` attribute is the shortest way to define a WebSocket route:
+//!
+//! ```
+//! use rapina::prelude::*;
+//! use rapina::websocket::{Message, WebSocket};
+//!
+//! #[ws("/echo")]
+//! async fn echo(mut socket: WebSocket) {
+//!     while let Some(Ok(message)) = socket.recv().await {
+//!         if let Message::Text(text) = message {
+//!             let _ = socket.send(Message::Text(text)).await;
+//!         }
+//!     }
+//! }
+//!
+//! let router = Router::new().get("/echo", echo);
+//! ```
+//!
+//! Use the extractor directly when the handler needs to reject the upgrade
+//! or negotiate a subprotocol:
+//!
+//! ```
+//! use rapina::prelude::*;
+//! use rapina::response::BoxBody;
+//! use rapina::websocket::WebSocketUpgrade;
+//!
+//! #[get("/chat")]
+//! async fn chat(ws: WebSocketUpgrade) -> http::Response<BoxBody> {
+//!     ws.protocols(["chat.v1"]).on_upgrade(|mut socket| async move {
+//!         let _ = socket.send_json(&serde_json::json!({ "hello": "world" })).await;
+//!     })
+//! }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_util::{SinkExt, Stream, StreamExt};
+use http::{HeaderMap, HeaderValue, Method, Response, StatusCode, header};
+use hyper::upgrade::{OnUpgrade, Upgraded};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::{self, handshake::derive_accept_key, protocol::Role};
+
+use crate::error::Error;
+use crate::extract::{FromRequestParts, PathParams};
+use crate::response::{BoxBody, empty};
+use crate::state::AppState;
+
+/// Extracts a WebSocket upgrade request.
+///
+/// Rejects the request with `400 Bad Request` if it isn't a valid
+/// WebSocket handshake. Call [`on_upgrade`](Self::on_upgrade) to accept
+/// the connection and return the `101 Switching Protocols` response.
+pub struct WebSocketUpgrade {
+    key: HeaderValue,
+    requested_protocols: Option<HeaderValue>,
+    protocol: Option<HeaderValue>,
+    on_upgrade: OnUpgrade,
+}
+
+impl WebSocketUpgrade {
+    /// Sets the subprotocols supported by the server.
+    ///
+    /// The first protocol requested by the client that appears in this list
+    /// is selected and echoed back in `Sec-WebSocket-Protocol`.
+    pub fn protocols<I>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let supported: Vec<I::Item> = protocols.into_iter().collect();
+        self.protocol = self
+            .requested_protocols
+            .as_ref()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|requested| {
+                requested
+                    .split(',')
+                    .map(str::trim)
+                    .find(|p| supported.iter().any(|s| s.as_ref() == *p))
+                    .and_then(|p| HeaderValue::from_str(p).ok())
+            });
+        self
+    }
+
+    /// Returns the subprotocol selected by [`protocols`](Self::protocols), if any.
+    pub fn selected_protocol(&self) -> Option<&HeaderValue> {
+        self.protocol.as_ref()
+    }
+
+    /// Accepts the upgrade and runs `callback` with the socket once the
+    /// handshake completes.
+    ///
+    /// The callback runs on its own task, after the `101` response returned
+    /// by this method has been sent.
+    pub fn on_upgrade<F, Fut>(self, callback: F) -> Response<BoxBody>
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let on_upgrade = self.on_upgrade;
+        tokio::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => {
+                    let stream = WebSocketStream::from_raw_socket(
+                        TokioIo::new(upgraded),
+                        Role::Server,
+                        None,
+                    )
+                    .await;
+                    callback(WebSocket { inner: stream }).await;
+                }
+                Err(e) => tracing::debug!(error = %e, "websocket upgrade failed"),
+            }
+        });
+
+        let accept = derive_accept_key(self.key.as_bytes());
+        let mut builder = Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, HeaderValue::from_static("upgrade"))
+            .header(header::UPGRADE, HeaderValue::from_static("websocket"))
+            .header(header::SEC_WEBSOCKET_ACCEPT, accept);
+        if let Some(protocol) = self.protocol {
+            builder = builder.header(header::SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
+        builder.body(empty()).unwrap()
+    }
+}
+
+fn header_contains(headers: &HeaderMap, name: header::HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim().eq_ignore_ascii_case(token))
+}
+
+impl FromRequestParts for WebSocketUpgrade {
+    async fn from_request_parts(
+        parts: &http::request::Parts,
+        _params: &PathParams,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Error> {
+        if parts.method != Method::GET {
+            return Err(Error::bad_request("WebSocket upgrade requires GET"));
+        }
+        if !header_contains(&parts.headers, header::CONNECTION, "upgrade")
+            || !header_contains(&parts.headers, header::UPGRADE, "websocket")
+        {
+            return Err(Error::bad_request("Missing WebSocket upgrade headers"));
+        }
+        if parts
+            .headers
+            .get(header::SEC_WEBSOCKET_VERSION)
+            .is_none_or(|v| v != "13")
+        {
+            return Err(Error::bad_request("Unsupported WebSocket version"));
+        }
+        let key = parts
+            .headers
+            .get(header::SEC_WEBSOCKET_KEY)
+            .cloned()
+            .ok_or_else(|| Error::bad_request("Missing Sec-WebSocket-Key header"))?;
+        let on_upgrade = parts
+            .extensions
+            .get::<OnUpgrade>()
+            .cloned()
+            .ok_or_else(|| Error::bad_request("Connection does not support upgrades"))?;
+
+        Ok(WebSocketUpgrade {
+            key,
+            requested_protocols: parts.headers.get(header::SEC_WEBSOCKET_PROTOCOL).cloned(),
+            protocol: None,
+            on_upgrade,
+        })
+    }
+}
+
+/// A message sent or received over a [`WebSocket`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// A UTF-8 text message.
+    Text(String),
+    /// A binary message.
+    Binary(Bytes),
+    /// A ping. Pongs are sent back automatically.
+    Ping(Bytes),
+    /// A pong.
+    Pong(Bytes),
+    /// A close message, with an optional close frame.
+    Close(Option<CloseFrame>),
+}
+
+/// The code and reason sent with a close message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseFrame {
+    /// The close code (e.g. `1000` for a normal closure).
+    pub code: u16,
+    /// A human-readable reason.
+    pub reason: String,
+}
+
+impl From<Message> for tungstenite::Message {
+    fn from(message: Message) -> Self {
+        match message {
+            Message::Text(text) => tungstenite::Message::Text(text.into()),
+            Message::Binary(data) => tungstenite::Message::Binary(data),
+            Message::Ping(data) => tungstenite::Message::Ping(data),
+            Message::Pong(data) => tungstenite::Message::Pong(data),
+            Message::Close(frame) => {
+                tungstenite::Message::Close(frame.map(|frame| tungstenite::protocol::CloseFrame {
+                    code: frame.code.into(),
+                    reason: frame.reason.into(),
+                }))
+            }
+        }
+    }
+}
+
+impl Message {
+    fn from_tungstenite(message: tungstenite::Message) -> Option<Self> {
+        match message {
+            tungstenite::Message::Text(text) => Some(Message::Text(text.as_str().to_owned())),
+            tungstenite::Message::Binary(data) => Some(Message::Binary(data)),
+            tungstenite::Message::Ping(data) => Some(Message::Ping(data)),
+            tungstenite::Message::Pong(data) => Some(Message::Pong(data)),
+            tungstenite::Message::Close(frame) => {
+                Some(Message::Close(frame.map(|frame| CloseFrame {
+                    code: frame.code.into(),
+                    reason: frame.reason.as_str().to_owned(),
+                })))
+            }
+            // Raw frames are never returned when reading.
+            tungstenite::Message::Frame(_) => None,
+        }
+    }
+}
+
+/// Errors returned by [`WebSocket`] operations.
+#[derive(Debug)]
+pub enum WebSocketError {
+    /// The connection failed or the peer violated the protocol.
+    Connection(tungstenite::Error),
+    /// A message could not be encoded or decoded as JSON.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for WebSocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebSocketError::Connection(e) => write!(f, "WebSocket error: {}", e),
+            WebSocketError::Json(e) => write!(f, "Invalid JSON message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WebSocketError {}
+
+impl From<tungstenite::Error> for WebSocketError {
+    fn from(e: tungstenite::Error) -> Self {
+        WebSocketError::Connection(e)
+    }
+}
+
+impl From<serde_json::Error> for WebSocketError {
+    fn from(e: serde_json::Error) -> Self {
+        WebSocketError::Json(e)
+    }
+}
+
+/// An established WebSocket connection.
+///
+/// Also implements [`Stream`] of incoming messages, so the usual
+/// `StreamExt` combinators can be used to read from it.
+pub struct WebSocket {
+    inner: WebSocketStream<TokioIo<Upgraded>>,
+}
+
+impl WebSocket {
+    /// Receives the next message, or `None` once the connection is closed.
+    pub async fn recv(&mut self) -> Option<Result<Message, WebSocketError>> {
+        self.next().await
+    }
+
+    /// Sends a message.
+    pub async fn send(&mut self, message: Message) -> Result<(), WebSocketError> {
+        self.inner.send(message.into()).await?;
+        Ok(())
+    }
+
+    /// Serializes a value as JSON and sends it as a text message.
+    pub async fn send_json<T: Serialize>(&mut self, value: &T) -> Result<(), WebSocketError> {
+        let text = serde_json::to_string(value)?;
+        self.send(Message::Text(text)).await
+    }
+
+    /// Receives the next text or binary message and deserializes it from JSON.
+    ///
+    /// Pings and pongs are skipped. Returns `None` once the connection is closed.
+    pub async fn recv_json<T: DeserializeOwned>(&mut self) -> Option<Result<T, WebSocketError>> {
+        loop {
+            let result = match self.recv().await? {
+                Ok(Message::Text(text)) => serde_json::from_str(&text),
+                Ok(Message::Binary(data)) => serde_json::from_slice(&data),
+                Ok(Message::Ping(_) | Message::Pong(_)) => continue,
+                Ok(Message::Close(_)) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+            return Some(result.map_err(WebSocketError::from));
+        }
+    }
+
+    /// Sends a close message and waits for the connection to shut down.
+    pub async fn close(mut self, frame: Option<CloseFrame>) -> Result<(), WebSocketError> {
+        self.inner
+            .close(frame.map(|frame| tungstenite::protocol::CloseFrame {
+                code: frame.code.into(),
+                reason: frame.reason.into(),
+            }))
+            .await?;
+        Ok(())
+    }
+}
+
+impl Stream for WebSocket {
+    type Item = Result<Message, WebSocketError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(message))) => {
+                    if let Some(message) = Message::from_tungstenite(message) {
+                        return Poll::Ready(Some(Ok(message)));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgrade_parts(headers: &[(&str, &str)]) -> http::request::Parts {
+        let mut builder = http::Request::builder().method(Method::GET);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let (mut parts, _) = builder.body(()).unwrap().into_parts();
+        let mut req = http::Request::new(());
+        parts.extensions.insert(hyper::upgrade::on(&mut req));
+        parts
+    }
+
+    const HANDSHAKE: &[(&str, &str)] = &[
+        ("connection", "keep-alive, Upgrade"),
+        ("upgrade", "websocket"),
+        ("sec-websocket-version", "13"),
+        ("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="),
+        ("sec-websocket-protocol", "chat.v2, chat.v1"),
+    ];
+
+    async fn extract(parts: &http::request::Parts) -> Result<WebSocketUpgrade, Error> {
+        let state = Arc::new(AppState::new());
+        WebSocketUpgrade::from_request_parts(parts, &PathParams::new(), &state).await
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_response() {
+        let upgrade = extract(&upgrade_parts(HANDSHAKE)).await.unwrap();
+        let response = upgrade.on_upgrade(|_| async {});
+
+        assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(
+            response.headers().get(header::UPGRADE).unwrap(),
+            "websocket"
+        );
+        // Example key and accept value from RFC 6455.
+        assert_eq!(
+            response
+                .headers()
+                .get(header::SEC_WEBSOCKET_ACCEPT)
+                .unwrap(),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert!(
+            response
+                .headers()
+                .get(header::SEC_WEBSOCKET_PROTOCOL)
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_protocol_negotiation() {
+        let upgrade = extract(&upgrade_parts(HANDSHAKE))
+            .await
+            .unwrap()
+            .protocols(["chat.v1", "chat.v3"]);
+        assert_eq!(upgrade.selected_protocol().unwrap(), "chat.v1");
+
+        let response = upgrade.on_upgrade(|_| async {});
+        assert_eq!(
+            response
+                .headers()
+                .get(header::SEC_WEBSOCKET_PROTOCOL)
+                .unwrap(),
+            "chat.v1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_missing_upgrade_headers() {
+        let err = extract(&upgrade_parts(&[("sec-websocket-version", "13")]))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unsupported_version() {
+        let err = extract(&upgrade_parts(&[
+            ("connection", "upgrade"),
+            ("upgrade", "websocket"),
+            ("sec-websocket-version", "8"),
+            ("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="),
+        ]))
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(err.status, 400);
+        assert_eq!(err.message, "Unsupported WebSocket version");
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        let message = Message::Close(Some(CloseFrame {
+            code: 1000,
+            reason: "bye".to_string(),
+        }));
+        let converted = Message::from_tungstenite(message.clone().into()).unwrap();
+        assert_eq!(converted, message);
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "docs")]
+//! Integration tests for the embedded API docs page.
+
+use http::StatusCode;
+use rapina::openapi::DocsUi;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[get("/hello")]
+async fn hello() -> &'static str {
+    "Hello!"
+}
+
+fn app(ui: DocsUi) -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .openapi("Pets", "2.1.0")
+        .docs(ui)
+        .router(Router::new().get("/hello", hello))
+}
+
+#[tokio::test]
+async fn test_docs_page_is_served() {
+    let client = TestClient::new(app(DocsUi::SwaggerUi)).await;
+
+    let response = client.get("/__rapina/docs").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let html = response.text();
+    assert!(html.contains("<title>Pets 2.1.0</title>"));
+    assert!(html.contains("/__rapina/docs/swagger-ui-bundle.js"));
+}
+
+#[tokio::test]
+async fn test_docs_assets_are_served() {
+    let client = TestClient::new(app(DocsUi::Redoc)).await;
+
+    let response = client
+        .get("/__rapina/docs/redoc.standalone.js")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/javascript; charset=utf-8"
+    );
+    assert!(response.headers().contains_key("cache-control"));
+    assert!(!response.text().is_empty());
+
+    let response = client.get("/__rapina/docs/missing.js").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_docs_enables_openapi_endpoint() {
+    let client = TestClient::new(
+        Rapina::new()
+            .with_introspection(false)
+            .docs(DocsUi::Scalar)
+            .router(Router::new().get("/hello", hello)),
+    )
+    .await;
+
+    let response = client.get("/__rapina/openapi.json").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let spec: serde_json::Value = response.json();
+    assert!(spec["paths"]["/hello"]["get"].is_object());
+}
+
+#[tokio::test]
+async fn test_docs_page_enables_bearer_auth_with_auth() {
+    let app = app(DocsUi::SwaggerUi).with_auth(AuthConfig::new("secret", 3600));
+    let client = TestClient::new(app).await;
+
+    let html = client.get("/__rapina/docs").send().await.text();
+    assert!(html.contains("const BEARER_AUTH = true;"));
+}
@@ -362,6 +362,26 @@ async fn test_router_404_response() {
     let client = TestClient::new(app).await;
     let response = client.get("/not-exists").send().await;
 
-    // Router returns plain 404, not JSON error
+    // Router returns the standard JSON error with a trace_id
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "NOT_FOUND");
+    assert_eq!(json["error"]["message"], "no route matches GET /not-exists");
+    assert!(!json["trace_id"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_router_405_response() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().route(http::Method::GET, "/exists", |_, _, _| async { "found" }));
+
+    let client = TestClient::new(app).await;
+    let response = client.delete("/exists").send().await;
+
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, HEAD");
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "METHOD_NOT_ALLOWED");
+    assert!(json["trace_id"].is_string());
 }
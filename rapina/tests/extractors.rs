@@ -225,6 +225,50 @@ async fn test_path_extraction_multiple_params() {
     assert_eq!(response.text(), "User 10 - Post 99");
 }
 
+#[get("/orgs/:org/users/:user_id")]
+async fn get_member(path: Path<(String, u64)>) -> String {
+    let (org, user_id) = path.into_inner();
+    format!("{} in {}", user_id, org)
+}
+
+#[derive(Deserialize)]
+struct MemberPath {
+    user_id: u64,
+    org: String,
+}
+
+#[get("/orgs/:org/members/:user_id")]
+async fn get_member_by_name(path: Path<MemberPath>) -> String {
+    let path = path.into_inner();
+    format!("{} in {}", path.user_id, path.org)
+}
+
+#[tokio::test]
+async fn test_path_extraction_tuple_and_struct() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/orgs/:org/users/:user_id", get_member)
+            .get("/orgs/:org/members/:user_id", get_member_by_name),
+    );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/orgs/acme/users/7").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "7 in acme");
+
+    let response = client.get("/orgs/acme/members/7").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "7 in acme");
+
+    let response = client.get("/orgs/acme/members/seven").send().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json();
+    assert_eq!(
+        json["error"]["message"],
+        "Path parameter 'user_id' must be a valid u64, got 'seven'"
+    );
+}
+
 // Headers Extractor Tests
 
 #[tokio::test]
@@ -0,0 +1,139 @@
+//! Integration tests for the fuzzer.
+
+use http::StatusCode;
+use rapina::prelude::*;
+use rapina::response::full;
+use rapina::testing::Fuzzer;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema, Validate)]
+struct NewOrder {
+    #[validate(range(min = 1, max = 1000))]
+    quantity: u32,
+    note: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct Page {
+    page: Option<u32>,
+    tag: Option<String>,
+}
+
+#[post("/orders")]
+async fn create_order(order: Validated<Json<NewOrder>>) -> Result<String> {
+    let order = order.into_inner().0;
+    Ok(format!(
+        "{} x {}",
+        order.quantity,
+        order.note.unwrap_or_default()
+    ))
+}
+
+#[get("/orders/:id")]
+async fn get_order(id: Path<u64>, page: Query<Page>) -> String {
+    format!("{} {:?} {:?}", id.into_inner(), page.0.page, page.0.tag)
+}
+
+#[get("/ratio/:n")]
+async fn ratio(n: Path<u32>) -> String {
+    (100 / n.into_inner()).to_string()
+}
+
+#[post("/stock")]
+async fn add_stock(order: Json<NewOrder>) -> Result<String> {
+    if order.0.quantity > 50 {
+        return Err(Error::internal("stock overflow"));
+    }
+    Ok(order.0.quantity.to_string())
+}
+
+#[tokio::test]
+async fn test_fuzzer_passes_well_behaved_routes() {
+    let app = Rapina::new().router(
+        Router::new()
+            .post("/orders", create_order)
+            .get("/orders/:id", get_order),
+    );
+
+    let report = Fuzzer::new(app).seed(1).cases(100).run().await;
+    report.assert_ok();
+    assert_eq!(report.seed, 1);
+    assert_eq!(report.requests, 200);
+}
+
+#[tokio::test]
+async fn test_fuzzer_shrinks_server_errors() {
+    let app = Rapina::new().router(Router::new().post("/stock", add_stock));
+
+    let report = Fuzzer::new(app).seed(1).run().await;
+    assert!(!report.is_ok());
+    let failure = &report.failures[0];
+    assert_eq!(failure.route, "POST /stock");
+    assert_eq!(failure.status, Some(StatusCode::INTERNAL_SERVER_ERROR));
+    // The optional note is dropped and the quantity halved while it fails
+    let body: serde_json::Value =
+        serde_json::from_str(failure.request.body.as_deref().unwrap()).unwrap();
+    let quantity = body["quantity"].as_u64().unwrap();
+    assert_eq!(body.as_object().unwrap().len(), 1, "{}", body);
+    assert!((51..=101).contains(&quantity), "{}", body);
+}
+
+#[tokio::test]
+async fn test_fuzzer_reports_panics() {
+    let app = Rapina::new().router(Router::new().get("/ratio/:n", ratio));
+
+    let report = Fuzzer::new(app).seed(1).run().await;
+    let failure = &report.failures[0];
+    assert_eq!(failure.request.uri, "/ratio/0");
+    assert!(failure.reason.contains("panicked"), "{}", failure.reason);
+
+    let message = std::panic::catch_unwind(|| report.assert_ok()).unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains("reproduce with `.seed(1)`"), "{}", message);
+    assert!(message.contains("GET /ratio/{n}"), "{}", message);
+}
+
+/// Replaces client error bodies with plain text.
+struct PlainErrors;
+
+impl Middleware for PlainErrors {
+    fn handle<'a>(
+        &'a self,
+        req: http::Request<rapina::extract::RequestBody>,
+        _ctx: &'a rapina::context::RequestContext,
+        next: Next<'a>,
+    ) -> rapina::middleware::BoxFuture<'a, http::Response<rapina::response::BoxBody>> {
+        Box::pin(async move {
+            let response = next.run(req).await;
+            if !response.status().is_client_error() {
+                return response;
+            }
+            http::Response::builder()
+                .status(response.status())
+                .body(full("bad request"))
+                .unwrap()
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_fuzzer_requires_structured_rejections() {
+    let app = Rapina::new()
+        .middleware(PlainErrors)
+        .router(Router::new().get("/orders/:id", get_order));
+
+    let report = Fuzzer::new(app).seed(1).run().await;
+    let failure = &report.failures[0];
+    assert!(
+        failure.reason.contains("no `ErrorResponse` body"),
+        "{}",
+        failure.reason
+    );
+    assert_eq!(failure.response, "bad request");
+    // Shrunk to the malformed part alone
+    assert!(
+        failure.request.uri.len() <= "/orders/fuzz".len(),
+        "{}",
+        failure.request
+    );
+}
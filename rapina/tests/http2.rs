@@ -0,0 +1,125 @@
+//! Integration tests for HTTP/2 and h2c serving.
+
+use http::{StatusCode, Version};
+use rapina::prelude::*;
+use rapina::server::Protocol;
+use rapina::sse::{Event, Sse};
+use rapina::testing::TestClient;
+
+#[get("/hello")]
+async fn hello() -> &'static str {
+    "Hello!"
+}
+
+#[get("/events")]
+async fn events() -> Sse<rapina::futures_util::stream::Iter<std::vec::IntoIter<Result<Event>>>> {
+    Sse::new(rapina::futures_util::stream::iter(vec![
+        Ok(Event::new().data("one")),
+        Ok(Event::new().data("two")),
+    ]))
+}
+
+fn app() -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/hello", hello).get("/events", events))
+}
+
+#[tokio::test]
+async fn test_auto_serves_http1() {
+    let client = TestClient::new(app()).await;
+    let response = client.get("/hello").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.version(), Version::HTTP_11);
+    assert_eq!(response.text(), "Hello!");
+}
+
+#[tokio::test]
+async fn test_auto_serves_h2c_prior_knowledge() {
+    let client = TestClient::new(app()).await.http2();
+    let response = client.get("/hello").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.version(), Version::HTTP_2);
+    assert_eq!(response.text(), "Hello!");
+}
+
+#[tokio::test]
+async fn test_http2_streams_sse() {
+    let client = TestClient::new(app()).await.http2();
+    let response = client.get("/events").send().await;
+
+    assert_eq!(response.version(), Version::HTTP_2);
+    assert_eq!(response.text(), "data: one\n\ndata: two\n\n");
+}
+
+#[tokio::test]
+async fn test_http2_concurrent_requests_share_connection() {
+    let app = app().server_config(ServerConfig::new().http2_max_concurrent_streams(16));
+    let client = TestClient::new(app).await.http2();
+
+    let responses =
+        futures_util::future::join_all((0..8).map(|_| client.get("/hello").send())).await;
+
+    for response in responses {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.version(), Version::HTTP_2);
+    }
+}
+
+#[tokio::test]
+async fn test_http2_only_server() {
+    let app = app().server_config(
+        ServerConfig::new()
+            .protocol(Protocol::Http2)
+            .http2_keep_alive(
+                std::time::Duration::from_secs(5),
+                std::time::Duration::from_secs(5),
+            )
+            .http2_max_header_list_size(16 * 1024),
+    );
+    let client = TestClient::new(app).await.http2();
+    let response = client.get("/hello").send().await;
+
+    assert_eq!(response.version(), Version::HTTP_2);
+    assert_eq!(response.text(), "Hello!");
+}
+
+#[tokio::test]
+async fn test_http1_only_server_rejects_h2c() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let app = app().server_config(ServerConfig::new().protocol(Protocol::Http1));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/hello").send().await;
+    assert_eq!(response.version(), Version::HTTP_11);
+
+    // An HTTP/1.1-only server never answers the HTTP/2 preface with HTTP/2 frames
+    let mut stream = tokio::net::TcpStream::connect(client.addr()).await.unwrap();
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = [0u8; 64];
+    let n = stream.read(&mut buf).await.unwrap();
+    assert!(n == 0 || buf.starts_with(b"HTTP/1.1 "), "{:?}", &buf[..n]);
+}
+
+#[tokio::test]
+async fn test_http1_max_headers() {
+    let app = app().server_config(ServerConfig::new().max_headers(4));
+    let client = TestClient::new(app).await;
+
+    let mut request = client.get("/hello");
+    for i in 0..8 {
+        request = request.header(&format!("x-extra-{}", i), "1");
+    }
+    let response = request.send().await;
+
+    assert_eq!(
+        response.status(),
+        StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+    );
+}
@@ -1,12 +1,17 @@
 //! Integration tests for middleware functionality.
 
+use flate2::read::GzDecoder;
 use http::StatusCode;
+use rapina::bytes::Bytes;
+use rapina::futures_util::stream;
 use rapina::middleware::{
     BodyLimitMiddleware, CompressionConfig, CorsConfig, RateLimitConfig, RateLimitMiddleware,
     TRACE_ID_HEADER, TimeoutMiddleware, TraceIdMiddleware,
 };
 use rapina::prelude::*;
+use rapina::response::Stream;
 use rapina::testing::TestClient;
+use std::io::Read;
 use std::time::Duration;
 
 #[tokio::test]
@@ -91,6 +96,28 @@ async fn test_timeout_middleware_passes_fast_request() {
     assert_eq!(response.text(), "fast response");
 }
 
+#[tokio::test]
+async fn test_timeout_middleware_passes_streamed_response() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TimeoutMiddleware::new(Duration::from_secs(5)))
+        .router(
+            Router::new().route(http::Method::GET, "/stream", |_, _, _| async {
+                let chunks = stream::iter(vec![
+                    Ok::<_, std::io::Error>(Bytes::from("first,")),
+                    Ok(Bytes::from("second")),
+                ]);
+                Stream::new(chunks).content_type("text/plain")
+            }),
+        );
+
+    let client = TestClient::new(app).await;
+    let response = client.get("/stream").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "first,second");
+}
+
 #[tokio::test]
 async fn test_body_limit_middleware_allows_small_body() {
     let app = Rapina::new()
@@ -111,6 +138,23 @@ async fn test_body_limit_middleware_allows_small_body() {
     assert!(response.text().contains("13 bytes")); // "small payload" is 13 bytes
 }
 
+#[tokio::test]
+async fn test_body_limit_middleware_rejects_large_body() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(BodyLimitMiddleware::new(8))
+        .router(Router::new().route(http::Method::POST, "/upload", |_, _, _| async { "ok" }));
+
+    let client = TestClient::new(app).await;
+    let response = client
+        .post("/upload")
+        .body("larger than eight")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
 #[tokio::test]
 async fn test_multiple_middlewares() {
     let app = Rapina::new()
@@ -484,6 +528,38 @@ async fn test_compression_gzip() {
     assert_eq!(response.headers().get("vary").unwrap(), "Accept-Encoding");
 }
 
+#[tokio::test]
+async fn test_compression_gzip_streamed_response() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_compression(CompressionConfig::default())
+        .router(
+            Router::new().route(http::Method::GET, "/", |_, _, _| async {
+                let chunks = stream::iter(
+                    (0..3).map(|i| Ok::<_, std::io::Error>(Bytes::from(format!("chunk {}\n", i)))),
+                );
+                Stream::new(chunks).content_type("text/plain")
+            }),
+        );
+
+    let client = TestClient::new(app).await;
+    let response = client
+        .get("/")
+        .header("Accept-Encoding", "gzip")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-encoding").unwrap(), "gzip");
+    assert!(response.headers().get("content-length").is_none());
+
+    let mut decoded = String::new();
+    GzDecoder::new(&response.bytes()[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, "chunk 0\nchunk 1\nchunk 2\n");
+}
+
 #[tokio::test]
 async fn test_compression_skips_small_response() {
     let app = Rapina::new()
@@ -543,3 +619,109 @@ async fn test_trace_id_middleware_preserves_incoming_trace_id() {
     let header_value = response.headers().get(TRACE_ID_HEADER).unwrap();
     assert_eq!(header_value.to_str().unwrap(), custom_trace_id);
 }
+
+/// Appends its label to the `x-trail` response header on the way out.
+struct Trail(&'static str);
+
+impl Middleware for Trail {
+    fn handle<'a>(
+        &'a self,
+        req: http::Request<rapina::extract::RequestBody>,
+        _ctx: &'a rapina::context::RequestContext,
+        next: Next<'a>,
+    ) -> rapina::middleware::BoxFuture<'a, http::Response<rapina::response::BoxBody>> {
+        Box::pin(async move {
+            let mut response = next.run(req).await;
+            response
+                .headers_mut()
+                .append("x-trail", http::HeaderValue::from_static(self.0));
+            response
+        })
+    }
+}
+
+#[get("/reports")]
+#[middleware(Trail("route"))]
+async fn reports() -> &'static str {
+    "reports"
+}
+
+#[get("/health")]
+async fn health() -> &'static str {
+    "ok"
+}
+
+fn trail(response: &rapina::testing::TestResponse) -> Vec<&str> {
+    response
+        .headers()
+        .get_all("x-trail")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_route_and_group_middleware_order() {
+    let admin = Router::new().get("/reports", reports).layer(Trail("inner"));
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(Trail("global"))
+        .router(
+            Router::new()
+                .get("/health", health)
+                .group("/admin", admin)
+                .layer(Trail("outer")),
+        );
+    let client = TestClient::new(app).await;
+
+    // Innermost middleware appends first
+    let response = client.get("/admin/reports").send().await;
+    assert_eq!(response.text(), "reports");
+    assert_eq!(trail(&response), vec!["route", "inner", "outer", "global"]);
+
+    let response = client.get("/health").send().await;
+    assert_eq!(trail(&response), vec!["outer", "global"]);
+
+    let response = client.get("/missing").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(trail(&response), vec!["global"]);
+}
+
+#[tokio::test]
+async fn test_route_middleware_listed_in_introspection() {
+    let router = Router::new()
+        .group(
+            "/admin",
+            Router::new().get("/reports", reports).layer(Trail("inner")),
+        )
+        .get("/health", health);
+
+    let routes = router.routes();
+    assert_eq!(routes[0].middleware, vec!["Trail", "Trail"]);
+    assert!(routes[1].middleware.is_empty());
+}
+
+#[tokio::test]
+async fn test_route_timeout_layer_only_applies_to_group() {
+    #[get("/slow")]
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        "slow"
+    }
+
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new().get("/other/slow", slow).group(
+            "/admin",
+            Router::new()
+                .get("/slow", slow)
+                .layer(TimeoutMiddleware::new(Duration::from_millis(50))),
+        ),
+    );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/admin/slow").send().await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let response = client.get("/other/slow").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
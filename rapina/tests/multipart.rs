@@ -0,0 +1,268 @@
+//! Integration tests for multipart/form-data uploads.
+
+use http::StatusCode;
+use rapina::middleware::BodyLimitMiddleware;
+use rapina::multipart::{Multipart, MultipartConfig, MultipartForm, UploadedFile};
+use rapina::openapi::build_openapi_spec;
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const BOUNDARY: &str = "rapina-boundary";
+
+/// Builds a multipart body from `(name, filename, contents)` parts.
+fn multipart_body(parts: &[(&str, Option<&str>, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, filename, contents) in parts {
+        body.extend_from_slice(format!("--{}\r\n", BOUNDARY).as_bytes());
+        match filename {
+            Some(filename) => body.extend_from_slice(
+                format!(
+                    "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                    name, filename
+                )
+                .as_bytes(),
+            ),
+            None => body.extend_from_slice(
+                format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name).as_bytes(),
+            ),
+        }
+        body.extend_from_slice(contents);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+fn content_type() -> String {
+    format!("multipart/form-data; boundary={}", BOUNDARY)
+}
+
+#[post("/raw")]
+async fn raw_upload(mut multipart: Multipart) -> Result<String> {
+    let mut summary = Vec::new();
+    while let Some(mut field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        let file_name = field.file_name().map(str::to_string);
+        let mut size = 0;
+        while let Some(chunk) = field.chunk().await? {
+            size += chunk.len();
+        }
+        summary.push(format!("{}:{:?}:{}", name, file_name, size));
+    }
+    Ok(summary.join(","))
+}
+
+#[derive(MultipartForm)]
+struct Upload {
+    title: String,
+    count: Option<u32>,
+    #[multipart(rename = "file")]
+    files: Vec<UploadedFile>,
+}
+
+#[post("/upload")]
+async fn form_upload(form: MultipartForm<Upload>) -> Result<String> {
+    let upload = form.into_inner();
+    let mut summary = vec![upload.title, format!("{:?}", upload.count)];
+    for file in &upload.files {
+        let contents = file
+            .bytes()
+            .await
+            .map_err(|e| Error::internal(e.to_string()))?;
+        summary.push(format!(
+            "{}={}:{}",
+            file.file_name().unwrap_or_default(),
+            String::from_utf8_lossy(&contents),
+            file.path().is_some()
+        ));
+    }
+    Ok(summary.join(","))
+}
+
+fn app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .post("/raw", raw_upload)
+            .post("/upload", form_upload),
+    )
+}
+
+#[tokio::test]
+async fn test_multipart_streams_fields() {
+    let client = TestClient::new(app()).await;
+    let body = multipart_body(&[
+        ("title", None, b"hello"),
+        ("file", Some("a.bin"), &[0u8; 1000]),
+    ]);
+
+    let response = client
+        .post("/raw")
+        .header("content-type", &content_type())
+        .body(body)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "title:None:5,file:Some(\"a.bin\"):1000");
+}
+
+#[tokio::test]
+async fn test_multipart_rejects_other_content_types() {
+    let client = TestClient::new(app()).await;
+    let response = client
+        .post("/raw")
+        .header("content-type", "application/json")
+        .body("{}")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_multipart_form_collects_text_and_files() {
+    let client = TestClient::new(app()).await;
+    let body = multipart_body(&[
+        ("title", None, b"docs"),
+        ("file", Some("a.txt"), b"first"),
+        ("file", Some("b.txt"), b"second"),
+        ("ignored", None, b"x"),
+    ]);
+
+    let response = client
+        .post("/upload")
+        .header("content-type", &content_type())
+        .body(body)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.text(),
+        "docs,None,a.txt=first:false,b.txt=second:false"
+    );
+}
+
+#[tokio::test]
+async fn test_multipart_form_missing_field() {
+    let client = TestClient::new(app()).await;
+    let body = multipart_body(&[("count", None, b"3")]);
+
+    let response = client
+        .post("/upload")
+        .header("content-type", &content_type())
+        .body(body)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json();
+    assert!(json["error"]["message"].as_str().unwrap().contains("title"));
+}
+
+#[tokio::test]
+async fn test_multipart_spills_large_files_to_disk() {
+    let app = app().state(MultipartConfig::new().spill_to_disk(4));
+    let client = TestClient::new(app).await;
+    let body = multipart_body(&[
+        ("title", None, b"docs"),
+        ("count", None, b"2"),
+        ("file", Some("small.txt"), b"tiny"),
+        ("file", Some("large.txt"), b"larger than four"),
+    ]);
+
+    let response = client
+        .post("/upload")
+        .header("content-type", &content_type())
+        .body(body)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.text(),
+        "docs,Some(2),small.txt=tiny:false,large.txt=larger than four:true"
+    );
+}
+
+#[tokio::test]
+async fn test_multipart_field_size_limit() {
+    let app = app().state(MultipartConfig::new().max_field_size(16));
+    let client = TestClient::new(app).await;
+    let body = multipart_body(&[("file", Some("a.bin"), &[1u8; 64])]);
+
+    let response = client
+        .post("/raw")
+        .header("content-type", &content_type())
+        .body(body)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_multipart_total_size_limit() {
+    let app = app().state(MultipartConfig::new().max_total_size(128));
+    let client = TestClient::new(app).await;
+    let body = multipart_body(&[("a", None, &[b'a'; 100]), ("b", None, &[b'b'; 100])]);
+
+    let response = client
+        .post("/raw")
+        .header("content-type", &content_type())
+        .body(body)
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_multipart_respects_body_limit_for_chunked_requests() {
+    let app = app().middleware(BodyLimitMiddleware::new(256));
+    let client = TestClient::new(app).await;
+    let body = multipart_body(&[("file", Some("a.bin"), &[1u8; 1024])]);
+
+    // Chunked encoding hides the size from the Content-Length check
+    let mut stream = TcpStream::connect(client.addr()).await.unwrap();
+    let head = format!(
+        "POST /raw HTTP/1.1\r\nhost: localhost\r\ncontent-type: {}\r\ntransfer-encoding: chunked\r\nconnection: close\r\n\r\n",
+        content_type()
+    );
+    stream.write_all(head.as_bytes()).await.unwrap();
+    for chunk in body.chunks(128) {
+        stream
+            .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+            .await
+            .unwrap();
+        stream.write_all(chunk).await.unwrap();
+        stream.write_all(b"\r\n").await.unwrap();
+    }
+    stream.write_all(b"0\r\n\r\n").await.unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    assert!(response.starts_with(b"HTTP/1.1 413 "));
+}
+
+#[test]
+fn test_multipart_openapi_request_body() {
+    let router = Router::new()
+        .post("/raw", raw_upload)
+        .post("/upload", form_upload);
+    let spec =
+        serde_json::to_value(build_openapi_spec("Uploads", "1.0.0", &router.routes())).unwrap();
+
+    let form = &spec["paths"]["/upload"]["post"]["requestBody"]["content"]["multipart/form-data"]["schema"];
+    assert_eq!(form["type"], "object");
+    assert_eq!(form["properties"]["title"]["type"], "string");
+    assert_eq!(form["properties"]["count"]["type"], "integer");
+    assert_eq!(form["properties"]["file"]["type"], "array");
+    assert_eq!(form["properties"]["file"]["items"]["format"], "binary");
+    assert_eq!(form["required"], serde_json::json!(["title"]));
+
+    let raw = &spec["paths"]["/raw"]["post"]["requestBody"]["content"]["multipart/form-data"];
+    assert_eq!(raw["schema"]["type"], "object");
+}
@@ -0,0 +1,523 @@
+//! Integration tests for the OpenAPI spec generated from route macros.
+
+use rapina::openapi::{
+    OpenApiError, OpenApiOptions, OpenApiVersion, build_openapi_spec, try_build_openapi_spec,
+};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateUser {
+    name: String,
+    email: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Login {
+    username: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct Pagination {
+    /// Page to return, starting at 1
+    page: u32,
+    search: Option<String>,
+}
+
+#[post("/users")]
+async fn create_user(body: Json<CreateUser>) -> String {
+    let user = body.into_inner();
+    format!("{} {:?}", user.name, user.email)
+}
+
+#[post("/login")]
+async fn login(form: Form<Login>) -> String {
+    form.into_inner().username
+}
+
+#[get("/users/:id/posts")]
+async fn list_posts(id: Path<u64>, query: Query<Pagination>) -> String {
+    let query = query.0;
+    format!("{} {} {:?}", id.into_inner(), query.page, query.search)
+}
+
+fn spec() -> serde_json::Value {
+    let router = Router::new()
+        .post("/users", create_user)
+        .post("/login", login)
+        .get("/users/:id/posts", list_posts);
+    serde_json::to_value(build_openapi_spec("Test API", "1.0.0", &router.routes())).unwrap()
+}
+
+#[test]
+fn test_json_body_documented() {
+    let spec = spec();
+    let body = &spec["paths"]["/users"]["post"]["requestBody"];
+
+    assert_eq!(body["required"], true);
+    assert_eq!(
+        body["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/CreateUser"
+    );
+    let schema = &spec["components"]["schemas"]["CreateUser"];
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["properties"]["name"]["type"], "string");
+    assert_eq!(schema["required"], serde_json::json!(["name"]));
+}
+
+#[test]
+fn test_body_without_json_schema_is_omitted() {
+    let spec = spec();
+    assert!(spec["paths"]["/login"]["post"].get("requestBody").is_none());
+}
+
+#[test]
+fn test_query_struct_expanded_into_parameters() {
+    let spec = spec();
+    let params = spec["paths"]["/users/{id}/posts"]["get"]["parameters"]
+        .as_array()
+        .unwrap();
+
+    let find = |name: &str| params.iter().find(|p| p["name"] == name).unwrap();
+
+    let page = find("page");
+    assert_eq!(page["in"], "query");
+    assert_eq!(page["required"], true);
+    assert_eq!(page["schema"]["type"], "integer");
+    assert_eq!(page["description"], "Page to return, starting at 1");
+
+    let search = find("search");
+    assert_eq!(search["in"], "query");
+    assert_eq!(search["required"], false);
+
+    let id = find("id");
+    assert_eq!(id["in"], "path");
+    assert_eq!(id["required"], true);
+    assert_eq!(id["schema"]["type"], "integer");
+    assert_eq!(id["schema"]["format"], "uint64");
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Address {
+    city: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct User {
+    id: u64,
+    address: Address,
+    nickname: Option<String>,
+}
+
+mod admin {
+    use rapina::prelude::*;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct User {
+        pub name: String,
+    }
+}
+
+#[get("/users/:id")]
+async fn get_user(id: Path<u64>) -> Json<User> {
+    Json(User {
+        id: id.into_inner(),
+        address: Address {
+            city: "Lisbon".to_string(),
+        },
+        nickname: None,
+    })
+}
+
+#[get("/users")]
+async fn list_users() -> Json<Vec<User>> {
+    Json(Vec::new())
+}
+
+#[get("/admins/me")]
+async fn get_admin() -> Json<admin::User> {
+    Json(admin::User {
+        name: "root".to_string(),
+    })
+}
+
+fn user_routes() -> Router {
+    Router::new()
+        .get("/users/:id", get_user)
+        .get("/users", list_users)
+}
+
+#[put("/users/:id")]
+async fn save_user(id: Path<u64>) -> String {
+    id.into_inner().to_string()
+}
+
+#[test]
+fn test_multi_method_routes_get_unique_operation_ids() {
+    let router = Router::new().post("/users", create_user).on(
+        &[Method::PUT, Method::PATCH],
+        "/users/:id",
+        save_user,
+    );
+    let spec =
+        serde_json::to_value(build_openapi_spec("Test API", "1.0.0", &router.routes())).unwrap();
+
+    assert_eq!(
+        spec["paths"]["/users"]["post"]["operationId"],
+        "create_user"
+    );
+    let item = &spec["paths"]["/users/{id}"];
+    assert_eq!(item["put"]["operationId"], "save_user_put");
+    assert_eq!(item["patch"]["operationId"], "save_user_patch");
+}
+
+#[test]
+fn test_shared_schemas_hoisted_into_components() {
+    let spec = serde_json::to_value(build_openapi_spec(
+        "Test API",
+        "1.0.0",
+        &user_routes().routes(),
+    ))
+    .unwrap();
+
+    let single = &spec["paths"]["/users/{id}"]["get"]["responses"]["200"]["content"]["application/json"]
+        ["schema"];
+    assert_eq!(single["$ref"], "#/components/schemas/User");
+
+    let list = &spec["paths"]["/users"]["get"]["responses"]["200"]["content"]["application/json"]["schema"];
+    assert_eq!(list["type"], "array");
+    assert_eq!(list["items"]["$ref"], "#/components/schemas/User");
+
+    let schemas = spec["components"]["schemas"].as_object().unwrap();
+    let mut names: Vec<_> = schemas.keys().collect();
+    names.sort();
+    assert_eq!(names, vec!["Address", "ErrorResponse", "User"]);
+    assert_eq!(
+        schemas["User"]["properties"]["address"]["$ref"],
+        "#/components/schemas/Address"
+    );
+    assert_eq!(
+        schemas["User"]["properties"]["nickname"],
+        serde_json::json!({ "type": "string", "nullable": true })
+    );
+    assert!(!spec.to_string().contains("$defs"));
+}
+
+#[test]
+fn test_openapi_31_keeps_json_schema_2020_12() {
+    let spec = try_build_openapi_spec(
+        "Test API",
+        "1.0.0",
+        &user_routes().routes(),
+        OpenApiOptions::new().version(OpenApiVersion::V3_1),
+    )
+    .unwrap();
+    let spec = serde_json::to_value(spec).unwrap();
+
+    assert_eq!(spec["openapi"], "3.1.0");
+    assert_eq!(
+        spec["components"]["schemas"]["User"]["properties"]["nickname"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+}
+
+#[test]
+fn test_same_name_from_different_modules_collides() {
+    let routes = user_routes().get("/admins/me", get_admin).routes();
+
+    let err =
+        try_build_openapi_spec("Test API", "1.0.0", &routes, OpenApiOptions::new()).unwrap_err();
+    assert!(matches!(
+        err,
+        OpenApiError::SchemaCollision { ref name, .. } if name == "User"
+    ));
+}
+
+/// Archive a user
+///
+/// The user keeps their data but can no longer sign in.
+#[tag("Users")]
+#[post("/users/:id/archive")]
+#[deprecated(note = "use `DELETE /users/:id`")]
+#[operation_id = "users.archive"]
+async fn archive_user(id: Path<u64>) -> String {
+    id.into_inner().to_string()
+}
+
+/// List invoices
+#[get("/")]
+#[tag("Billing", "Invoices")]
+async fn list_invoices() -> &'static str {
+    "invoices"
+}
+
+#[get("/:id")]
+async fn get_invoice(id: Path<u64>) -> String {
+    id.into_inner().to_string()
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_doc_comments_and_attributes_documented() {
+    let router = Router::new()
+        .post("/users/:id/archive", archive_user)
+        .group_tagged(
+            "/invoices",
+            Router::new()
+                .get("/", list_invoices)
+                .get("/:id", get_invoice),
+        );
+    let routes = router.routes();
+
+    assert_eq!(routes[0].summary.as_deref(), Some("Archive a user"));
+    assert!(routes[0].deprecated);
+    assert_eq!(routes[2].tags, vec!["invoices"]);
+
+    let spec = serde_json::to_value(build_openapi_spec("Test API", "1.0.0", &routes)).unwrap();
+
+    let archive = &spec["paths"]["/users/{id}/archive"]["post"];
+    assert_eq!(archive["summary"], "Archive a user");
+    assert_eq!(
+        archive["description"],
+        "The user keeps their data but can no longer sign in.\n\nDeprecated: use `DELETE /users/:id`"
+    );
+    assert_eq!(archive["tags"], serde_json::json!(["Users"]));
+    assert_eq!(archive["deprecated"], true);
+    assert_eq!(archive["operationId"], "users.archive");
+
+    let list = &spec["paths"]["/invoices"]["get"];
+    assert_eq!(list["summary"], "List invoices");
+    assert!(list.get("description").is_none());
+    assert_eq!(list["tags"], serde_json::json!(["Billing", "Invoices"]));
+    assert!(list.get("deprecated").is_none());
+
+    let get = &spec["paths"]["/invoices/{id}"]["get"];
+    assert_eq!(get["summary"], "Get invoice");
+    assert_eq!(get["operationId"], "get_invoice");
+    assert_eq!(get["tags"], serde_json::json!(["invoices"]));
+}
+
+#[get("/me")]
+async fn current_user(user: CurrentUser) -> String {
+    user.id
+}
+
+#[public]
+#[post("/sessions")]
+async fn sign_in() -> &'static str {
+    "token"
+}
+
+#[get("/health")]
+#[public]
+async fn health() -> &'static str {
+    "ok"
+}
+
+#[get("/version")]
+async fn version() -> &'static str {
+    "1.0.0"
+}
+
+fn auth_app() -> Rapina {
+    Rapina::new()
+        .with_introspection(false)
+        .openapi("Test API", "1.0.0")
+        .with_auth(AuthConfig::new("secret", 3600))
+        .public_route("GET", "/version")
+        .router(
+            Router::new()
+                .get("/me", current_user)
+                .post("/sessions", sign_in)
+                .get("/health", health)
+                .get("/version", version),
+        )
+}
+
+#[tokio::test]
+async fn test_public_routes_skip_auth() {
+    let client = TestClient::new(auth_app()).await;
+
+    assert_eq!(
+        client.get("/me").send().await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        client.post("/sessions").send().await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(client.get("/health").send().await.status(), StatusCode::OK);
+    assert_eq!(client.get("/version").send().await.status(), StatusCode::OK);
+}
+
+#[get("/profiles/:name")]
+#[public]
+async fn get_profile(name: Path<String>) -> String {
+    name.into_inner()
+}
+
+#[get("/profiles/me")]
+async fn get_own_profile(user: CurrentUser) -> String {
+    user.id
+}
+
+#[tokio::test]
+async fn test_public_param_route_does_not_open_static_sibling() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .openapi("Test API", "1.0.0")
+        .with_auth(AuthConfig::new("secret", 3600))
+        .router(
+            Router::new()
+                .get("/profiles/:name", get_profile)
+                .get("/profiles/me", get_own_profile),
+        );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/profiles/alice").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "alice");
+    assert_eq!(
+        client.get("/profiles/me").send().await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        client
+            .get("/profiles/me")
+            .as_user("bob")
+            .send()
+            .await
+            .text(),
+        "bob"
+    );
+
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+    let public = &spec["paths"]["/profiles/{name}"]["get"];
+    assert_eq!(public["security"], serde_json::json!([]));
+    let protected = &spec["paths"]["/profiles/me"]["get"];
+    assert!(protected.get("security").is_none());
+    assert!(protected["responses"].get("401").is_some());
+}
+
+#[tokio::test]
+async fn test_auth_documented_as_bearer_security_scheme() {
+    let client = TestClient::new(auth_app()).await;
+    let spec: serde_json::Value = client.get("/__rapina/openapi.json").send().await.json();
+
+    assert_eq!(spec["security"], serde_json::json!([{ "bearerAuth": [] }]));
+    assert_eq!(
+        spec["components"]["securitySchemes"]["bearerAuth"]["scheme"],
+        "bearer"
+    );
+
+    let me = &spec["paths"]["/me"]["get"];
+    assert!(me.get("security").is_none());
+    assert_eq!(
+        me["responses"]["401"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/ErrorResponse"
+    );
+
+    for (path, method) in [
+        ("/sessions", "post"),
+        ("/health", "get"),
+        ("/version", "get"),
+    ] {
+        let operation = &spec["paths"][path][method];
+        assert_eq!(operation["security"], serde_json::json!([]), "{}", path);
+        assert!(operation["responses"].get("401").is_none(), "{}", path);
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct MembershipPath {
+    /// Organization slug
+    org: String,
+    user_id: u64,
+}
+
+#[get("/orgs/:org/members/:user_id")]
+async fn get_membership(path: Path<MembershipPath>) -> String {
+    let path = path.into_inner();
+    format!("{} {}", path.org, path.user_id)
+}
+
+#[get("/orgs/:org/users/:user_id")]
+async fn get_org_user(path: Path<(String, u32)>) -> String {
+    let (org, user_id) = path.into_inner();
+    format!("{} {}", org, user_id)
+}
+
+#[test]
+fn test_struct_and_tuple_path_parameters_documented() {
+    let router = Router::new()
+        .get("/orgs/:org/members/:user_id", get_membership)
+        .get("/orgs/:org/users/:user_id", get_org_user);
+    let spec =
+        serde_json::to_value(build_openapi_spec("Test API", "1.0.0", &router.routes())).unwrap();
+
+    for path in [
+        "/orgs/{org}/members/{user_id}",
+        "/orgs/{org}/users/{user_id}",
+    ] {
+        let params = spec["paths"][path]["get"]["parameters"].as_array().unwrap();
+        assert_eq!(params.len(), 2, "{}", path);
+        assert_eq!(params[0]["name"], "org");
+        assert_eq!(params[0]["in"], "path");
+        assert_eq!(params[0]["schema"]["type"], "string");
+        assert_eq!(params[1]["name"], "user_id");
+        assert_eq!(params[1]["schema"]["type"], "integer");
+    }
+
+    let members = &spec["paths"]["/orgs/{org}/members/{user_id}"]["get"]["parameters"];
+    assert_eq!(members[0]["description"], "Organization slug");
+    assert!(
+        spec["components"]["schemas"]
+            .get("MembershipPath")
+            .is_none()
+    );
+}
+
+#[get("/files/*path")]
+async fn download_file(path: Path<String>) -> String {
+    path.into_inner()
+}
+
+#[get("/tickets/:id<uuid>/:code<[A-Z]{3}>")]
+async fn get_ticket() -> &'static str {
+    "ticket"
+}
+
+#[get("/reports/:year<int>/:month?")]
+async fn list_reports() -> &'static str {
+    "reports"
+}
+
+#[test]
+fn test_catch_all_constrained_and_optional_segments_documented() {
+    let router = Router::new()
+        .get("/files/*path", download_file)
+        .get("/tickets/:id<uuid>/:code<[A-Z]{3}>", get_ticket)
+        .get("/reports/:year<int>/:month?", list_reports);
+    let spec =
+        serde_json::to_value(build_openapi_spec("Test API", "1.0.0", &router.routes())).unwrap();
+
+    let params = &spec["paths"]["/files/{path}"]["get"]["parameters"];
+    assert_eq!(params[0]["name"], "path");
+    assert_eq!(params[0]["schema"]["type"], "string");
+
+    let params = &spec["paths"]["/tickets/{id}/{code}"]["get"]["parameters"];
+    assert_eq!(
+        params[0]["schema"],
+        serde_json::json!({ "type": "string", "format": "uuid" })
+    );
+    assert_eq!(params[1]["schema"]["pattern"], "^(?:[A-Z]{3})$");
+
+    let full = &spec["paths"]["/reports/{year}/{month}"]["get"];
+    assert_eq!(full["operationId"], "list_reports");
+    assert_eq!(full["parameters"].as_array().unwrap().len(), 2);
+    assert_eq!(full["parameters"][0]["schema"]["type"], "integer");
+
+    let short = &spec["paths"]["/reports/{year}"]["get"];
+    assert_eq!(short["operationId"], "list_reports_without_month");
+    assert_eq!(short["parameters"].as_array().unwrap().len(), 1);
+}
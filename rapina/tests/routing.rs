@@ -2,6 +2,7 @@
 
 use http::{Method, StatusCode};
 use rapina::prelude::*;
+use rapina::router::UrlForError;
 use rapina::testing::TestClient;
 
 #[tokio::test]
@@ -93,9 +94,41 @@ async fn test_method_not_matching() {
     let response = client.get("/resource").send().await;
     assert_eq!(response.status(), StatusCode::OK);
 
-    // POST should return 404 (method doesn't match)
+    // POST should return 405 (path matches, method doesn't)
     let response = client.post("/resource").send().await;
-    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, HEAD");
+}
+
+#[tokio::test]
+async fn test_method_not_allowed_lists_all_methods() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .route(Method::GET, "/users/:id", |_, _, _| async { "get" })
+            .route(Method::PUT, "/users/:id", |_, _, _| async { "put" }),
+    );
+
+    let client = TestClient::new(app).await;
+    let response = client.delete("/users/1").send().await;
+
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, HEAD, PUT");
+}
+
+#[tokio::test]
+async fn test_static_segment_wins_over_param() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .route(Method::GET, "/users/:id", |_, params, _| async move {
+                format!("user {}", params.get("id").cloned().unwrap_or_default())
+            })
+            .route(Method::GET, "/users/me", |_, _, _| async { "me" }),
+    );
+
+    let client = TestClient::new(app).await;
+
+    assert_eq!(client.get("/users/me").send().await.text(), "me");
+    assert_eq!(client.get("/users/7").send().await.text(), "user 7");
 }
 
 #[tokio::test]
@@ -222,3 +255,410 @@ async fn test_introspection_endpoint() {
     assert!(route_paths.contains(&"/health"));
     assert!(route_paths.contains(&"/users"));
 }
+
+#[get("/articles/:id")]
+async fn get_article(id: Path<u64>) -> String {
+    format!("article {}", id.into_inner())
+}
+
+#[patch("/articles/:id")]
+async fn patch_article(id: Path<u64>) -> String {
+    format!("patched {}", id.into_inner())
+}
+
+#[options("/articles")]
+async fn article_options() -> StatusCode {
+    StatusCode::NO_CONTENT
+}
+
+#[head("/articles")]
+async fn head_articles() -> StatusCode {
+    StatusCode::ACCEPTED
+}
+
+#[get("/catch-all")]
+async fn catch_all() -> &'static str {
+    "any"
+}
+
+#[tokio::test]
+async fn test_patch_head_and_options_routes() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/articles/:id", get_article)
+            .patch("/articles/:id", patch_article)
+            .options("/articles", article_options)
+            .head("/articles", head_articles),
+    );
+    let client = TestClient::new(app).await;
+
+    let response = client.patch("/articles/7").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "patched 7");
+
+    let response = client.options("/articles").send().await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = client.head("/articles").send().await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_head_answered_by_get_without_body() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/articles/:id", get_article));
+    let client = TestClient::new(app).await;
+
+    let response = client.head("/articles/7").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-length").unwrap(), "9");
+    assert!(response.text().is_empty());
+
+    let response = client.delete("/articles/7").send().await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, HEAD");
+}
+
+#[tokio::test]
+async fn test_on_and_any_register_multiple_methods() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .on(
+                &[Method::PUT, Method::PATCH],
+                "/articles/:id",
+                patch_article,
+            )
+            .any("/catch-all", catch_all),
+    );
+    let client = TestClient::new(app).await;
+
+    assert_eq!(client.put("/articles/1").send().await.text(), "patched 1");
+    assert_eq!(client.patch("/articles/1").send().await.text(), "patched 1");
+    assert_eq!(
+        client.get("/articles/1").send().await.status(),
+        StatusCode::METHOD_NOT_ALLOWED
+    );
+
+    for method in [Method::GET, Method::POST, Method::DELETE, Method::OPTIONS] {
+        let response = client.request(method, "/catch-all").send().await;
+        assert_eq!(response.text(), "any");
+    }
+    let response = client.head("/catch-all").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[get("/files/*path")]
+async fn get_file(path: Path<String>) -> String {
+    format!("file {}", path.into_inner())
+}
+
+#[get("/members/:id<int>")]
+async fn get_member(id: Path<i64>) -> String {
+    format!("member {}", id.into_inner())
+}
+
+#[get("/members/me")]
+async fn get_me() -> &'static str {
+    "me"
+}
+
+#[get("/members/:handle")]
+async fn get_member_by_handle(handle: Path<String>) -> String {
+    format!("handle {}", handle.into_inner())
+}
+
+#[get("/posts/:page?")]
+async fn list_posts(page: Path<Option<u32>>) -> String {
+    format!("page {}", page.into_inner().unwrap_or(1))
+}
+
+#[tokio::test]
+async fn test_catch_all_constrained_and_optional_segments() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/files/*path", get_file)
+            .get("/members/:id<int>", get_member)
+            .get("/members/me", get_me)
+            .get("/members/:handle", get_member_by_handle)
+            .get("/posts/:page?", list_posts),
+    );
+    let client = TestClient::new(app).await;
+
+    assert_eq!(
+        client.get("/files/docs/readme.md").send().await.text(),
+        "file docs/readme.md"
+    );
+    assert_eq!(
+        client.get("/files/").send().await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    assert_eq!(client.get("/members/42").send().await.text(), "member 42");
+    assert_eq!(client.get("/members/me").send().await.text(), "me");
+    assert_eq!(
+        client.get("/members/alice").send().await.text(),
+        "handle alice"
+    );
+
+    assert_eq!(client.get("/posts").send().await.text(), "page 1");
+    assert_eq!(client.get("/posts/3").send().await.text(), "page 3");
+}
+
+#[tokio::test]
+async fn test_introspection_lists_path_params() {
+    let app = Rapina::new()
+        .with_introspection(true)
+        .router(Router::new().get("/members/:id<int>", get_member));
+
+    let client = TestClient::new(app).await;
+    let routes: Vec<serde_json::Value> = client.get("/.__rapina/routes").send().await.json();
+    let route = routes
+        .iter()
+        .find(|r| r["path"] == "/members/:id<int>")
+        .unwrap();
+
+    assert_eq!(
+        route["path_params"],
+        serde_json::json!([{ "name": "id", "constraint": "int" }])
+    );
+}
+
+#[get("/")]
+async fn site_fallback() -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, "site fallback".to_string())
+}
+
+#[get("/")]
+async fn org_fallback(org: Path<String>) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("no such page in {}", org.into_inner()),
+    )
+}
+
+#[get("/")]
+async fn custom_method_not_allowed() -> (StatusCode, String) {
+    (StatusCode::METHOD_NOT_ALLOWED, "nope".to_string())
+}
+
+#[tokio::test]
+async fn test_fallbacks_scoped_by_group() {
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/articles/:id", get_article)
+            .fallback(site_fallback)
+            .group(
+                "/orgs/:org",
+                Router::new()
+                    .get("/articles/:id", get_article)
+                    .fallback(org_fallback),
+            )
+            .method_not_allowed(custom_method_not_allowed),
+    );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/missing").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.text(), "site fallback");
+
+    let response = client.post("/orgs/acme/missing").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.text(), "no such page in acme");
+
+    let response = client.delete("/articles/1").send().await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "GET, HEAD");
+    assert_eq!(response.text(), "nope");
+}
+
+#[tokio::test]
+async fn test_spa_fallback_serves_index_for_unmatched_gets() {
+    let dir = tempfile::tempdir().unwrap();
+    let index = dir.path().join("index.html");
+    std::fs::write(&index, "<div id=\"app\"></div>").unwrap();
+
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/articles/:id", get_article)
+            .spa_fallback("/app", &index),
+    );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/app/settings/profile").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(response.text(), "<div id=\"app\"></div>");
+
+    let response = client.post("/app/settings").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["code"], "NOT_FOUND");
+
+    let response = client.get("/other").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[get("/users/:id<int>")]
+async fn get_user(id: Path<i64>) -> String {
+    format!("user {}", id.into_inner())
+}
+
+#[post("/users")]
+async fn create_user(urls: UrlFor) -> Result<http::Response<rapina::response::BoxBody>> {
+    let location = urls.url("get_user", &[("id", &7)])?;
+    Ok(http::Response::builder()
+        .status(StatusCode::CREATED)
+        .header("location", location)
+        .body(rapina::response::full(""))
+        .unwrap())
+}
+
+#[get("/assets/*path")]
+async fn get_asset(urls: UrlFor) -> String {
+    urls.path("get_asset", &[("path", &"a b/c.txt")]).unwrap()
+}
+
+fn url_router() -> Router {
+    Router::new().group(
+        "/api",
+        Router::new()
+            .get("/users/:id<int>", get_user)
+            .post("/users", create_user)
+            .get("/assets/*path", get_asset),
+    )
+}
+
+#[test]
+fn test_url_for_across_groups() {
+    let router = url_router();
+
+    assert_eq!(
+        router.url_for("get_user", &[("id", &42)]).unwrap(),
+        "/api/users/42"
+    );
+    assert_eq!(
+        router
+            .url_for("get_asset", &[("path", &"docs/read me.md")])
+            .unwrap(),
+        "/api/assets/docs/read%20me.md"
+    );
+    assert_eq!(
+        router.url_for("get_user", &[]),
+        Err(UrlForError::MissingParam {
+            route: "get_user".to_string(),
+            param: "id".to_string()
+        })
+    );
+    assert!(matches!(
+        router.url_for("get_user", &[("id", &"me")]),
+        Err(UrlForError::InvalidParam { .. })
+    ));
+    assert!(matches!(
+        router.url_for("missing", &[]),
+        Err(UrlForError::UnknownRoute(_))
+    ));
+}
+
+#[test]
+fn test_typed_path_helper() {
+    assert_eq!(get_user::path(42), "/users/42");
+    assert_eq!(get_asset::path("a/b c"), "/assets/a/b%20c");
+}
+
+#[tokio::test]
+async fn test_url_for_extractor_uses_host_header() {
+    let app = Rapina::new().with_introspection(false).router(url_router());
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/api/users")
+        .header("host", "api.example.com")
+        .send()
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        "http://api.example.com/api/users/7"
+    );
+
+    let response = client.get("/api/assets/x").send().await;
+    assert_eq!(response.text(), "/api/assets/a%20b/c.txt");
+}
+
+#[tokio::test]
+async fn test_url_for_extractor_prefers_forwarded_headers() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .trust_proxy_headers()
+        .router(url_router());
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/api/users")
+        .header("host", "internal:3000")
+        .header(
+            "forwarded",
+            "for=192.0.2.60;proto=https;host=\"example.com\", for=10.0.0.1",
+        )
+        .send()
+        .await;
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        "https://example.com/api/users/7"
+    );
+
+    let response = client
+        .post("/api/users")
+        .header("host", "internal:3000")
+        .header("x-forwarded-proto", "https")
+        .header("x-forwarded-host", "api.example.org")
+        .send()
+        .await;
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        "https://api.example.org/api/users/7"
+    );
+}
+
+#[tokio::test]
+async fn test_url_for_extractor_ignores_untrusted_forwarded_headers() {
+    let app = Rapina::new().with_introspection(false).router(url_router());
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/api/users")
+        .header("host", "api.example.com")
+        .header("forwarded", "proto=https;host=evil.example")
+        .header("x-forwarded-host", "evil.example")
+        .send()
+        .await;
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        "http://api.example.com/api/users/7"
+    );
+}
+
+#[tokio::test]
+async fn test_url_for_extractor_ignores_invalid_hosts() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .trust_proxy_headers()
+        .router(url_router());
+    let client = TestClient::new(app).await;
+
+    let response = client
+        .post("/api/users")
+        .header("host", "api.example.com")
+        .header("x-forwarded-proto", "javascript")
+        .header("x-forwarded-host", "evil.example/phish?")
+        .send()
+        .await;
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        "http://api.example.com/api/users/7"
+    );
+}
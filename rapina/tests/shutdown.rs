@@ -0,0 +1,144 @@
+//! Integration tests for graceful shutdown.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use rapina::middleware::MiddlewareStack;
+use rapina::prelude::*;
+use rapina::server::{ServerConfig, ShutdownConfig, serve_with_shutdown};
+use rapina::state::AppState;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+fn slow_router(delay: Duration) -> Router {
+    Router::new().route(http::Method::GET, "/slow", move |_, _, _| async move {
+        tokio::time::sleep(delay).await;
+        "done"
+    })
+}
+
+async fn start(
+    router: Router,
+    shutdown: ShutdownConfig,
+) -> (
+    std::net::SocketAddr,
+    oneshot::Sender<()>,
+    tokio::task::JoinHandle<std::io::Result<()>>,
+) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_shutdown(
+        router,
+        AppState::new(),
+        MiddlewareStack::new(),
+        listener,
+        async {
+            let _ = stop_rx.await;
+        },
+        ServerConfig::default(),
+        shutdown,
+    ));
+    (addr, stop_tx, server)
+}
+
+#[tokio::test]
+async fn test_in_flight_request_finishes_before_shutdown() {
+    let hook_ran = Arc::new(AtomicBool::new(false));
+    let flag = hook_ran.clone();
+    let shutdown = ShutdownConfig {
+        grace_period: Duration::from_secs(5),
+        hooks: vec![Box::new(move || {
+            Box::pin(async move { flag.store(true, Ordering::SeqCst) })
+        })],
+    };
+    let (addr, stop, server) = start(slow_router(Duration::from_millis(200)), shutdown).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    stop.send(()).unwrap();
+
+    // The connection is closed once the response is written, so reading to
+    // the end also checks that keep-alive was turned off.
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("done"));
+
+    server.await.unwrap().unwrap();
+    assert!(hook_ran.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_idle_connections_are_closed() {
+    let (addr, stop, server) = start(
+        slow_router(Duration::ZERO),
+        ShutdownConfig {
+            grace_period: Duration::from_secs(5),
+            hooks: Vec::new(),
+        },
+    )
+    .await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = Instant::now();
+    stop.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    let mut buf = Vec::new();
+    assert_eq!(stream.read_to_end(&mut buf).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_grace_period_drops_slow_requests() {
+    let (addr, stop, server) = start(
+        slow_router(Duration::from_secs(30)),
+        ShutdownConfig {
+            grace_period: Duration::from_millis(100),
+            hooks: Vec::new(),
+        },
+    )
+    .await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = Instant::now();
+    stop.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let mut buf = Vec::new();
+    let _ = stream.read_to_end(&mut buf).await;
+    assert!(buf.is_empty());
+}
+
+#[tokio::test]
+async fn test_listen_with_shutdown_runs_hooks() {
+    let hook_ran = Arc::new(AtomicBool::new(false));
+    let flag = hook_ran.clone();
+
+    Rapina::new()
+        .with_introspection(false)
+        .shutdown_timeout(Duration::from_secs(1))
+        .on_shutdown(move || async move { flag.store(true, Ordering::SeqCst) })
+        .router(Router::new())
+        .listen_with_shutdown("127.0.0.1:0", async {})
+        .await
+        .unwrap();
+
+    assert!(hook_ran.load(Ordering::SeqCst));
+}
@@ -0,0 +1,76 @@
+//! Integration tests for Server-Sent Events.
+
+use http::StatusCode;
+use rapina::futures_util::stream::{self, BoxStream, StreamExt};
+use rapina::prelude::*;
+use rapina::sse::{Event, LastEventId, Sse};
+use rapina::testing::TestClient;
+
+#[derive(Serialize)]
+struct Tick {
+    count: u64,
+}
+
+#[get("/ticks")]
+async fn ticks(last: LastEventId) -> Sse<BoxStream<'static, Result<Event>>> {
+    let start: u64 = last
+        .into_inner()
+        .and_then(|id| id.parse().ok())
+        .map(|id: u64| id + 1)
+        .unwrap_or(0);
+    let events = stream::iter(start..start + 2).map(|count| {
+        Ok(Event::new()
+            .event("tick")
+            .id(count.to_string())
+            .json_data(&Tick { count })
+            .unwrap())
+    });
+    Sse::new(events.boxed())
+}
+
+#[tokio::test]
+async fn test_sse_handler_streams_events() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/ticks", ticks));
+
+    let client = TestClient::new(app).await;
+    let response = client.get("/ticks").send().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+    assert_eq!(
+        response.text(),
+        "event: tick\nid: 0\ndata: {\"count\":0}\n\nevent: tick\nid: 1\ndata: {\"count\":1}\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_sse_resumes_from_last_event_id() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/ticks", ticks));
+
+    let client = TestClient::new(app).await;
+    let response = client
+        .get("/ticks")
+        .header("Last-Event-ID", "4")
+        .send()
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().starts_with("event: tick\nid: 5\n"));
+}
+
+#[test]
+fn test_sse_route_documents_event_stream() {
+    let routes = Router::new().get("/ticks", ticks).routes();
+
+    assert_eq!(
+        routes[0].response_content_type.as_deref(),
+        Some("text/event-stream")
+    );
+}
@@ -0,0 +1,314 @@
+//! Integration tests for the test client.
+
+use http::{StatusCode, header};
+use rapina::prelude::*;
+use rapina::response::full;
+use rapina::testing::TestClient;
+use serde::Serialize;
+
+#[post("/login")]
+async fn login() -> http::Response<rapina::response::BoxBody> {
+    http::Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(header::LOCATION, "/dashboard")
+        .header(header::SET_COOKIE, "session=alice; Path=/; HttpOnly")
+        .body(full(""))
+        .unwrap()
+}
+
+#[post("/logout")]
+async fn logout() -> http::Response<rapina::response::BoxBody> {
+    http::Response::builder()
+        .header(header::SET_COOKIE, "session=; Path=/; Max-Age=0")
+        .body(full(""))
+        .unwrap()
+}
+
+#[get("/dashboard")]
+async fn dashboard(headers: Headers) -> Result<String> {
+    let session = headers
+        .get("cookie")
+        .and_then(|cookies| cookies.to_str().ok())
+        .and_then(|cookies| cookies.strip_prefix("session="))
+        .ok_or_else(|| Error::unauthorized("not logged in"))?;
+    Ok(format!("welcome {}", session))
+}
+
+#[get("/me")]
+async fn me(user: CurrentUser) -> String {
+    user.id
+}
+
+fn session_app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .post("/login", login)
+            .post("/logout", logout)
+            .get("/dashboard", dashboard),
+    )
+}
+
+#[tokio::test]
+async fn test_cookies_persist_across_requests() {
+    let client = TestClient::new(session_app()).await;
+
+    let response = client.post("/login").send().await;
+    response.assert_status(StatusCode::SEE_OTHER);
+    assert_eq!(client.cookie("session").as_deref(), Some("alice"));
+
+    client
+        .get("/dashboard")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    client.post("/logout").send().await;
+    assert_eq!(client.cookie("session"), None);
+    client
+        .get("/dashboard")
+        .send()
+        .await
+        .assert_error_code("UNAUTHORIZED");
+
+    client.set_cookie("session", "bob");
+    assert_eq!(client.get("/dashboard").send().await.text(), "welcome bob");
+    client.clear_cookies();
+    client
+        .get("/dashboard")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_follow_redirects() {
+    let client = TestClient::in_process(session_app()).follow_redirects(true);
+
+    let response = client.post("/login").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "welcome alice");
+}
+
+#[tokio::test]
+async fn test_redirect_loop_is_reported() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(
+            Router::new().route(http::Method::GET, "/loop", |_, _, _| async {
+                http::Response::builder()
+                    .status(StatusCode::FOUND)
+                    .header(header::LOCATION, "loop")
+                    .body(full(""))
+                    .unwrap()
+            }),
+        );
+    let client = TestClient::in_process(app).follow_redirects(true);
+
+    let result = tokio::spawn(async move { client.get("/loop").send().await.status() }).await;
+    let panic = result.unwrap_err().into_panic();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("more than 10 redirects"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_as_user_signs_with_the_app_auth_config() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_auth(AuthConfig::new("test-secret", 3600))
+        .router(Router::new().get("/me", me));
+    let client = TestClient::in_process(app);
+
+    let response = client.get("/me").as_user("42").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "42");
+
+    client
+        .get("/me")
+        .bearer("not-a-token")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED)
+        .assert_error_code("UNAUTHORIZED");
+    client
+        .get("/me")
+        .send()
+        .await
+        .assert_error_code("UNAUTHORIZED");
+}
+
+#[tokio::test]
+#[should_panic(expected = "as_user needs an app configured with `with_auth`")]
+async fn test_as_user_without_auth_panics() {
+    let client = TestClient::in_process(session_app());
+    client.get("/me").as_user("42").send().await;
+}
+
+#[tokio::test]
+async fn test_error_assertions() {
+    let client = TestClient::in_process(session_app());
+    let response = client.get("/missing").send().await;
+
+    response
+        .assert_status(StatusCode::NOT_FOUND)
+        .assert_header("content-type", "application/json")
+        .assert_error_code("NOT_FOUND");
+    let error = response.error_response();
+    assert!(!error.trace_id.is_empty());
+}
+
+#[tokio::test]
+#[should_panic(expected = "unexpected error code")]
+async fn test_assert_error_code_mismatch_panics() {
+    let client = TestClient::in_process(session_app());
+    client
+        .get("/missing")
+        .send()
+        .await
+        .assert_error_code("BAD_REQUEST");
+}
+
+#[derive(Serialize, JsonSchema)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+enum UserError {
+    NotFound,
+}
+
+impl IntoApiError for UserError {
+    fn into_api_error(self) -> Error {
+        match self {
+            UserError::NotFound => Error::not_found("user not found"),
+        }
+    }
+}
+
+impl DocumentedError for UserError {
+    fn error_variants() -> Vec<ErrorVariant> {
+        vec![ErrorVariant {
+            status: 404,
+            code: "NOT_FOUND",
+            description: "User not found",
+        }]
+    }
+}
+
+#[get("/users/:id")]
+#[errors(UserError)]
+async fn get_user(id: Path<u64>) -> Result<Json<User>> {
+    match id.into_inner() {
+        0 => Err(UserError::NotFound.into()),
+        id => Ok(Json(User {
+            id,
+            name: "Ann".to_string(),
+        })),
+    }
+}
+
+#[get("/conflict")]
+async fn conflict() -> Result<Json<User>> {
+    Err(Error::conflict("taken"))
+}
+
+fn contract_app() -> Rapina {
+    Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/users/:id", get_user)
+            .get("/conflict", conflict),
+    )
+}
+
+#[tokio::test]
+async fn test_contract_accepts_documented_responses() {
+    let client = TestClient::in_process(contract_app()).contract(true);
+
+    client
+        .get("/users/1")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    client
+        .get("/users/0")
+        .send()
+        .await
+        .assert_error_code("NOT_FOUND");
+    // Requests no route matches aren't part of the contract
+    client
+        .get("/missing")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let coverage = client.coverage();
+    let route = coverage
+        .iter()
+        .find(|route| route.handler_name == "get_user")
+        .unwrap();
+    assert_eq!(route.documented, vec![200, 404]);
+    assert_eq!(route.observed, vec![200, 404]);
+    assert!(route.missing().is_empty());
+}
+
+#[tokio::test]
+#[should_panic(expected = "status 409 is not documented (documented: 200)")]
+async fn test_contract_rejects_undocumented_status() {
+    let client = TestClient::in_process(contract_app()).contract(true);
+    client.get("/conflict").send().await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "body doesn't match the documented schema at $.id")]
+async fn test_contract_rejects_wrong_body() {
+    // Documents a `User`, but returns something else
+    let router = Router::new().route_named(
+        http::Method::GET,
+        "/broken",
+        "broken",
+        Some(serde_json::to_value(rapina::schemars::schema_for!(User)).unwrap()),
+        Vec::new(),
+        |_, _, _| async {
+            http::Response::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(full(r#"{"id":"one","name":"Ann"}"#))
+                .unwrap()
+        },
+    );
+    let client = TestClient::in_process(Rapina::new().router(router)).contract(true);
+    client.get("/broken").send().await;
+}
+
+#[tokio::test]
+async fn test_contract_documents_auth_errors() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_auth(AuthConfig::new("test-secret", 3600))
+        .router(Router::new().get("/me", me));
+    let client = TestClient::new(app).await.contract(true);
+
+    client
+        .get("/me")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    client
+        .get("/me")
+        .as_user("1")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(client.coverage()[0].missing().is_empty());
+}
+
+#[tokio::test]
+async fn test_response_snapshots() {
+    let client = TestClient::in_process(contract_app());
+
+    client.get("/users/7").send().await.assert_snapshot("user");
+    client
+        .get("/users/0")
+        .send()
+        .await
+        .assert_snapshot("errors/user_not_found");
+}
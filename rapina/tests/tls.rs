@@ -0,0 +1,311 @@
+#![cfg(feature = "tls")]
+
+//! Integration tests for TLS termination, mTLS and certificate reloading.
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{Request, StatusCode, Version};
+use http_body_util::{BodyExt, Empty};
+use hyper_util::rt::TokioIo;
+use rapina::middleware::MiddlewareStack;
+use rapina::prelude::*;
+use rapina::server::{ShutdownConfig, serve_with_shutdown};
+use rapina::state::AppState;
+use rapina::tls::{PeerCertificate, TlsConfig};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, Issuer, KeyPair};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::{TlsConnector, rustls::crypto::ring};
+
+#[get("/hello")]
+async fn hello() -> &'static str {
+    "Hello!"
+}
+
+#[get("/whoami")]
+async fn whoami(cert: PeerCertificate) -> String {
+    format!("{} {}", cert.chain().len(), cert.der().len())
+}
+
+struct ServerCert {
+    der: CertificateDer<'static>,
+}
+
+/// Writes a self-signed certificate for `localhost` to `cert.pem`/`key.pem`.
+fn write_server_cert(dir: &Path) -> ServerCert {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    std::fs::write(dir.join("cert.pem"), cert.cert.pem()).unwrap();
+    std::fs::write(dir.join("key.pem"), cert.signing_key.serialize_pem()).unwrap();
+    ServerCert {
+        der: cert.cert.der().clone(),
+    }
+}
+
+struct ClientIdentity {
+    certs: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+/// Writes a client CA to `ca.pem` and returns a client certificate it signed.
+fn write_client_ca(dir: &Path) -> ClientIdentity {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+    std::fs::write(dir.join("ca.pem"), ca_cert.pem()).unwrap();
+
+    let issuer = Issuer::new(ca_params, ca_key);
+    let client_key = KeyPair::generate().unwrap();
+    let client_cert = CertificateParams::new(vec!["client".to_string()])
+        .unwrap()
+        .signed_by(&client_key, &issuer)
+        .unwrap();
+
+    ClientIdentity {
+        certs: vec![client_cert.der().clone()],
+        key: PrivateKeyDer::try_from(client_key.serialize_der()).unwrap(),
+    }
+}
+
+async fn start(tls: TlsConfig) -> (SocketAddr, oneshot::Sender<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+
+    let router = Router::new().get("/hello", hello).get("/whoami", whoami);
+    router.compile().unwrap();
+
+    tokio::spawn(serve_with_shutdown(
+        router,
+        AppState::new(),
+        MiddlewareStack::new(),
+        listener,
+        async {
+            let _ = stop_rx.await;
+        },
+        ServerConfig::new().tls(tls),
+        ShutdownConfig::default(),
+    ));
+    (addr, stop_tx)
+}
+
+async fn connect(
+    addr: SocketAddr,
+    trusted: &CertificateDer<'static>,
+    identity: Option<&ClientIdentity>,
+    alpn: &[&[u8]],
+) -> std::io::Result<TlsStream<TcpStream>> {
+    let mut roots = RootCertStore::empty();
+    roots.add(trusted.clone()).unwrap();
+
+    let builder = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+    let mut config = match identity {
+        Some(identity) => builder
+            .with_client_auth_cert(identity.certs.clone(), identity.key.clone_key())
+            .unwrap(),
+        None => builder.with_no_client_auth(),
+    };
+    config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+
+    let stream = TcpStream::connect(addr).await?;
+    TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+}
+
+async fn get_http1(
+    stream: TlsStream<TcpStream>,
+    path: &str,
+) -> hyper::Result<(StatusCode, String)> {
+    let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+    tokio::spawn(conn);
+    let request = Request::get(path)
+        .header("host", "localhost")
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+    let response = sender.send_request(request).await?;
+    let status = response.status();
+    let body = response.into_body().collect().await?.to_bytes();
+    Ok((status, String::from_utf8_lossy(&body).to_string()))
+}
+
+fn served_cert(stream: &TlsStream<TcpStream>) -> CertificateDer<'static> {
+    stream.get_ref().1.peer_certificates().unwrap()[0].clone()
+}
+
+#[tokio::test]
+async fn test_https_http1() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = write_server_cert(dir.path());
+    let (addr, _stop) = start(TlsConfig::new(
+        dir.path().join("cert.pem"),
+        dir.path().join("key.pem"),
+    ))
+    .await;
+
+    let stream = connect(addr, &server.der, None, &[b"http/1.1"])
+        .await
+        .unwrap();
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"http/1.1"[..]));
+
+    let (status, body) = get_http1(stream, "/hello").await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "Hello!");
+}
+
+#[tokio::test]
+async fn test_https_negotiates_h2() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = write_server_cert(dir.path());
+    let (addr, _stop) = start(TlsConfig::new(
+        dir.path().join("cert.pem"),
+        dir.path().join("key.pem"),
+    ))
+    .await;
+
+    let stream = connect(addr, &server.der, None, &[b"h2", b"http/1.1"])
+        .await
+        .unwrap();
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+
+    let (mut sender, conn) = hyper::client::conn::http2::handshake(
+        hyper_util::rt::TokioExecutor::new(),
+        TokioIo::new(stream),
+    )
+    .await
+    .unwrap();
+    tokio::spawn(conn);
+    let request = Request::get("https://localhost/hello")
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+
+    assert_eq!(response.version(), Version::HTTP_2);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_mtls_requires_client_cert() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = write_server_cert(dir.path());
+    let client = write_client_ca(dir.path());
+    let (addr, _stop) = start(
+        TlsConfig::new(dir.path().join("cert.pem"), dir.path().join("key.pem"))
+            .require_client_cert(dir.path().join("ca.pem")),
+    )
+    .await;
+
+    // With TLS 1.3 the server rejects the missing certificate after the
+    // client considers the handshake done, so the failure may surface on
+    // the first request instead.
+    let rejected = match connect(addr, &server.der, None, &[]).await {
+        Ok(stream) => get_http1(stream, "/hello").await.is_err(),
+        Err(_) => true,
+    };
+    assert!(rejected);
+
+    let stream = connect(addr, &server.der, Some(&client), &[])
+        .await
+        .unwrap();
+    let (status, body) = get_http1(stream, "/whoami").await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, format!("1 {}", client.certs[0].len()));
+}
+
+#[tokio::test]
+async fn test_optional_client_cert_extractor_rejects_anonymous() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = write_server_cert(dir.path());
+    write_client_ca(dir.path());
+    let (addr, _stop) = start(
+        TlsConfig::new(dir.path().join("cert.pem"), dir.path().join("key.pem"))
+            .request_client_cert(dir.path().join("ca.pem")),
+    )
+    .await;
+
+    let stream = connect(addr, &server.der, None, &[]).await.unwrap();
+    let (status, _) = get_http1(stream, "/whoami").await.unwrap();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let stream = connect(addr, &server.der, None, &[]).await.unwrap();
+    let (status, _) = get_http1(stream, "/hello").await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_certificate_hot_reload_keeps_connections() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = write_server_cert(dir.path());
+    let (addr, _stop) = start(
+        TlsConfig::new(dir.path().join("cert.pem"), dir.path().join("key.pem"))
+            .reload_interval(Duration::from_millis(20)),
+    )
+    .await;
+
+    let stream = connect(addr, &first.der, None, &[]).await.unwrap();
+    assert_eq!(served_cert(&stream), first.der);
+    let (mut old_sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    tokio::spawn(conn);
+
+    // Make sure the new files get a different modification time
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let second = write_server_cert(dir.path());
+
+    let mut reloaded = None;
+    for _ in 0..100 {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        if let Ok(stream) = connect(addr, &second.der, None, &[]).await {
+            reloaded = Some(stream);
+            break;
+        }
+    }
+    let stream = reloaded.expect("certificate was not reloaded");
+    assert_eq!(served_cert(&stream), second.der);
+    let (status, _) = get_http1(stream, "/hello").await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+
+    // The connection established before the reload is still usable
+    let request = Request::get("/hello")
+        .header("host", "localhost")
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+    let response = old_sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_invalid_certificate_fails_to_start() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("cert.pem"), "").unwrap();
+    std::fs::write(dir.path().join("key.pem"), "").unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let err = serve_with_shutdown(
+        Router::new(),
+        AppState::new(),
+        MiddlewareStack::new(),
+        listener,
+        std::future::pending(),
+        ServerConfig::new().tls(TlsConfig::new(
+            dir.path().join("cert.pem"),
+            dir.path().join("key.pem"),
+        )),
+        ShutdownConfig::default(),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("no certificates found"));
+}
@@ -0,0 +1,315 @@
+//! Integration tests for tower and hyper interoperability.
+
+use http::{HeaderValue, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use rapina::bytes::Bytes;
+use rapina::extract::RequestBody;
+use rapina::middleware::TowerLayerMiddleware;
+use rapina::prelude::*;
+use rapina::response::{BoxBody, full};
+use rapina::testing::TestClient;
+use tower::{Layer, ServiceExt};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::set_header::SetResponseHeaderLayer;
+
+#[post("/echo")]
+async fn echo(body: Json<serde_json::Value>) -> Json<serde_json::Value> {
+    body
+}
+
+#[get("/hello")]
+async fn hello() -> &'static str {
+    "hello"
+}
+
+fn powered_by(value: &'static str) -> SetResponseHeaderLayer<HeaderValue> {
+    SetResponseHeaderLayer::overriding(
+        header::HeaderName::from_static("x-powered-by"),
+        HeaderValue::from_static(value),
+    )
+}
+
+async fn body_text(response: http::Response<BoxBody>) -> String {
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_any_body_service_handles_requests() {
+    let service = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().post("/echo", echo))
+        .into_service()
+        .any_body();
+
+    let request = http::Request::post("/echo")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(r#"{"msg":"ping"}"#)))
+        .unwrap();
+    let response = service.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_text(response).await, r#"{"msg":"ping"}"#);
+
+    let request = http::Request::get("/missing")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[derive(Clone)]
+struct Tenant(&'static str);
+
+#[tokio::test]
+async fn test_any_body_service_keeps_extensions() {
+    let service = Rapina::new()
+        .with_introspection(false)
+        .router(
+            Router::new().route(http::Method::GET, "/tenant", |req, _, _| async move {
+                req.extensions()
+                    .get::<Tenant>()
+                    .map(|tenant| tenant.0)
+                    .unwrap_or("none")
+            }),
+        )
+        .into_service()
+        .any_body();
+
+    let mut request = http::Request::get("/tenant")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    request.extensions_mut().insert(Tenant("acme"));
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(body_text(response).await, "acme");
+}
+
+#[tokio::test]
+async fn test_service_can_be_wrapped_in_tower_layers() {
+    let service = powered_by("tower").layer(
+        Rapina::new()
+            .with_introspection(false)
+            .router(Router::new().get("/hello", hello))
+            .into_service()
+            .any_body(),
+    );
+
+    let request = http::Request::get("/hello")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(response.headers().get("x-powered-by").unwrap(), "tower");
+    assert_eq!(body_text(response).await, "hello");
+}
+
+#[tokio::test]
+async fn test_service_serves_hyper_connections() {
+    let service = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/hello", hello))
+        .into_service();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let _ = hyper::server::conn::http1::Builder::new()
+            .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+            .await;
+    });
+
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let (mut sender, conn) =
+        hyper::client::conn::http1::handshake(hyper_util::rt::TokioIo::new(stream))
+            .await
+            .unwrap();
+    tokio::spawn(conn);
+    let request = http::Request::get("/hello")
+        .header(header::HOST, "localhost")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "hello");
+}
+
+#[tokio::test]
+async fn test_tower_layer_middleware() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TowerLayerMiddleware::new(powered_by("global")))
+        .router(Router::new().get("/hello", hello));
+
+    let client = TestClient::new(app).await;
+    let response = client.get("/hello").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("x-powered-by").unwrap(), "global");
+    assert_eq!(response.text(), "hello");
+}
+
+#[tokio::test]
+async fn test_tower_layer_changing_the_body_type() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TowerLayerMiddleware::new(RequestBodyLimitLayer::new(8)))
+        .router(Router::new().post("/echo", echo));
+
+    let client = TestClient::new(app).await;
+    let response = client.post("/echo").json(&"short").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json::<String>(), "short");
+
+    let response = client.post("/echo").json(&"far too long").send().await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_tower_layer_body_limit_on_streamed_body() {
+    let service = Rapina::new()
+        .with_introspection(false)
+        .middleware(TowerLayerMiddleware::new(RequestBodyLimitLayer::new(8)))
+        .router(Router::new().post("/echo", echo))
+        .into_service()
+        .any_body();
+
+    // No Content-Length, so the limit is only hit while reading the body
+    let chunks = rapina::futures_util::stream::iter([Ok::<_, std::convert::Infallible>(
+        hyper::body::Frame::data(Bytes::from(r#""far too long""#)),
+    )]);
+    let request = http::Request::post("/echo")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(http_body_util::StreamBody::new(chunks))
+        .unwrap();
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+/// Numbers the requests its service sees.
+struct CountLayer;
+
+impl<S> Layer<S> for CountLayer {
+    type Service = Count<S>;
+
+    fn layer(&self, inner: S) -> Count<S> {
+        Count { inner, seen: 0 }
+    }
+}
+
+struct Count<S> {
+    inner: S,
+    seen: usize,
+}
+
+impl<S, B> tower::Service<http::Request<B>> for Count<S>
+where
+    S: tower::Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        self.seen += 1;
+        req.headers_mut()
+            .insert("x-seen", HeaderValue::from(self.seen));
+        self.inner.call(req)
+    }
+}
+
+#[tokio::test]
+async fn test_tower_layer_service_is_shared_between_requests() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(TowerLayerMiddleware::new(CountLayer))
+        .router(
+            Router::new().route(http::Method::GET, "/seen", |req, _, _| async move {
+                req.headers()["x-seen"].to_str().unwrap().to_string()
+            }),
+        );
+
+    let client = TestClient::in_process(app);
+    for expected in ["1", "2", "3"] {
+        assert_eq!(client.get("/seen").send().await.text(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_tower_layer_on_router_group() {
+    let router = Router::new().get("/hello", hello).group(
+        "/admin",
+        Router::new()
+            .get("/hello", hello)
+            .layer(TowerLayerMiddleware::new(powered_by("admin"))),
+    );
+
+    let routes = router.routes();
+    assert!(routes[0].middleware.is_empty());
+    assert_eq!(routes[1].middleware, vec!["SetResponseHeaderLayer"]);
+
+    let client = TestClient::new(Rapina::new().with_introspection(false).router(router)).await;
+    let response = client.get("/admin/hello").send().await;
+    assert_eq!(response.headers().get("x-powered-by").unwrap(), "admin");
+    let response = client.get("/hello").send().await;
+    assert!(response.headers().get("x-powered-by").is_none());
+}
+
+#[tokio::test]
+async fn test_mount_tower_service() {
+    let legacy = tower::service_fn(|req: http::Request<RequestBody>| async move {
+        let body = format!("{} {}", req.method(), req.uri());
+        Ok::<_, std::convert::Infallible>(http::Response::new(full(body)))
+    });
+
+    let app = Rapina::new().with_introspection(false).router(
+        Router::new()
+            .get("/legacy/hello", hello)
+            .mount("/legacy", legacy)
+            .group("/v1/orgs/:org", Router::new().mount("/old", legacy)),
+    );
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/legacy/users?page=2").send().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "GET /users?page=2");
+
+    let response = client.delete("/legacy").send().await;
+    assert_eq!(response.text(), "DELETE /");
+
+    let response = client.get("/legacy/hello").send().await;
+    assert_eq!(response.text(), "hello");
+
+    let response = client.post("/v1/orgs/acme/old/items").send().await;
+    assert_eq!(response.text(), "POST /items");
+
+    let response = client.get("/other").send().await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_mount_tower_service_at_root() {
+    let legacy = tower::service_fn(|req: http::Request<RequestBody>| async move {
+        let body = format!("{} {}", req.method(), req.uri());
+        Ok::<_, std::convert::Infallible>(http::Response::new(full(body)))
+    });
+
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/hello", hello).mount("/", legacy));
+    let client = TestClient::new(app).await;
+
+    let response = client.get("/users/1?page=2").send().await;
+    assert_eq!(response.text(), "GET /users/1?page=2");
+
+    let response = client.get("/").send().await;
+    assert_eq!(response.text(), "GET /");
+
+    let response = client.get("/hello").send().await;
+    assert_eq!(response.text(), "hello");
+}
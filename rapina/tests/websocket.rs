@@ -0,0 +1,168 @@
+//! Integration tests for WebSocket upgrades.
+
+use http::StatusCode;
+use rapina::auth::AuthMiddleware;
+use rapina::futures_util::{SinkExt, StreamExt};
+use rapina::prelude::*;
+use rapina::testing::TestClient;
+use rapina::websocket::{Message, WebSocket};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest};
+
+#[ws("/echo")]
+async fn echo(mut socket: WebSocket) {
+    while let Some(Ok(message)) = socket.recv().await {
+        match message {
+            Message::Text(_) | Message::Binary(_) => {
+                if socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Greeting {
+    user: String,
+}
+
+#[ws("/me")]
+async fn me(mut socket: WebSocket, user: CurrentUser) {
+    let _ = socket.send_json(&Greeting { user: user.id }).await;
+}
+
+async fn connect(
+    client: &TestClient,
+    path: &str,
+    token: Option<&str>,
+) -> std::result::Result<tokio_tungstenite::WebSocketStream<TcpStream>, tungstenite::Error> {
+    let mut request = format!("ws://{}{}", client.addr(), path)
+        .into_client_request()
+        .unwrap();
+    if let Some(token) = token {
+        request.headers_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+    }
+    let stream = TcpStream::connect(client.addr()).await.unwrap();
+    let (socket, _) = tokio_tungstenite::client_async(request, stream).await?;
+    Ok(socket)
+}
+
+#[tokio::test]
+async fn test_websocket_echo() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/echo", echo));
+
+    let client = TestClient::new(app).await;
+    let mut socket = connect(&client, "/echo", None).await.unwrap();
+
+    socket
+        .send(tungstenite::Message::text("hello"))
+        .await
+        .unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    assert_eq!(reply, tungstenite::Message::text("hello"));
+
+    socket
+        .send(tungstenite::Message::binary(vec![1, 2, 3]))
+        .await
+        .unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    assert_eq!(reply, tungstenite::Message::binary(vec![1, 2, 3]));
+
+    socket.close(None).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_websocket_route_rejects_plain_get() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/echo", echo));
+
+    let client = TestClient::new(app).await;
+    let response = client.get("/echo").send().await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_websocket_upgrade_runs_auth_middleware() {
+    let auth = AuthConfig::new("test-secret", 3600);
+    let token = auth.create_token("user-42").unwrap();
+
+    let app = Rapina::new()
+        .with_introspection(false)
+        .middleware(AuthMiddleware::new(auth))
+        .router(Router::new().get("/me", me));
+
+    let client = TestClient::new(app).await;
+
+    match connect(&client, "/me", None).await {
+        Err(tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        other => panic!(
+            "expected the handshake to be rejected, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+
+    let mut socket = connect(&client, "/me", Some(&token)).await.unwrap();
+    let message = socket.next().await.unwrap().unwrap();
+    let greeting: Greeting = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    assert_eq!(
+        greeting,
+        Greeting {
+            user: "user-42".to_string()
+        }
+    );
+}
+
+#[public]
+#[ws("/lobby")]
+async fn lobby(mut socket: WebSocket) {
+    let _ = socket.send(Message::Text("welcome".into())).await;
+}
+
+#[tokio::test]
+async fn test_public_websocket_skips_auth() {
+    let app = Rapina::new()
+        .with_introspection(false)
+        .with_auth(AuthConfig::new("test-secret", 3600))
+        .router(Router::new().get("/lobby", lobby).get("/me", me));
+
+    let client = TestClient::new(app).await;
+
+    let mut socket = connect(&client, "/lobby", None).await.unwrap();
+    let message = socket.next().await.unwrap().unwrap();
+    assert_eq!(message, tungstenite::Message::text("welcome"));
+    assert!(connect(&client, "/me", None).await.is_err());
+}
+
+#[ws("/rooms/:room")]
+async fn join_room(mut socket: WebSocket, mut room: Path<String>) {
+    room.0.insert_str(0, "room ");
+    let _ = socket.send(Message::Text(room.into_inner())).await;
+}
+
+#[tokio::test]
+async fn test_websocket_path_params_and_helper() {
+    assert_eq!(join_room::path("a b"), "/rooms/a%20b");
+
+    let app = Rapina::new()
+        .with_introspection(false)
+        .router(Router::new().get("/rooms/:room", join_room));
+    let client = TestClient::new(app).await;
+
+    let mut socket = connect(&client, &join_room::path("lobby"), None)
+        .await
+        .unwrap();
+    let message = socket.next().await.unwrap().unwrap();
+    assert_eq!(message, tungstenite::Message::text("room lobby"));
+}